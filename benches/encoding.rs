@@ -0,0 +1,70 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use minecraft_console_client::protocol::{
+    encoding::{Decode, VarInt},
+    packets::{login::LoginSuccess, Handshake},
+    Packet,
+};
+
+/// A spread of values covering every encoded var-int length.
+const VAR_INTS: [i32; 6] = [0, 127, 25565, 2_097_151, i32::MAX, -1];
+
+fn var_int(c: &mut Criterion) {
+    let mut group = c.benchmark_group("var_int");
+    group.throughput(Throughput::Elements(VAR_INTS.len() as u64));
+
+    group.bench_function("encode", |b| {
+        b.iter(|| {
+            for value in VAR_INTS {
+                black_box(VarInt::from(black_box(value)).as_slice().len());
+            }
+        });
+    });
+
+    let encoded: Vec<u8> = VAR_INTS
+        .iter()
+        .flat_map(|value| VarInt::from(*value).as_slice().to_vec())
+        .collect();
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            let mut reader = black_box(encoded.as_slice());
+            for _ in VAR_INTS {
+                black_box(i32::from(VarInt::decode(&mut reader).unwrap()));
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn packet(c: &mut Criterion) {
+    let mut group = c.benchmark_group("packet");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("assemble_handshake", |b| {
+        b.iter(|| {
+            let packet: Packet =
+                Handshake::new(764, black_box("localhost".to_string()), 25565, true)
+                    .unwrap()
+                    .into();
+            black_box(Vec::try_from(packet).unwrap());
+        });
+    });
+
+    let body = vec![0x2a; 1024];
+    group.bench_function("assemble_1kb", |b| {
+        b.iter(|| {
+            let packet = Packet::new(0x05, black_box(body.clone()));
+            black_box(Vec::try_from(packet).unwrap());
+        });
+    });
+
+    let login_success = [[0; 16].as_slice(), b"\x05Steve", &[0]].concat();
+    group.bench_function("decode_login_success", |b| {
+        b.iter(|| black_box(LoginSuccess::decode(black_box(&login_success), 764).unwrap()));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, var_int, packet);
+criterion_main!(benches);
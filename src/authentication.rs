@@ -1,17 +1,21 @@
 use std::{
     collections::HashMap,
+    future::Future,
     io::{self, BufRead, Write},
+    time::Instant,
 };
 
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::cache::Cache;
+use crate::{cache::Cache, clock::Clock, http::HttpBackend};
 
 /// The Azure Application client ID
 const CLIENT_ID: &str = "54473e32-df8f-42e9-a649-9419b0dab9d3";
 
+/// How many authorization codes the user may enter before signing in gives up, by default.
+pub const DEFAULT_AUTH_MAX_ATTEMPTS: u32 = 3;
+
 /// The response from authenticating with Microsoft OAuth flow
 #[derive(Deserialize, Serialize)]
 struct MicrosoftTokenAuthorizeResponse {
@@ -42,7 +46,7 @@ struct XboxLiveAuthenticationResponse {
     /// The xbox authentication token to use
     token: String,
     /// An object that contains a vec of `uhs` objects
-    /// Looks like { "xui": [{"uhs": "xbl_token"}] }
+    /// Looks like `{ "xui": [{"uhs": "xbl_token"}] }`
     display_claims: HashMap<String, Vec<HashMap<String, String>>>,
 }
 
@@ -60,12 +64,50 @@ struct MinecraftAuthenticationResponse {
 }
 
 /// The response from Minecraft when attempting to retrieve a users profile
-#[derive(Serialize, Deserialize, Debug)]
-struct MinecraftProfileResponse {
-    /// The UUID of the account
-    id: String,
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftProfile {
+    /// The UUID of the account, without hyphens
+    pub id: String,
     /// The name of the user
-    name: String,
+    pub name: String,
+}
+
+/// A Minecraft token, with the profile of the account it is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftSession {
+    pub minecraft_token: String,
+    pub profile: MinecraftProfile,
+}
+
+/// The URLs of the services used during authentication.
+#[derive(Clone, Debug)]
+pub struct Endpoints {
+    /// Where Microsoft authorization codes and refresh tokens are exchanged for tokens
+    pub microsoft_token: String,
+    /// Where a Microsoft token is exchanged for an Xbox Live token
+    pub xbox_authenticate: String,
+    /// Where an Xbox Live token is exchanged for an Xbox security token
+    pub xsts_authorize: String,
+    /// Where an Xbox security token is exchanged for a Minecraft token
+    pub minecraft_login: String,
+    /// Where the profile of a Minecraft token's account is retrieved
+    pub minecraft_profile: String,
+}
+
+impl std::default::Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            microsoft_token: String::from(
+                "https://login.microsoftonline.com/consumers/oauth2/v2.0/token",
+            ),
+            xbox_authenticate: String::from("https://user.auth.xboxlive.com/user/authenticate"),
+            xsts_authorize: String::from("https://xsts.auth.xboxlive.com/xsts/authorize"),
+            minecraft_login: String::from(
+                "https://api.minecraftservices.com/authentication/login_with_xbox",
+            ),
+            minecraft_profile: String::from("https://api.minecraftservices.com/minecraft/profile"),
+        }
+    }
 }
 
 pub struct TokenResult {
@@ -73,162 +115,792 @@ pub struct TokenResult {
     pub retrieve_type: RetrieveType,
 }
 
+impl TokenResult {
+    /// Whether the token came from the cache, rather than from the user logging in, see
+    /// [`RetrieveType::used_cache`].
+    pub fn used_cache(&self) -> bool {
+        self.retrieve_type.used_cache()
+    }
+}
+
 pub enum RetrieveType {
+    /// The cached Minecraft token was still valid
     FromCache,
+    /// The user logged in interactively
     FromUserLogin {
         microsoft_refresh_token: String,
         expires_in: u32,
     },
+    /// The cached Microsoft refresh token was exchanged for a new token, without the user
+    FromRefresh {
+        microsoft_refresh_token: String,
+        expires_in: u32,
+    },
 }
 
-async fn microsoft_authenticate_token<T>(
-    client: &Client,
-    data: T,
-) -> Result<MicrosoftTokenAuthorizeResponse, Box<dyn std::error::Error>>
+impl RetrieveType {
+    /// Whether the cached Minecraft token was used as it was, without contacting Microsoft.
+    pub fn is_from_cache(&self) -> bool {
+        matches!(self, Self::FromCache)
+    }
+
+    /// Whether the cache was used to get the token, either as the token itself or as the
+    /// Microsoft refresh token it was refreshed with, so the user did not have to log in.
+    pub fn used_cache(&self) -> bool {
+        match self {
+            Self::FromCache | Self::FromRefresh { .. } => true,
+            Self::FromUserLogin { .. } => false,
+        }
+    }
+}
+
+/// Waits for the request of an authentication `step`, logging how long it took at debug level so
+/// slow steps can be told apart.
+async fn timed<T>(step: &str, request: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let response = request.await;
+    tracing::debug!("{step} took {}ms", start.elapsed().as_millis());
+
+    response
+}
+
+async fn microsoft_authenticate_token<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    data: &[(&str, &str)],
+) -> Result<MicrosoftTokenAuthorizeResponse, Box<dyn std::error::Error>> {
+    timed(
+        "Microsoft token",
+        client.post_form(&endpoints.microsoft_token, data),
+    )
+    .await?
+    .json()
+}
+
+/// Finds the authorization code in what the user entered, without the whitespace around it. The
+/// whole redirect URL is often pasted in place of the code, so the URL's `code` is taken from it.
+fn parse_auth_code(input: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("no authorization code was entered".into());
+    }
+    if !input.contains("code=") {
+        return Ok(input.to_string());
+    }
+
+    reqwest::Url::parse(input)
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "code")
+                .map(|(_, code)| code.into_owned())
+        })
+        .filter(|code| !code.is_empty())
+        .ok_or_else(|| format!("could not find the authorization code in '{input}'").into())
+}
+
+/// Reads an authorization code from `reader`, as [`parse_auth_code`] finds it in the line.
+///
+/// The user is only prompted for the code if the input is `interactive` (e.g., a terminal), and
+/// is prompted again after entering nothing or something with no code in it. Otherwise the code
+/// is read from the input as it was piped in, and the input ending before a code is an error, as
+/// no one is there to type one.
+fn get_auth_code<R>(mut reader: R, interactive: bool) -> Result<String, Box<dyn std::error::Error>>
 where
-    T: Serialize + Sized,
+    R: BufRead,
 {
-    let authorization_token = client
-        .post("https://login.microsoftonline.com/consumers/oauth2/v2.0/token")
-        .form(&data)
-        .send()
-        .await?
-        .json()
-        .await?;
+    loop {
+        if interactive {
+            print!("Authorization code: ");
+            io::stdout().flush()?;
+        }
+
+        let mut buffer = String::new();
+        if reader.read_line(&mut buffer)? == 0 && interactive {
+            return Err("the input ended before an authorization code was entered".into());
+        }
+
+        if !interactive && buffer.trim().is_empty() {
+            return Err(
+                "no authorization code was piped in, and stdin is not a terminal to ask for one"
+                    .into(),
+            );
+        }
 
-    Ok(authorization_token)
+        match parse_auth_code(&buffer) {
+            Err(e) if interactive => println!("{e}, try again"),
+            result => return result,
+        }
+    }
 }
 
-fn get_auth_code<R>(mut reader: R) -> Result<String, Box<dyn std::error::Error>>
+/// Prompts the user for an authorization code and exchanges it for a Microsoft token, prompting
+/// again when the code is rejected (e.g., because it was mistyped), up to `max_attempts` codes.
+///
+/// The codes are read without prompting unless `reader` is `interactive`, see [`get_auth_code`].
+async fn exchange_auth_code<H, R>(
+    client: &H,
+    endpoints: &Endpoints,
+    mut reader: R,
+    interactive: bool,
+    max_attempts: u32,
+) -> Result<MicrosoftTokenAuthorizeResponse, Box<dyn std::error::Error>>
 where
+    H: HttpBackend,
     R: BufRead,
 {
-    print!("Authorization code: ");
-    io::stdout().flush()?;
+    let mut attempts = 0;
 
-    let mut buffer = String::new();
-    reader.read_line(&mut buffer)?;
+    loop {
+        let code = get_auth_code(&mut reader, interactive)?;
+        attempts += 1;
 
-    Ok(buffer)
+        match microsoft_authenticate_token(
+            client,
+            endpoints,
+            &[
+                ("client_id", CLIENT_ID),
+                ("code", &code),
+                ("grant_type", "authorization_code"),
+                ("redirect_uri", "https://mccteam.github.io/redirect.html"),
+            ],
+        )
+        .await
+        {
+            Ok(token) => return Ok(token),
+            Err(e) if attempts >= max_attempts => {
+                return Err(format!(
+                    "gave up after {attempts} authorization codes did not work: {e}"
+                )
+                .into())
+            }
+            Err(e) => {
+                tracing::warn!("Authorization code did not work: {e}");
+                println!("That authorization code did not work, please try again.");
+            }
+        }
+    }
 }
 
 /// Attempts to authenticate with Mojang and Minecraft servers, using the current cache if it exists.
 /// Returns the Minecraft token.
-pub async fn authenticate<R>(
-    client: &Client,
+///
+/// When the user has to log in, they are prompted for an authorization code up to `max_attempts`
+/// times before giving up. The codes are read from `reader`, which is only prompted on if it is
+/// `interactive` (e.g., stdin is a terminal rather than piped).
+pub async fn authenticate<H, R, C>(
+    client: &H,
+    endpoints: &Endpoints,
     reader: R,
+    interactive: bool,
+    max_attempts: u32,
     cache: Option<&Cache>,
+    clock: &C,
 ) -> Result<TokenResult, Box<dyn std::error::Error>>
 where
+    H: HttpBackend,
     R: BufRead,
+    C: Clock,
 {
+    // a cache without the selected account is treated as if there was no cache
+    let cache = cache.filter(|cache| cache.has_selected_account());
+
     // if the cache exists, let's check to see if the minecraft token has expired or not
     if let Some(cache) = cache {
-        let cached_token = cache.get_minecraft_token();
+        let cached_token = cache.get_minecraft_token(clock);
 
         if let Some(token) = cached_token {
-            println!("Cached token was valid!");
+            tracing::info!("Cached token was valid!");
             return Ok(TokenResult {
                 minecraft_token: token,
                 retrieve_type: RetrieveType::FromCache,
             });
         }
 
-        println!("Cached token was invalid, generating a new token...");
+        tracing::info!("Cached token was invalid, generating a new token...");
     }
 
     // step 1: get authorization token
     // if the cache exists, we can use the microsoft `refresh_token` to skip user authorization again
-    let authorization_token = if let Some(cache) = cache {
-        microsoft_authenticate_token(
+    if let Some(cache) = cache {
+        return refresh(client, endpoints, cache.get_microsoft_refresh_token()).await;
+    }
+
+    // attempt to login to microsoft account (OAuth flow)
+    // requires authorization from the user
+    println!("Please login with your Microsoft account in the following link and retrieve the authorization code: https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize?client_id={CLIENT_ID}&response_type=code&scope=XboxLive.signin%20offline_access");
+
+    // retrieve the code from them the user
+    let authorization_token =
+        exchange_auth_code(client, endpoints, reader, interactive, max_attempts).await?;
+
+    Ok(TokenResult {
+        minecraft_token: authenticate_with_microsoft(
             client,
-            vec![
-                ("client_id", CLIENT_ID),
-                ("refresh_token", cache.get_microsoft_refresh_token()),
-                ("grant_type", "refresh_token"),
-                ("redirect_uri", "https://mccteam.github.io/redirect.html"),
-            ],
+            endpoints,
+            &authorization_token.access_token,
         )
-        .await?
-    } else {
-        // attempt to login to microsoft account (OAuth flow)
-        // requires authorization from the user
-        println!("Please login with your Microsoft account in the following link and retrieve the authorization code: https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize?client_id={client_id}&response_type=code&scope=XboxLive.signin%20offline_access", client_id=CLIENT_ID);
+        .await?,
+        retrieve_type: RetrieveType::FromUserLogin {
+            microsoft_refresh_token: authorization_token.refresh_token,
+            expires_in: authorization_token.expires_in,
+        },
+    })
+}
 
-        // retrieve the code from them the user
-        let code = get_auth_code(reader)?;
+/// Exchanges a Microsoft `refresh_token` for a new Minecraft token, without any interaction from
+/// the user.
+pub async fn refresh<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    microsoft_refresh_token: &str,
+) -> Result<TokenResult, Box<dyn std::error::Error>> {
+    let authorization_token = microsoft_authenticate_token(
+        client,
+        endpoints,
+        &[
+            ("client_id", CLIENT_ID),
+            ("refresh_token", microsoft_refresh_token),
+            ("grant_type", "refresh_token"),
+            ("redirect_uri", "https://mccteam.github.io/redirect.html"),
+        ],
+    )
+    .await?;
 
-        microsoft_authenticate_token(
+    Ok(TokenResult {
+        minecraft_token: authenticate_with_microsoft(
             client,
-            vec![
-                ("client_id", CLIENT_ID),
-                ("code", &code),
-                ("grant_type", "authorization_code"),
-                ("redirect_uri", "https://mccteam.github.io/redirect.html"),
-            ],
+            endpoints,
+            &authorization_token.access_token,
         )
-        .await?
-    };
+        .await?,
+        retrieve_type: RetrieveType::FromRefresh {
+            microsoft_refresh_token: authorization_token.refresh_token,
+            expires_in: authorization_token.expires_in,
+        },
+    })
+}
+
+/// Exchanges the cached Microsoft refresh token of the selected account in `cache` for a new
+/// Minecraft token, even if the cached Minecraft token has not yet expired.
+///
+/// Fails without making any request if no refresh token is cached, as the user must log in.
+pub async fn force_refresh<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    cache: &Cache,
+) -> Result<TokenResult, Box<dyn std::error::Error>> {
+    if !cache.has_selected_account() || cache.get_microsoft_refresh_token().is_empty() {
+        return Err(
+            "no refresh token is cached; run minecraft-console-client without a command to log in"
+                .into(),
+        );
+    }
+
+    refresh(client, endpoints, cache.get_microsoft_refresh_token()).await
+}
 
+/// Checks that Minecraft services still accept `minecraft_token`, which may have been revoked
+/// even though it has not yet expired.
+///
+/// Returns `false` if the token was rejected as unauthorized.
+pub async fn validate_token<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    minecraft_token: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let response = timed(
+        "Minecraft profile",
+        client.get(&endpoints.minecraft_profile, minecraft_token),
+    )
+    .await?;
+
+    if response.status == 401 {
+        return Ok(false);
+    }
+    if !response.is_success() {
+        return Err(format!("profile request failed with status {}", response.status).into());
+    }
+
+    Ok(true)
+}
+
+/// Retrieves the profile of the account `minecraft_token` is for (e.g., the player's name).
+pub async fn get_profile<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    minecraft_token: &str,
+) -> Result<MinecraftProfile, Box<dyn std::error::Error>> {
+    timed(
+        "Minecraft profile",
+        client.get(&endpoints.minecraft_profile, minecraft_token),
+    )
+    .await?
+    .json()
+}
+
+/// Exchanges a Microsoft `access_token` which was retrieved elsewhere (e.g., by an application
+/// this crate is part of) for a Minecraft token, skipping the Microsoft log in, and retrieves the
+/// profile of its account.
+///
+/// The access token must have been issued for the `XboxLive.signin` scope.
+pub async fn authenticate_with_microsoft_token<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    access_token: &str,
+) -> Result<MinecraftSession, Box<dyn std::error::Error>> {
+    let minecraft_token = authenticate_with_microsoft(client, endpoints, access_token).await?;
+    let profile = get_profile(client, endpoints, &minecraft_token).await?;
+
+    Ok(MinecraftSession {
+        minecraft_token,
+        profile,
+    })
+}
+
+/// Runs the Xbox Live and Minecraft steps of authentication, once a Microsoft `access_token` has
+/// been retrieved, returning the Minecraft token.
+async fn authenticate_with_microsoft<H: HttpBackend>(
+    client: &H,
+    endpoints: &Endpoints,
+    access_token: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
     // step 3: authenticate with xbox live
     let xbox_authenticate_json = json!({
         "Properties": {
             "AuthMethod": "RPS",
             "SiteName": "user.auth.xboxlive.com",
-            "RpsTicket": &format!("d={}", authorization_token.access_token)
+            "RpsTicket": &format!("d={access_token}")
         },
         "RelyingParty": "http://auth.xboxlive.com",
         "TokenType": "JWT"
     });
 
-    let xbox_resp: XboxLiveAuthenticationResponse = client
-        .post("https://user.auth.xboxlive.com/user/authenticate")
-        .json(&xbox_authenticate_json)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let xbox_resp: XboxLiveAuthenticationResponse = timed(
+        "Xbox Live authentication",
+        client.post_json(&endpoints.xbox_authenticate, &xbox_authenticate_json),
+    )
+    .await?
+    .json()?;
 
     let xbox_token = &xbox_resp.token;
     let user_hash = &xbox_resp.display_claims["xui"][0]["uhs"];
 
     // step 4: convert xbox token into xbox security token
-    let xbox_security_token_resp: XboxLiveAuthenticationResponse = client
-        .post("https://xsts.auth.xboxlive.com/xsts/authorize")
-        .json(&json!({
-            "Properties": {
-                "SandboxId": "RETAIL",
-                "UserTokens": [xbox_token]
-            },
-            "RelyingParty": "rp://api.minecraftservices.com/",
-            "TokenType": "JWT"
-        }))
-        .send()
-        .await?
-        .json()
-        .await?;
+    let xsts_authorize_json = json!({
+        "Properties": {
+            "SandboxId": "RETAIL",
+            "UserTokens": [xbox_token]
+        },
+        "RelyingParty": "rp://api.minecraftservices.com/",
+        "TokenType": "JWT"
+    });
+    let xbox_security_token_resp: XboxLiveAuthenticationResponse = timed(
+        "XSTS authorization",
+        client.post_json(&endpoints.xsts_authorize, &xsts_authorize_json),
+    )
+    .await?
+    .json()?;
 
     // step 5: authenticate with minecraft
-    let minecraft_resp: MinecraftAuthenticationResponse = client
-        .post("https://api.minecraftservices.com/authentication/login_with_xbox")
-        .json(&json!({
-            "identityToken":
-                format!(
-                    "XBL3.0 x={user_hash};{xsts_token}",
-                    user_hash = user_hash,
-                    xsts_token = xbox_security_token_resp.token
-                )
-        }))
-        .send()
-        .await?
-        .json()
-        .await?;
+    let minecraft_login_json = json!({
+        "identityToken":
+            format!(
+                "XBL3.0 x={user_hash};{xsts_token}",
+                user_hash = user_hash,
+                xsts_token = xbox_security_token_resp.token
+            )
+    });
+    let minecraft_resp: MinecraftAuthenticationResponse = timed(
+        "Minecraft login",
+        client.post_json(&endpoints.minecraft_login, &minecraft_login_json),
+    )
+    .await?
+    .json()?;
 
-    Ok(TokenResult {
-        minecraft_token: minecraft_resp.access_token,
-        retrieve_type: RetrieveType::FromUserLogin {
-            microsoft_refresh_token: authorization_token.refresh_token,
-            expires_in: authorization_token.expires_in,
-        },
-    })
+    Ok(minecraft_resp.access_token)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use reqwest::Client;
+    use serde_json::json;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use super::{
+        authenticate, authenticate_with_microsoft_token, force_refresh, get_auth_code,
+        parse_auth_code, refresh, validate_token, Endpoints, MinecraftProfile, RetrieveType,
+        TokenResult, DEFAULT_AUTH_MAX_ATTEMPTS,
+    };
+    use crate::{
+        cache::{test::cache_for, Cache},
+        clock::SystemClock,
+        http::test::FakeBackend,
+        logging,
+        protocol::capture::test::SharedBuffer,
+    };
+
+    /// Serves HTTP on a local port until the test ends, answering a request for each path in
+    /// `routes` with its status code and body (and any other path with a 404).
+    ///
+    /// Returns the base URL of the server.
+    pub async fn serve(routes: Vec<(&'static str, u16, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut stream = BufReader::new(stream);
+
+                // the path is the second word of the request line
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).await.unwrap();
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+
+                // skip the headers, then the body
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    stream.read_line(&mut header).await.unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                stream.read_exact(&mut body).await.unwrap();
+
+                let (status, body) = routes
+                    .iter()
+                    .find(|(route, ..)| *route == path)
+                    .map_or((404, String::new()), |(_, status, body)| {
+                        (*status, body.clone())
+                    });
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        base
+    }
+
+    /// Builds the endpoints of a server made by [`serve`] at `base`.
+    pub fn endpoints(base: &str) -> Endpoints {
+        Endpoints {
+            microsoft_token: format!("{base}/token"),
+            xbox_authenticate: format!("{base}/xbox"),
+            xsts_authorize: format!("{base}/xsts"),
+            minecraft_login: format!("{base}/minecraft"),
+            minecraft_profile: format!("{base}/profile"),
+        }
+    }
+
+    /// The routes of a server accepting every authentication step, giving out the Minecraft token
+    /// `minecraft_token` and the refresh token `new_refresh_token`.
+    pub fn authentication_routes() -> Vec<(&'static str, u16, String)> {
+        let xbox = json!({
+            "IssueInstant": "2022-07-01T00:00:00Z",
+            "NotAfter": "2022-07-02T00:00:00Z",
+            "Token": "xbox_token",
+            "DisplayClaims": { "xui": [{ "uhs": "user_hash" }] }
+        })
+        .to_string();
+
+        vec![
+            (
+                "/token",
+                200,
+                json!({
+                    "token_type": "bearer",
+                    "scope": "XboxLive.signin offline_access",
+                    "expires_in": 3600,
+                    "ext_expires_in": 3600,
+                    "access_token": "microsoft_token",
+                    "refresh_token": "new_refresh_token",
+                    "id_token": "id"
+                })
+                .to_string(),
+            ),
+            ("/xbox", 200, xbox.clone()),
+            ("/xsts", 200, xbox),
+            (
+                "/minecraft",
+                200,
+                json!({
+                    "username": "uuid",
+                    "access_token": "minecraft_token",
+                    "token_type": "Bearer",
+                    "expires_in": 86400
+                })
+                .to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn reports_cache_use() {
+        let login = RetrieveType::FromUserLogin {
+            microsoft_refresh_token: String::from("refresh_token"),
+            expires_in: 3600,
+        };
+        assert!(!login.used_cache());
+        assert!(!login.is_from_cache());
+
+        let refresh = RetrieveType::FromRefresh {
+            microsoft_refresh_token: String::from("refresh_token"),
+            expires_in: 3600,
+        };
+        assert!(refresh.used_cache());
+        assert!(!refresh.is_from_cache());
+
+        let cached = TokenResult {
+            minecraft_token: String::from("minecraft_token"),
+            retrieve_type: RetrieveType::FromCache,
+        };
+        assert!(cached.used_cache());
+        assert!(cached.retrieve_type.is_from_cache());
+    }
+
+    #[tokio::test]
+    async fn refreshes_expired_cached_token() {
+        let base = serve(authentication_routes()).await;
+
+        // the default cache holds a long-expired Minecraft token
+        let cache = Cache::default();
+        let result = authenticate(
+            &Client::new(),
+            &endpoints(&base),
+            &b""[..],
+            true,
+            DEFAULT_AUTH_MAX_ATTEMPTS,
+            Some(&cache),
+            &SystemClock,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.minecraft_token, "minecraft_token");
+        assert!(matches!(
+            result.retrieve_type,
+            RetrieveType::FromRefresh { ref microsoft_refresh_token, expires_in: 3600 }
+                if microsoft_refresh_token == "new_refresh_token"
+        ));
+    }
+
+    #[tokio::test]
+    async fn exchanges_tokens_with_backend() {
+        let base = "https://auth.test";
+        let backend = FakeBackend::new(
+            authentication_routes()
+                .into_iter()
+                .map(|(path, status, body)| (format!("{base}{path}"), status, body)),
+        );
+
+        let result = refresh(&backend, &endpoints(base), "refresh_token")
+            .await
+            .unwrap();
+
+        assert_eq!(result.minecraft_token, "minecraft_token");
+        assert_eq!(
+            *backend.requested.lock().unwrap(),
+            ["/token", "/xbox", "/xsts", "/minecraft"].map(|path| format!("{base}{path}"))
+        );
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_microsoft_token() {
+        let base = "https://auth.test";
+        let profile = json!({ "id": "uuid", "name": "Steve" }).to_string();
+        let backend = FakeBackend::new(
+            authentication_routes()
+                .into_iter()
+                .chain([("/profile", 200, profile)])
+                .map(|(path, status, body)| (format!("{base}{path}"), status, body)),
+        );
+
+        let session = authenticate_with_microsoft_token(&backend, &endpoints(base), "access")
+            .await
+            .unwrap();
+
+        assert_eq!(session.minecraft_token, "minecraft_token");
+        assert_eq!(
+            session.profile,
+            MinecraftProfile {
+                id: "uuid".to_string(),
+                name: "Steve".to_string(),
+            }
+        );
+        // the Microsoft token endpoint is never used
+        assert_eq!(
+            *backend.requested.lock().unwrap(),
+            ["/xbox", "/xsts", "/minecraft", "/profile"].map(|path| format!("{base}{path}"))
+        );
+    }
+
+    #[tokio::test]
+    async fn forces_refresh_of_cached_token() {
+        let base = serve(authentication_routes()).await;
+        let client = Client::new();
+
+        // the default cache has no refresh token to use
+        assert!(force_refresh(&client, &endpoints(&base), &Cache::default())
+            .await
+            .is_err());
+
+        // the cached token is refreshed even though it has not expired
+        let cache = cache_for("steve", "refresh_token");
+        let result = force_refresh(&client, &endpoints(&base), &cache)
+            .await
+            .unwrap();
+
+        assert_eq!(result.minecraft_token, "minecraft_token");
+        assert!(matches!(
+            result.retrieve_type,
+            RetrieveType::FromRefresh { ref microsoft_refresh_token, .. }
+                if microsoft_refresh_token == "new_refresh_token"
+        ));
+    }
+
+    #[tokio::test]
+    async fn validates_token() {
+        let profile = json!({ "id": "uuid", "name": "Steve" }).to_string();
+        let valid = serve(vec![("/profile", 200, profile)]).await;
+        let revoked = serve(vec![("/profile", 401, String::new())]).await;
+        let failing = serve(vec![("/profile", 500, String::new())]).await;
+
+        let client = Client::new();
+        assert!(validate_token(&client, &endpoints(&valid), "token")
+            .await
+            .unwrap());
+        assert!(!validate_token(&client, &endpoints(&revoked), "token")
+            .await
+            .unwrap());
+        assert!(validate_token(&client, &endpoints(&failing), "token")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn reads_piped_auth_code() {
+        let mut reader = &b"M.C507_BAY.2.U.code\n"[..];
+        assert_eq!(
+            get_auth_code(&mut reader, false).unwrap(),
+            "M.C507_BAY.2.U.code"
+        );
+
+        // the input ends without a code
+        assert!(get_auth_code(&b""[..], false).is_err());
+        assert!(get_auth_code(&b"\n"[..], false).is_err());
+    }
+
+    #[test]
+    fn prompts_again_for_missing_auth_codes() {
+        let mut reader = &b"\n  \nhttps://example.com/?code=\nM.C507_BAY.code\n"[..];
+        assert_eq!(get_auth_code(&mut reader, true).unwrap(), "M.C507_BAY.code");
+
+        // no one is left to type a code
+        assert!(get_auth_code(&b"\n"[..], true).is_err());
+    }
+
+    #[test]
+    fn parses_pasted_auth_codes() {
+        assert_eq!(
+            parse_auth_code("M.C507_BAY.code").unwrap(),
+            "M.C507_BAY.code"
+        );
+        assert_eq!(
+            parse_auth_code("  M.C507_BAY.code \t\r\n").unwrap(),
+            "M.C507_BAY.code"
+        );
+        assert_eq!(
+            parse_auth_code(
+                "https://mccteam.github.io/redirect.html?code=M.C507_BAY.code&state=1\n"
+            )
+            .unwrap(),
+            "M.C507_BAY.code"
+        );
+
+        assert!(parse_auth_code(" \n").is_err());
+        assert!(parse_auth_code("https://mccteam.github.io/redirect.html?code=").is_err());
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let base = "https://auth.test";
+        let backend = FakeBackend::new([(
+            format!("{base}/token"),
+            400,
+            json!({ "error": "invalid_grant" }).to_string(),
+        )]);
+        let mut reader = &b"bad\nbad\nbad\nbad\n"[..];
+
+        let result = authenticate(
+            &backend,
+            &endpoints(base),
+            &mut reader,
+            true,
+            3,
+            None,
+            &SystemClock,
+        )
+        .await;
+
+        assert!(result.is_err());
+        // each code is tried once, leaving the fourth unread
+        assert_eq!(backend.requested.lock().unwrap().len(), 3);
+        assert_eq!(reader, b"bad\n");
+    }
+
+    #[tokio::test]
+    async fn logs_how_long_each_step_took() {
+        let base = "https://auth.test";
+        let profile = json!({ "id": "uuid", "name": "Steve" }).to_string();
+        let backend = FakeBackend::new(
+            authentication_routes()
+                .into_iter()
+                .chain([("/profile", 200, profile)])
+                .map(|(path, status, body)| (format!("{base}{path}"), status, body)),
+        );
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let _guard = tracing::subscriber::set_default(logging::subscriber(
+            logging::level(false, true),
+            move || writer.clone(),
+        ));
+
+        refresh(&backend, &endpoints(base), "refresh_token")
+            .await
+            .unwrap();
+        validate_token(&backend, &endpoints(base), "minecraft_token")
+            .await
+            .unwrap();
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        for step in [
+            "Microsoft token",
+            "Xbox Live authentication",
+            "XSTS authorization",
+            "Minecraft login",
+            "Minecraft profile",
+        ] {
+            assert!(
+                written.contains(&format!("{step} took ")),
+                "no timing for {step} in {written}"
+            );
+        }
+    }
 }
@@ -1,86 +1,396 @@
-use std::{fs, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-use chrono::DateTime;
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use toml_edit::Datetime;
 
+use crate::clock::{format_remaining, Clock};
+
 const CACHE_PATH: &str = "cache.toml";
 
+/// The name of the account used when none is chosen.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// How many accounts are cached when no cap is configured.
+pub const DEFAULT_MAX_ACCOUNTS: usize = 5;
+
 #[derive(Serialize, Deserialize, PartialEq, Eq)]
 pub struct Cache {
+    /// The name of the account tokens are retrieved for and saved to
+    selected: String,
+
+    /// The tokens of each account, by name
+    accounts: BTreeMap<String, CachedAccount>,
+
+    /// How many accounts are kept when saving, evicting the least recently used
+    #[serde(skip, default = "default_max_accounts")]
+    max_accounts: usize,
+
+    /// Where the cache is read from and saved to
+    #[serde(skip, default = "default_path")]
+    path: PathBuf,
+}
+
+fn default_max_accounts() -> usize {
+    DEFAULT_MAX_ACCOUNTS
+}
+
+fn default_path() -> PathBuf {
+    PathBuf::from(CACHE_PATH)
+}
+
+/// The tokens cached for a single account.
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct CachedAccount {
     /// The microsoft token
     microsoft_refresh_token: String,
 
     /// The minecraft token
     minecraft_token: CachedSessionToken,
+
+    /// When tokens were last saved for the account
+    last_used: DateTime<Utc>,
+}
+
+/// The cache as saved before multiple accounts were supported, holding one account's tokens.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CacheFile {
+    Legacy {
+        microsoft_refresh_token: String,
+        minecraft_token: CachedSessionToken,
+    },
+    Accounts(Cache),
+}
+
+impl From<CacheFile> for Cache {
+    fn from(file: CacheFile) -> Self {
+        match file {
+            CacheFile::Legacy {
+                microsoft_refresh_token,
+                minecraft_token,
+            } => {
+                let mut cache = Self::default();
+                let account = cache.selected_mut();
+                account.microsoft_refresh_token = microsoft_refresh_token;
+                account.minecraft_token = minecraft_token;
+
+                cache
+            }
+            CacheFile::Accounts(cache) => cache,
+        }
+    }
 }
 
 impl Cache {
     pub fn get() -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        Self::get_at(Path::new(CACHE_PATH))
+    }
+
+    /// Reads the cache saved at `path`, which is then saved back there.
+    pub fn get_at(path: &Path) -> Result<Option<Self>, Box<dyn std::error::Error>> {
         // if the cache file does not exist, we return None
         // otherwise, if there was an error we bubble up
         // if success, we get the cache
-        let cache = match fs::read_to_string(CACHE_PATH) {
+        let cache: CacheFile = match fs::read_to_string(path) {
             Ok(cache) => toml_edit::easy::from_str(&cache)?,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
-            Err(e) => return Err(Box::new(e)),
+            Err(e) => return Err(permission_hint(e, path)),
         };
 
+        let mut cache = Self::from(cache);
+        cache.set_path(path.to_path_buf());
+
         Ok(Some(cache))
     }
 
-    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        std::fs::write(CACHE_PATH, toml_edit::easy::to_string_pretty(self)?)?;
+    /// Sets where the cache is saved to, which is `cache.toml` in the working directory unless it
+    /// was read from elsewhere.
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
 
-        Ok(())
+    /// Chooses the account (by `name`) tokens are retrieved for and saved to.
+    pub fn select(&mut self, name: &str) {
+        self.selected = name.to_string();
+    }
+
+    /// Whether any tokens have been cached for the selected account.
+    pub fn has_selected_account(&self) -> bool {
+        self.accounts.contains_key(&self.selected)
+    }
+
+    /// Sets how many accounts are kept when saving, evicting the least recently used beyond it.
+    ///
+    /// The selected account is never evicted.
+    pub fn set_max_accounts(&mut self, max_accounts: usize) {
+        self.max_accounts = max_accounts;
+    }
+
+    /// Writes the cache to disk, marking the selected account as used at the time of `clock` and
+    /// evicting the least recently used accounts beyond the cap.
+    pub fn save<C: Clock>(&mut self, clock: &C) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(account) = self.accounts.get_mut(&self.selected) {
+            account.last_used = clock.now();
+        }
+        self.evict();
+
+        self.save_to(&self.path)
+    }
+
+    /// Imports the sessions the original C# Minecraft Console Client saved in its
+    /// `SessionCache.ini` (given as `contents`), adding an account named after each session's
+    /// player (as used at the time of `clock`), and returns the names of the accounts imported.
+    ///
+    /// Each session is a line of the form `login=token,player name,player id,client id,refresh
+    /// token`, possibly followed by more fields. The file does not say when tokens expire, so the
+    /// imported token is taken as expired and refreshed with the imported refresh token.
+    ///
+    /// Nothing is imported if the cache would then hold more accounts than it keeps, as saving it
+    /// would evict some of them.
+    pub fn import_mcc_sessions<C: Clock>(
+        &mut self,
+        contents: &str,
+        clock: &C,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut sessions = BTreeMap::new();
+        let mut imported = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Option<Vec<&str>> = line
+                .split_once('=')
+                .map(|(_, session)| session.split(',').collect());
+            let Some([token, player_name, _, _, refresh_token, ..]) = fields.as_deref() else {
+                return Err(format!(
+                    "line {} is not a session of the form 'login=token,player name,player id,\
+                    client id,refresh token'",
+                    number + 1
+                )
+                .into());
+            };
+
+            sessions.insert(
+                (*player_name).to_string(),
+                CachedAccount {
+                    microsoft_refresh_token: (*refresh_token).to_string(),
+                    minecraft_token: CachedSessionToken {
+                        token: (*token).to_string(),
+                        ..CachedAccount::default().minecraft_token
+                    },
+                    last_used: clock.now(),
+                },
+            );
+            imported.push((*player_name).to_string());
+        }
+
+        let accounts = self
+            .accounts
+            .keys()
+            .filter(|name| !sessions.contains_key(*name))
+            .count()
+            + sessions.len();
+        if accounts > self.max_accounts {
+            return Err(format!(
+                "importing would leave {accounts} accounts cached, but only {} are kept, so some \
+                would be evicted; raise max_cached_accounts in the config to import them all",
+                self.max_accounts
+            )
+            .into());
+        }
+        self.accounts.extend(sessions);
+
+        Ok(imported)
+    }
+
+    /// Removes the least recently used accounts other than the selected one, until at most
+    /// `max_accounts` remain.
+    fn evict(&mut self) {
+        while self.accounts.len() > self.max_accounts {
+            let least_recently_used = self
+                .accounts
+                .iter()
+                .filter(|(name, _)| **name != self.selected)
+                .min_by_key(|(_, account)| account.last_used)
+                .map(|(name, _)| name.clone());
+
+            match least_recently_used {
+                Some(name) => self.accounts.remove(&name),
+                None => break,
+            };
+        }
+    }
+
+    /// Writes the cache to `path`, which (on Unix) only the current user may read or write, as
+    /// the cache holds tokens to the user's account.
+    fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let contents = toml_edit::easy::to_string_pretty(self)?;
+
+        write_private(path, contents.as_bytes()).map_err(|e| permission_hint(e, path))
+    }
+
+    /// Retrieves the tokens of the selected account, creating an empty entry for it if it has
+    /// none yet.
+    fn selected_mut(&mut self) -> &mut CachedAccount {
+        self.accounts.entry(self.selected.clone()).or_default()
+    }
+
+    /// Describes where the cache is and the accounts it holds, with when each account's
+    /// Minecraft token expires and whether it is still valid at the time of `clock`.
+    ///
+    /// Tokens are redacted as they are by `Debug`, so the description is safe to share.
+    pub fn describe<C: Clock>(&self, clock: &C) -> String {
+        let mut description = format!("Cache: {}\nAccounts:\n", self.path.display());
+        for (name, account) in &self.accounts {
+            let selected = if *name == self.selected {
+                " (selected)"
+            } else {
+                ""
+            };
+            let token = &account.minecraft_token;
+            let validity = match DateTime::parse_from_rfc3339(&token.expiry_time) {
+                Ok(expiry_time) => match clock.remaining(expiry_time.with_timezone(&Utc)) {
+                    Some(remaining) => format!("valid, {} left", format_remaining(Some(remaining))),
+                    None => String::from("expired"),
+                },
+                Err(_) => String::from("unreadable, so taken as expired"),
+            };
+
+            let _ = write!(
+                description,
+                "  {name}{selected}\n    Minecraft token: {} (expires {}, {validity})\n    \
+                Microsoft refresh token: {}\n    Last used: {}\n",
+                redact(&token.token),
+                token.expiry_time,
+                redact(&account.microsoft_refresh_token),
+                account
+                    .last_used
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            );
+        }
+
+        description
     }
 
     /// Retrieves the inner minecraft token, wrapped in an option.
-    /// Returns `None` if the token has expired, otherwise returns the token.
-    pub fn get_minecraft_token(&self) -> Option<String> {
-        self.minecraft_token.get_token()
+    /// Returns `None` if the token has expired at the time of `clock`, otherwise returns the token.
+    pub fn get_minecraft_token<C: Clock>(&self, clock: &C) -> Option<String> {
+        self.accounts
+            .get(&self.selected)
+            .and_then(|account| account.minecraft_token.get_token(clock))
     }
 
-    /// Saves a new Minecraft token with expiry time to the cache
-    pub fn save_minecraft_token(
+    /// Saves a new Minecraft token with expiry time to the cache, at the time of `clock`
+    pub fn save_minecraft_token<C: Clock>(
         &mut self,
         token: String,
         expiry_time: chrono::DateTime<chrono::Utc>,
+        clock: &C,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // write and save
-        self.minecraft_token = CachedSessionToken::new(token, expiry_time)?;
-        self.save()?;
+        self.selected_mut().minecraft_token = CachedSessionToken::new(token, expiry_time)?;
+        self.save(clock)?;
 
         Ok(())
     }
 
     /// Retrieves the inner microsoft refresh token.
     pub fn get_microsoft_refresh_token(&self) -> &str {
-        &self.microsoft_refresh_token
+        self.accounts
+            .get(&self.selected)
+            .map_or("", |account| &account.microsoft_refresh_token)
     }
 
-    /// Saves a new Microsoft refresh token to the cache
-    pub fn save_microsoft_refresh_token(
+    /// Saves a new Microsoft refresh token to the cache, at the time of `clock`
+    pub fn save_microsoft_refresh_token<C: Clock>(
         &mut self,
         token: String,
+        clock: &C,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // write and save
-        self.microsoft_refresh_token = token;
-        self.save()?;
+        self.selected_mut().microsoft_refresh_token = token;
+        self.save(clock)?;
 
         Ok(())
     }
 }
 
+/// Writes `contents` to the file at `path`, which on Unix is made readable and writable by only
+/// its owner (`0600`).
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::{io::Write, os::unix::fs::OpenOptionsExt, os::unix::fs::PermissionsExt};
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // the mode only applies to new files, so restrict any existing file too
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents)
+}
+
+/// Writes `contents` to the file at `path`.
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    fs::write(path, contents)
+}
+
+/// Hides a `token` as `Debug` does, keeping only its length, or says that there is none.
+fn redact(token: &str) -> String {
+    if token.is_empty() {
+        String::from("none")
+    } else {
+        "X".repeat(token.len())
+    }
+}
+
+/// Explains how to fix a permission error accessing the cache at `path`, passing through any
+/// other error as-is.
+fn permission_hint(e: io::Error, path: &Path) -> Box<dyn std::error::Error> {
+    if e.kind() != io::ErrorKind::PermissionDenied {
+        return Box::new(e);
+    }
+
+    format!(
+        "permission denied accessing the cache at '{}' ({e}); make sure it is owned by the \
+        current user (e.g., with `chown`), or remove it to log in again",
+        path.display()
+    )
+    .into()
+}
+
 impl std::default::Default for Cache {
+    fn default() -> Self {
+        Self {
+            selected: DEFAULT_ACCOUNT.to_string(),
+            accounts: BTreeMap::from([(DEFAULT_ACCOUNT.to_string(), CachedAccount::default())]),
+            max_accounts: DEFAULT_MAX_ACCOUNTS,
+            path: default_path(),
+        }
+    }
+}
+
+impl std::default::Default for CachedAccount {
     fn default() -> Self {
         Self {
             microsoft_refresh_token: String::new(),
             minecraft_token: CachedSessionToken {
                 token: String::new(),
-                expiry_time: toml_edit::Datetime::from_str("2011-11-18T12:00:00Z")
-                    .expect("Failed to create DateTime"),
+                expiry_time: String::from("2011-11-18T12:00:00Z"),
             },
+            last_used: Utc.timestamp_opt(0, 0).unwrap(),
         }
     }
 }
@@ -88,11 +398,23 @@ impl std::default::Default for Cache {
 impl std::fmt::Debug for Cache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Cache")
+            .field("selected", &self.selected)
+            .field("accounts", &self.accounts)
+            .field("max_accounts", &self.max_accounts)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for CachedAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedAccount")
             .field(
                 "microsoft_refresh_token",
                 &"X".repeat(self.microsoft_refresh_token.len()),
             )
             .field("minecraft_token", &self.minecraft_token)
+            .field("last_used", &self.last_used)
             .finish()
     }
 }
@@ -101,8 +423,29 @@ impl std::fmt::Debug for Cache {
 pub struct CachedSessionToken {
     /// The token itself
     pub token: String,
-    ///  An ISO-8601 timestamp of when the token expires
-    pub expiry_time: Datetime,
+    /// An RFC 3339 timestamp of when the token expires
+    #[serde(deserialize_with = "deserialize_expiry_time")]
+    pub expiry_time: String,
+}
+
+/// An expiry time as it may be saved in the cache.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpiryTime {
+    Rfc3339(String),
+    /// A TOML datetime, which older caches saved the expiry time as
+    Datetime(Datetime),
+}
+
+/// Reads an expiry time saved as either a string or (in older caches) a TOML datetime.
+fn deserialize_expiry_time<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match ExpiryTime::deserialize(deserializer)? {
+        ExpiryTime::Rfc3339(expiry_time) => expiry_time,
+        ExpiryTime::Datetime(expiry_time) => expiry_time.to_string(),
+    })
 }
 
 impl CachedSessionToken {
@@ -112,33 +455,32 @@ impl CachedSessionToken {
     ) -> Result<Self, Box<dyn std::error::Error>> {
         Ok(Self {
             token,
-            expiry_time: toml_edit::Datetime::from_str(
-                &expiry_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-            )?,
+            expiry_time: expiry_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
         })
     }
 
-    /// Retrieves the inner minecraft token, and if it valid
+    /// Retrieves the inner minecraft token, and if it valid at the time of `clock`
     /// ```rust
-    /// # use minecraft_console_client::cache::CachedSessionToken;
+    /// # use minecraft_console_client::{cache::CachedSessionToken, clock::SystemClock};
     /// # use std::str::FromStr;
     /// let expired_session = CachedSessionToken::new("secret_token".to_string(), chrono::Utc::now())?;
-    /// assert_eq!(expired_session.get_token(), None);
+    /// assert_eq!(expired_session.get_token(&SystemClock), None);
     ///
     /// let valid_session = CachedSessionToken::new("secret_token".to_string(), chrono::DateTime::from_str("2100-01-01T12:00:00Z")?)?;
-    /// assert_eq!(valid_session.get_token(), Some("secret_token".to_string()));
+    /// assert_eq!(valid_session.get_token(&SystemClock), Some("secret_token".to_string()));
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn get_token(&self) -> Option<String> {
+    pub fn get_token<C: Clock>(&self, clock: &C) -> Option<String> {
         let token = &self.token;
 
-        let expiry_time: DateTime<chrono::Utc> =
-            chrono::DateTime::from_str(&self.expiry_time.to_string())
-                .unwrap_or_else(|_| panic!("Failed to parse expiry time '{}'", self.expiry_time));
+        // an expiry time which can not be read is taken as expired, so a new token is retrieved
+        let Ok(expiry_time) = DateTime::parse_from_rfc3339(&self.expiry_time) else {
+            return None;
+        };
 
         // if expiry_time > current_time, then we have not expired
         // and should return the token
-        if expiry_time > chrono::Utc::now() {
+        if expiry_time > clock.now() {
             Some(token.clone())
         } else {
             None
@@ -154,3 +496,295 @@ impl std::fmt::Debug for CachedSessionToken {
             .finish()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{path::PathBuf, str::FromStr};
+
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::{Cache, CacheFile, CachedAccount, CachedSessionToken};
+    use crate::clock::FixedClock;
+
+    /// A clock long before the tokens saved by the tests expire.
+    fn clock() -> FixedClock {
+        FixedClock(DateTime::from_str("2022-07-01T12:00:00Z").unwrap())
+    }
+
+    /// Builds a cache of `accounts` last used on the given dates, selecting `selected`.
+    fn cache_with(selected: &str, accounts: &[(&str, &str)], max_accounts: usize) -> Cache {
+        let mut cache = Cache::default();
+        cache.select(selected);
+        cache.set_max_accounts(max_accounts);
+        cache.accounts = accounts
+            .iter()
+            .map(|(name, last_used)| {
+                let account = CachedAccount {
+                    last_used: DateTime::from_str(last_used).unwrap(),
+                    ..CachedAccount::default()
+                };
+                ((*name).to_string(), account)
+            })
+            .collect();
+
+        cache
+    }
+
+    /// Builds a cache which has selected the account `name`, holding `microsoft_refresh_token`
+    /// and an unexpired Minecraft token.
+    pub fn cache_for(name: &str, microsoft_refresh_token: &str) -> Cache {
+        let mut cache = Cache::default();
+        cache.select(name);
+
+        let account = cache.selected_mut();
+        account.microsoft_refresh_token = microsoft_refresh_token.to_string();
+        account.minecraft_token = CachedSessionToken::new(
+            "minecraft_token".to_string(),
+            DateTime::from_str("2100-01-01T12:00:00Z").unwrap(),
+        )
+        .unwrap();
+
+        cache
+    }
+
+    /// A cache file in a directory of its own under the system's temporary directory, which is
+    /// removed when dropped, so tests saving a cache touch neither the real one nor each other's.
+    pub struct TempCache {
+        directory: PathBuf,
+    }
+
+    impl TempCache {
+        /// Creates an empty directory for the cache of the test called `name`.
+        pub fn new(name: &str) -> Self {
+            let directory = std::env::temp_dir().join(format!(
+                "minecraft-console-client-test-cache-{name}-{}",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&directory);
+            std::fs::create_dir_all(&directory).unwrap();
+
+            Self { directory }
+        }
+
+        /// Retrieves where the cache is saved.
+        pub fn path(&self) -> PathBuf {
+            self.directory.join("cache.toml")
+        }
+
+        /// Creates an empty cache, which is saved to the temporary file.
+        pub fn cache(&self) -> Cache {
+            let mut cache = Cache::default();
+            cache.set_path(self.path());
+
+            cache
+        }
+
+        /// Reads the cache back from the temporary file, if it has been saved.
+        pub fn load(&self) -> Option<Cache> {
+            Cache::get_at(&self.path()).unwrap()
+        }
+    }
+
+    impl Drop for TempCache {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.directory);
+        }
+    }
+
+    fn account_names(cache: &Cache) -> Vec<&str> {
+        cache.accounts.keys().map(String::as_str).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn saves_privately() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "minecraft-console-client-test-cache-{}.toml",
+            std::process::id()
+        ));
+        // an existing world-readable cache is restricted too
+        std::fs::write(&path, "").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        Cache::default().save_to(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn describes_without_secrets() {
+        let cache = cache_for("alt", "microsoft_refresh_token");
+        let clock = FixedClock(DateTime::from_str("2100-01-01T11:00:00Z").unwrap());
+
+        let description = cache.describe(&clock);
+        assert!(!description.contains("minecraft_token"));
+        assert!(!description.contains("microsoft_refresh_token"));
+        assert!(description.contains("  alt (selected)\n"));
+        assert!(description.contains(
+            "Minecraft token: XXXXXXXXXXXXXXX (expires 2100-01-01T12:00:00Z, valid, 01:00:00 left)"
+        ));
+        // the default account has no tokens
+        assert!(description.contains("Microsoft refresh token: none"));
+
+        let clock = FixedClock(DateTime::from_str("2100-01-02T00:00:00Z").unwrap());
+        assert!(cache
+            .describe(&clock)
+            .contains("(expires 2100-01-01T12:00:00Z, expired)"));
+    }
+
+    #[test]
+    fn expires_at_expiry_time() {
+        let expiry_time: DateTime<Utc> = DateTime::from_str("2022-07-01T12:00:00Z").unwrap();
+        let session = CachedSessionToken::new("secret_token".to_string(), expiry_time).unwrap();
+
+        assert_eq!(
+            session.get_token(&FixedClock(expiry_time - Duration::seconds(1))),
+            Some("secret_token".to_string())
+        );
+        assert_eq!(session.get_token(&FixedClock(expiry_time)), None);
+        assert_eq!(
+            session.get_token(&FixedClock(expiry_time + Duration::seconds(1))),
+            None
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_used_accounts() {
+        let mut cache = cache_with(
+            "alex",
+            &[
+                ("alex", "2022-07-03T00:00:00Z"),
+                ("herobrine", "2022-07-01T00:00:00Z"),
+                ("steve", "2022-07-02T00:00:00Z"),
+            ],
+            2,
+        );
+
+        cache.evict();
+        assert_eq!(account_names(&cache), ["alex", "steve"]);
+    }
+
+    #[test]
+    fn keeps_selected_account() {
+        let mut cache = cache_with(
+            "herobrine",
+            &[
+                ("alex", "2022-07-03T00:00:00Z"),
+                ("herobrine", "2022-07-01T00:00:00Z"),
+            ],
+            1,
+        );
+
+        cache.evict();
+        assert_eq!(account_names(&cache), ["herobrine"]);
+    }
+
+    #[test]
+    fn reads_single_account_cache() {
+        let file: CacheFile = toml_edit::easy::from_str(
+            "microsoft_refresh_token = \"refresh\"\n\n[minecraft_token]\ntoken = \"token\"\nexpiry_time = 2100-01-01T12:00:00Z\n",
+        )
+        .unwrap();
+        let cache = Cache::from(file);
+
+        assert_eq!(cache.get_microsoft_refresh_token(), "refresh");
+        assert_eq!(
+            cache.get_minecraft_token(&clock()),
+            Some("token".to_string())
+        );
+    }
+
+    #[test]
+    fn round_trips_expiry_time() {
+        let toml = "token = \"secret_token\"\nexpiry_time = \"2100-01-01T12:00:00Z\"\n";
+        let session: CachedSessionToken = toml_edit::easy::from_str(toml).unwrap();
+
+        assert_eq!(session.expiry_time, "2100-01-01T12:00:00Z");
+        assert_eq!(
+            session.get_token(&clock()),
+            Some("secret_token".to_string())
+        );
+        assert_eq!(toml_edit::easy::to_string(&session).unwrap(), toml);
+
+        // older caches saved the expiry time as a TOML datetime
+        let session: CachedSessionToken = toml_edit::easy::from_str(
+            "token = \"secret_token\"\nexpiry_time = 2100-01-01T12:00:00Z\n",
+        )
+        .unwrap();
+        assert_eq!(session.expiry_time, "2100-01-01T12:00:00Z");
+    }
+
+    #[test]
+    fn imports_mcc_sessions() {
+        let mut cache = Cache::default();
+        let imported = cache
+            .import_mcc_sessions(
+                "# Minecraft Console Client session cache\n\
+                steve@example.com=token1,Steve,0000000000000001,client,refresh1,hash,key\n\
+                \n\
+                alex@example.com=token2,Alex,0000000000000002,client,refresh2\n",
+                &clock(),
+            )
+            .unwrap();
+        assert_eq!(imported, ["Steve", "Alex"]);
+
+        cache.select("Steve");
+        assert_eq!(cache.get_microsoft_refresh_token(), "refresh1");
+        // the expiry of imported tokens is unknown, so they are refreshed when first used
+        assert_eq!(cache.get_minecraft_token(&clock()), None);
+
+        assert!(Cache::default()
+            .import_mcc_sessions("steve@example.com=token1,Steve", &clock())
+            .is_err());
+    }
+
+    #[test]
+    fn refuses_imports_which_would_evict() {
+        let mut cache = cache_with("alex", &[("alex", "2022-07-03T00:00:00Z")], 2);
+        let sessions = "steve@example.com=token1,Steve,1,client,refresh1\n\
+            herobrine@example.com=token2,Herobrine,2,client,refresh2\n";
+
+        assert!(cache.import_mcc_sessions(sessions, &clock()).is_err());
+        assert_eq!(account_names(&cache), ["alex"]);
+
+        // accounts which are already cached are replaced, rather than added
+        let sessions = "alex@example.com=token1,alex,1,client,refresh1\n\
+            steve@example.com=token2,Steve,2,client,refresh2\n";
+        cache.import_mcc_sessions(sessions, &clock()).unwrap();
+        assert_eq!(account_names(&cache), ["Steve", "alex"]);
+    }
+
+    #[test]
+    fn saves_and_reloads_tokens() {
+        let temp = TempCache::new("reload");
+        assert!(temp.load().is_none());
+
+        let mut cache = temp.cache();
+        cache.select("steve");
+        cache
+            .save_microsoft_refresh_token("refresh_token".to_string(), &clock())
+            .unwrap();
+        cache
+            .save_minecraft_token(
+                "minecraft_token".to_string(),
+                DateTime::from_str("2100-01-01T12:00:00Z").unwrap(),
+                &clock(),
+            )
+            .unwrap();
+
+        let mut reloaded = temp.load().unwrap();
+        reloaded.select("steve");
+        assert_eq!(reloaded.get_microsoft_refresh_token(), "refresh_token");
+        assert_eq!(
+            reloaded.get_minecraft_token(&clock()),
+            Some("minecraft_token".to_string())
+        );
+        assert!(reloaded
+            .describe(&clock())
+            .starts_with(&format!("Cache: {}\n", temp.path().display())));
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+};
+
+use crate::config::Config;
+
+/// The usage shown when the arguments could not be parsed.
+pub const USAGE: &str = "Usage: minecraft-console-client [command] [options]
+
+Commands:
+  refresh             Refresh the cached token of the account, then exit
+  cache show          Print the cached accounts and when their tokens expire, without the
+                      tokens themselves, then exit
+  generate-config [path]
+                      Write a fully commented default config to [path] (config.toml by
+                      default), then exit
+
+Options:
+  --capture <file>    Record every received packet to <file>
+  --capture-sent      Also record sent packets in the capture
+  --replay <file>     Decode every packet of a capture from <file>, then exit
+  --import <file>     Import the accounts of the C# client's SessionCache.ini, then exit
+  --ping <address>    Ping the server at <address>, then exit (may be repeated)
+  --concurrency <n>   Ping at most <n> servers at once
+  --probe-all-versions <address>
+                      Report which protocol versions the server at <address> accepts, then
+                      exit
+  --probe-login       Also start a login on each version probed, which briefly joins
+                      offline-mode servers
+  --probe-timeout <seconds>
+                      Give up on each probe, and on each ping left unanswered after a
+                      status, after <seconds> (5 by default)
+  --auth-max-attempts <n>
+                      Give up logging in after <n> authorization codes did not work
+  --format <format>   Print ping results as text (the default) or prometheus metrics
+  --out <file>        Write ping or probe results to <file> instead of printing them
+  --append            Add to the end of the --out file, rather than replacing it
+  --bind <address>    Connect from the local <address>, with or without a port
+  --no-srv            Connect to the address as given, without following SRV records
+  --insecure-chat     Send unsigned chat even to servers which enforce secure chat
+  --strict-config     Fail on settings in the config which are not known, to catch typos
+  --force             Let generate-config overwrite an existing file
+  --quiet             Only print results, warnings, and errors
+  --verbose           Also print debug messages, e.g., how long each step of signing in took";
+
+/// A command to run instead of connecting to the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Refresh the cached token, and save it to the cache.
+    Refresh,
+    /// Print what the cache holds, with its tokens redacted.
+    CacheShow,
+    /// Write a commented default config to the path, or the usual config path if none is given.
+    GenerateConfig(Option<PathBuf>),
+}
+
+/// How the results of pinging servers are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A line of readable text for each server.
+    #[default]
+    Text,
+    /// Metrics in the Prometheus text format, for a textfile collector to scrape.
+    Prometheus,
+}
+
+/// The command line arguments, which override the config for a single run.
+#[derive(Debug, Default, PartialEq, Eq)]
+// each flag is an independent switch, rather than a state machine
+#[allow(clippy::struct_excessive_bools)]
+pub struct Args {
+    /// The command to run, if any.
+    pub command: Option<Command>,
+    /// Where packets are recorded to, if anywhere.
+    pub capture: Option<PathBuf>,
+    /// If sent packets are recorded in the capture, as well as received packets.
+    pub capture_sent: bool,
+    /// The capture to replay through the decoders, instead of running the client.
+    pub replay: Option<PathBuf>,
+    /// The session cache of the C# client to import accounts from, instead of running the
+    /// client.
+    pub import: Option<PathBuf>,
+    /// The servers to ping, instead of running the client.
+    pub ping: Vec<String>,
+    /// How many servers are pinged at once, overriding the config.
+    pub concurrency: Option<usize>,
+    /// The server whose accepted protocol versions are probed, instead of running the client.
+    pub probe_all_versions: Option<String>,
+    /// If a login is started on each protocol version probed, as well as the status requested.
+    pub probe_login: bool,
+    /// How long each probe may take, and how long a server is given to answer a ping, in seconds.
+    pub probe_timeout: Option<u64>,
+    /// How many authorization codes may be entered when logging in, overriding the config.
+    pub auth_max_attempts: Option<u32>,
+    /// How the results of pinging servers are printed.
+    pub format: OutputFormat,
+    /// The file ping or probe results are written to, instead of being printed.
+    pub out: Option<PathBuf>,
+    /// If ping results are added to the end of the output file, rather than replacing it.
+    pub append: bool,
+    /// The local address connections are made from, overriding the config.
+    pub bind_address: Option<SocketAddr>,
+    /// If SRV records are not followed, overriding the config.
+    pub no_srv: bool,
+    /// If unsigned chat is sent to servers which enforce secure chat, overriding the config.
+    pub insecure_chat: bool,
+    /// If settings in the config which are not known are an error, rather than ignored.
+    pub strict_config: bool,
+    /// If files written by commands replace existing files.
+    pub force: bool,
+    /// If informational messages are hidden.
+    pub quiet: bool,
+    /// If debug messages are shown, as well as informational messages.
+    pub verbose: bool,
+}
+
+impl Args {
+    /// Parses the arguments given to the program, not including the program name.
+    pub fn parse<I>(args: I) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--capture" => parsed.capture = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--capture-sent" => parsed.capture_sent = true,
+                "--replay" => parsed.replay = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--import" => parsed.import = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--ping" => parsed.ping.push(value(&mut args, &arg)?),
+                "--concurrency" => {
+                    let concurrency = value(&mut args, &arg)?;
+                    parsed.concurrency =
+                        Some(concurrency.parse().map_err(|e| {
+                            format!("invalid value '{concurrency}' for '{arg}': {e}")
+                        })?);
+                }
+                "--probe-all-versions" => {
+                    parsed.probe_all_versions = Some(value(&mut args, &arg)?);
+                }
+                "--probe-login" => parsed.probe_login = true,
+                "--probe-timeout" => {
+                    let timeout = value(&mut args, &arg)?;
+                    let seconds = timeout
+                        .parse()
+                        .map_err(|e| format!("invalid value '{timeout}' for '{arg}': {e}"))?;
+                    // no server could answer a probe in no time at all
+                    if seconds == 0 {
+                        return Err(format!(
+                            "invalid value '{timeout}' for '{arg}': must be at least 1 second"
+                        )
+                        .into());
+                    }
+                    parsed.probe_timeout = Some(seconds);
+                }
+                "--auth-max-attempts" => {
+                    let attempts = value(&mut args, &arg)?;
+                    parsed.auth_max_attempts = Some(
+                        attempts
+                            .parse()
+                            .map_err(|e| format!("invalid value '{attempts}' for '{arg}': {e}"))?,
+                    );
+                }
+                "--format" => {
+                    parsed.format = match value(&mut args, &arg)?.as_str() {
+                        "text" => OutputFormat::Text,
+                        "prometheus" => OutputFormat::Prometheus,
+                        other => {
+                            return Err(format!(
+                            "invalid value '{other}' for '{arg}': expected 'text' or 'prometheus'"
+                        )
+                            .into())
+                        }
+                    };
+                }
+                "--out" => parsed.out = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--append" => parsed.append = true,
+                "--bind" => {
+                    let address = value(&mut args, &arg)?;
+                    parsed.bind_address = Some(parse_bind_address(&address).ok_or_else(|| {
+                        format!("invalid value '{address}' for '{arg}': not an IP address")
+                    })?);
+                }
+                "--no-srv" => parsed.no_srv = true,
+                "--insecure-chat" => parsed.insecure_chat = true,
+                "--strict-config" => parsed.strict_config = true,
+                "--force" => parsed.force = true,
+                "--quiet" => parsed.quiet = true,
+                "--verbose" => parsed.verbose = true,
+                "refresh" if parsed.command.is_none() => parsed.command = Some(Command::Refresh),
+                "cache" if parsed.command.is_none() => match value(&mut args, &arg)?.as_str() {
+                    "show" => parsed.command = Some(Command::CacheShow),
+                    other => {
+                        return Err(format!("unknown cache command '{other}'\n\n{USAGE}").into())
+                    }
+                },
+                "generate-config" if parsed.command.is_none() => {
+                    parsed.command = Some(Command::GenerateConfig(None));
+                }
+                path if parsed.command == Some(Command::GenerateConfig(None))
+                    && !path.starts_with("--") =>
+                {
+                    parsed.command = Some(Command::GenerateConfig(Some(PathBuf::from(path))));
+                }
+                _ => return Err(format!("unknown argument '{arg}'\n\n{USAGE}").into()),
+            }
+        }
+
+        // only pings and probes have results to write
+        if parsed.out.is_some() && parsed.ping.is_empty() && parsed.probe_all_versions.is_none() {
+            return Err(
+                format!("'--out' needs '--ping' or '--probe-all-versions'\n\n{USAGE}").into(),
+            );
+        }
+        if parsed.append && parsed.out.is_none() {
+            return Err(format!("'--append' needs '--out'\n\n{USAGE}").into());
+        }
+
+        Ok(parsed)
+    }
+
+    /// Overrides the fields of `config` which were given on the command line.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(concurrency) = self.concurrency {
+            config.ping_concurrency = concurrency;
+        }
+        if let Some(attempts) = self.auth_max_attempts {
+            config.auth_max_attempts = attempts;
+        }
+        if let Some(bind_address) = self.bind_address {
+            config.bind_address = Some(bind_address);
+        }
+        if self.no_srv {
+            config.resolve_srv = false;
+        }
+        if self.insecure_chat {
+            config.insecure_chat = true;
+        }
+    }
+}
+
+/// Parses a local address to bind to, which is given any free port if it has none.
+fn parse_bind_address(address: &str) -> Option<SocketAddr> {
+    address
+        .parse()
+        .ok()
+        .or_else(|| Some(SocketAddr::new(address.parse::<IpAddr>().ok()?, 0)))
+}
+
+/// Retrieves the value following the option `name`.
+fn value<I>(args: &mut I, name: &str) -> Result<String, Box<dyn std::error::Error>>
+where
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .ok_or_else(|| format!("missing value for '{name}'\n\n{USAGE}").into())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{Args, Command, OutputFormat};
+    use crate::config::Config;
+
+    fn parse(args: &[&str]) -> Result<Args, Box<dyn std::error::Error>> {
+        Args::parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parses_no_arguments() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+    }
+
+    #[test]
+    fn parses_capture() {
+        let args = parse(&["--capture", "packets.cap", "--capture-sent"]).unwrap();
+
+        assert_eq!(args.capture, Some(PathBuf::from("packets.cap")));
+        assert!(args.capture_sent);
+    }
+
+    #[test]
+    fn parses_import() {
+        let args = parse(&["--import", "SessionCache.ini"]).unwrap();
+
+        assert_eq!(args.import, Some(PathBuf::from("SessionCache.ini")));
+    }
+
+    #[test]
+    fn parses_replay() {
+        let args = parse(&["--replay", "packets.cap"]).unwrap();
+
+        assert_eq!(args.replay, Some(PathBuf::from("packets.cap")));
+    }
+
+    #[test]
+    fn concurrency_overrides_config() {
+        let mut config = Config::default();
+        parse(&[]).unwrap().apply(&mut config);
+        assert_eq!(config.ping_concurrency, 16);
+
+        let args = parse(&["--ping", "a:25565", "--ping", "b", "--concurrency", "4"]).unwrap();
+        args.apply(&mut config);
+        assert_eq!(args.ping, ["a:25565", "b"]);
+        assert_eq!(config.ping_concurrency, 4);
+    }
+
+    #[test]
+    fn auth_max_attempts_overrides_config() {
+        let mut config = Config::default();
+        parse(&[]).unwrap().apply(&mut config);
+        assert_eq!(config.auth_max_attempts, 3);
+
+        parse(&["--auth-max-attempts", "5"])
+            .unwrap()
+            .apply(&mut config);
+        assert_eq!(config.auth_max_attempts, 5);
+
+        assert!(parse(&["--auth-max-attempts", "-1"]).is_err());
+    }
+
+    #[test]
+    fn flags_override_config() {
+        let mut config = Config::default();
+        assert!(config.resolve_srv);
+
+        parse(&["--no-srv"]).unwrap().apply(&mut config);
+        assert!(!config.resolve_srv);
+        assert!(!config.insecure_chat);
+
+        parse(&["--insecure-chat"]).unwrap().apply(&mut config);
+        assert!(config.insecure_chat);
+
+        assert!(parse(&["--quiet"]).unwrap().quiet);
+        assert!(parse(&["--verbose"]).unwrap().verbose);
+        assert!(parse(&["--strict-config"]).unwrap().strict_config);
+    }
+
+    #[test]
+    fn parses_format() {
+        assert_eq!(parse(&[]).unwrap().format, OutputFormat::Text);
+
+        let args = parse(&["--ping", "a", "--format", "prometheus"]).unwrap();
+        assert_eq!(args.format, OutputFormat::Prometheus);
+        assert!(parse(&["--format", "json"]).is_err());
+    }
+
+    #[test]
+    fn parses_output_file() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.out, None);
+        assert!(!args.append);
+
+        let args = parse(&["--ping", "a", "--out", "logs/ping.txt", "--append"]).unwrap();
+        assert_eq!(args.out, Some(PathBuf::from("logs/ping.txt")));
+        assert!(args.append);
+        assert!(parse(&["--out"]).is_err());
+
+        let args = parse(&["--probe-all-versions", "a", "--out", "probe.txt"]).unwrap();
+        assert_eq!(args.out, Some(PathBuf::from("probe.txt")));
+        // there is nothing to write without a ping or probe
+        assert!(parse(&["--out", "logs/ping.txt"]).is_err());
+        assert!(parse(&["--ping", "a", "--append"]).is_err());
+    }
+
+    #[test]
+    fn parses_probe_options() {
+        let args = parse(&[
+            "--probe-all-versions",
+            "mc.example.com",
+            "--probe-login",
+            "--probe-timeout",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(args.probe_all_versions.as_deref(), Some("mc.example.com"));
+        assert!(args.probe_login);
+        assert_eq!(args.probe_timeout, Some(2));
+        assert!(parse(&["--probe-timeout", "soon"]).is_err());
+        assert!(parse(&["--probe-timeout", "0"]).is_err());
+    }
+
+    #[test]
+    fn parses_bind_address() {
+        let args = parse(&["--bind", "192.168.1.2"]).unwrap();
+        assert_eq!(args.bind_address, Some("192.168.1.2:0".parse().unwrap()));
+
+        let args = parse(&["--bind", "[::1]:40000"]).unwrap();
+        assert_eq!(args.bind_address, Some("[::1]:40000".parse().unwrap()));
+
+        assert!(parse(&["--bind", "localhost"]).is_err());
+    }
+
+    #[test]
+    fn parses_command() {
+        let args = parse(&["refresh", "--quiet"]).unwrap();
+
+        assert_eq!(args.command, Some(Command::Refresh));
+        assert!(args.quiet);
+        assert!(parse(&["refresh", "refresh"]).is_err());
+    }
+
+    #[test]
+    fn parses_generate_config() {
+        let args = parse(&["generate-config"]).unwrap();
+        assert_eq!(args.command, Some(Command::GenerateConfig(None)));
+        assert!(!args.force);
+
+        let args = parse(&["generate-config", "bot.toml", "--force"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::GenerateConfig(Some(PathBuf::from("bot.toml"))))
+        );
+        assert!(args.force);
+
+        assert!(parse(&["generate-config", "a.toml", "b.toml"]).is_err());
+        assert!(parse(&["refresh", "a.toml"]).is_err());
+    }
+
+    #[test]
+    fn parses_cache_show() {
+        let args = parse(&["cache", "show"]).unwrap();
+        assert_eq!(args.command, Some(Command::CacheShow));
+
+        assert!(parse(&["cache"]).is_err());
+        assert!(parse(&["cache", "clear"]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--concurrency", "many"]).is_err());
+        assert!(parse(&["--capture"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+}
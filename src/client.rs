@@ -0,0 +1,632 @@
+//! A single entry point to the client, tying together the config, the account's tokens, and
+//! connections to servers.
+//!
+//! [`McClient`] wraps the lower-level modules ([`config`], [`cache`](crate::cache),
+//! [`authentication`], [`get_server_info`], and [`connect`]), which remain available for
+//! anything it does not cover.
+
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::net::TcpStream;
+
+use crate::{
+    authentication::{self, Endpoints, RetrieveType},
+    cache::Cache,
+    clock::{Clock, SystemClock},
+    config::{self, Config},
+    connect,
+    get_server_info::{self, PingOptions},
+    protocol::{packets::ServerStatus, Connection, ConnectionError, Event, PROTOCOL_VERSION},
+};
+
+/// The client, which pings servers, logs in to the player's account, and joins a server.
+///
+/// Created with [`McClient::builder`].
+pub struct McClient {
+    config: Config,
+    ping_options: PingOptions,
+    http: reqwest::Client,
+    endpoints: Endpoints,
+    /// The cache, once it has been read (or was given to the builder).
+    cache: Option<Cache>,
+    /// If the cache was read from disk or given to the builder, rather than started afresh.
+    cache_exists: bool,
+    /// The name the player joins servers as, once it is known (or was given to the builder).
+    username: Option<String>,
+    /// What the expiry of the account's tokens is checked against.
+    clock: Box<dyn Clock + Send + Sync>,
+    /// The address of the server joined last, which the client reconnects to, if any.
+    server_address: Option<String>,
+    /// The connection to the server joined last, if any.
+    connection: Option<Connection<TcpStream>>,
+}
+
+/// Builds an [`McClient`], loading whatever it is not given.
+#[derive(Default)]
+#[must_use]
+pub struct McClientBuilder {
+    config: Option<Config>,
+    strict_config: bool,
+    cache: Option<Cache>,
+    endpoints: Option<Endpoints>,
+    username: Option<String>,
+    clock: Option<Box<dyn Clock + Send + Sync>>,
+    capture: Option<PathBuf>,
+    capture_sent: bool,
+}
+
+impl McClientBuilder {
+    /// Uses `config`, rather than reading the config file.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Fails to build if the config file has unknown settings, when it is read.
+    pub fn strict_config(mut self, strict: bool) -> Self {
+        self.strict_config = strict;
+        self
+    }
+
+    /// Uses `cache` for the account's tokens, rather than reading the cache file.
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Authenticates against `endpoints`, rather than the Microsoft and Minecraft services.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Joins servers as `username`, rather than the name on the profile of the config's account.
+    pub fn username<T: Into<String>>(mut self, username: T) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Checks the expiry of tokens against `clock`, rather than the [`SystemClock`].
+    pub fn clock<C: Clock + Send + Sync + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Records the packets of pings to `path`, including those sent if `capture_sent` is set.
+    pub fn capture(mut self, path: Option<PathBuf>, capture_sent: bool) -> Self {
+        self.capture = path;
+        self.capture_sent = capture_sent;
+        self
+    }
+
+    /// Builds the client, reading the config file if no config was given, and failing if the
+    /// config is invalid.
+    ///
+    /// The cache is only read once it is first needed, so the client can ping servers even if it
+    /// can not be read.
+    pub fn build(self) -> Result<McClient, Box<dyn std::error::Error>> {
+        let config = match self.config {
+            Some(config) => config,
+            None => config::get(self.strict_config)?,
+        };
+        config.validate()?;
+
+        let ping_options = PingOptions {
+            capture: self.capture,
+            capture_sent: self.capture_sent,
+            resolve_srv: config.resolve_srv,
+            bind_address: config.bind_address,
+            address_family: config.address_family,
+            delays: config.connect_delays(),
+            handshake_host: config.handshake_host.clone(),
+            ..PingOptions::default()
+        };
+
+        Ok(McClient {
+            ping_options,
+            http: reqwest::Client::new(),
+            endpoints: self.endpoints.unwrap_or_default(),
+            cache_exists: self.cache.is_some(),
+            cache: self.cache,
+            username: self.username,
+            clock: self.clock.unwrap_or_else(|| Box::new(SystemClock)),
+            server_address: None,
+            connection: None,
+            config,
+        })
+    }
+}
+
+impl McClient {
+    /// Starts building a client.
+    pub fn builder() -> McClientBuilder {
+        McClientBuilder::default()
+    }
+
+    /// Retrieves the config the client was built with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Retrieves how servers are pinged, as the config says.
+    pub fn ping_options(&self) -> &PingOptions {
+        &self.ping_options
+    }
+
+    /// Retrieves the cache, with the config's account selected, reading it the first time.
+    ///
+    /// A cache which does not exist (or is disabled in the config) starts out empty.
+    pub fn cache(&mut self) -> Result<&mut Cache, Box<dyn std::error::Error>> {
+        let cache = match self.cache.take() {
+            Some(cache) => cache,
+            None => self.read_cache()?,
+        };
+
+        Ok(self.cache.insert(cache))
+    }
+
+    /// Reads the cache from disk, if caching is enabled, selecting the config's account.
+    fn read_cache(&mut self) -> Result<Cache, Box<dyn std::error::Error>> {
+        let fs_cache = self
+            .config
+            .cache_enabled
+            .then(Cache::get)
+            .unwrap_or(Ok(None))?;
+        self.cache_exists = fs_cache.is_some();
+
+        let mut cache = fs_cache.unwrap_or_default();
+        cache.select(&self.config.account);
+        cache.set_max_accounts(self.config.max_cached_accounts);
+
+        Ok(cache)
+    }
+
+    /// Whether the cache existed before the client started, rather than being started afresh.
+    ///
+    /// This is only known once the cache has been read, with [`cache`](Self::cache).
+    pub fn cache_exists(&self) -> bool {
+        self.cache_exists
+    }
+
+    /// Retrieves a Minecraft token for the config's account, from the cache if it holds a valid
+    /// one, saving any new tokens to the cache.
+    ///
+    /// The user is asked to log in on stdin if there is no token to use.
+    pub async fn token(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let cache = if let Some(cache) = &mut self.cache {
+            cache
+        } else {
+            let cache = self.read_cache()?;
+            self.cache.insert(cache)
+        };
+        let (http, endpoints, config, clock) =
+            (&self.http, &self.endpoints, &self.config, &self.clock);
+
+        let authenticate_cache = if self.cache_exists {
+            Some(&*cache)
+        } else {
+            None
+        };
+        let mut authenticate_result = authentication::authenticate(
+            http,
+            endpoints,
+            io::stdin().lock(),
+            io::stdin().is_terminal(),
+            config.auth_max_attempts,
+            authenticate_cache,
+            clock,
+        )
+        .await?;
+
+        // a cached token may have been revoked before it expired
+        if config.validate_cached_token
+            && authenticate_result.retrieve_type.is_from_cache()
+            && !authentication::validate_token(
+                http,
+                endpoints,
+                &authenticate_result.minecraft_token,
+            )
+            .await?
+        {
+            tracing::info!("Cached token was rejected, refreshing token...");
+            authenticate_result =
+                authentication::refresh(http, endpoints, cache.get_microsoft_refresh_token())
+                    .await?;
+        }
+        let token = authenticate_result.minecraft_token;
+
+        let (microsoft_refresh_token, expires_in) = match authenticate_result.retrieve_type {
+            RetrieveType::FromCache => (None, 0),
+            RetrieveType::FromUserLogin {
+                microsoft_refresh_token,
+                expires_in,
+            } => (Some(microsoft_refresh_token), expires_in),
+            RetrieveType::FromRefresh {
+                microsoft_refresh_token,
+                expires_in,
+            } => {
+                tracing::info!("Refreshed token using the cached refresh token");
+                (Some(microsoft_refresh_token), expires_in)
+            }
+        };
+
+        // save any new tokens to cache
+        if let (Some(microsoft_refresh_token), true) =
+            (microsoft_refresh_token, config.cache_enabled)
+        {
+            let expiry_time = clock.expires_at(expires_in);
+            cache.save_minecraft_token(token.clone(), expiry_time, clock)?;
+            cache.save_microsoft_refresh_token(microsoft_refresh_token, clock)?;
+        }
+
+        Ok(token)
+    }
+
+    /// Refreshes the cached token of the config's account, saving the new tokens to the cache,
+    /// and returns when the new token expires, if one was retrieved.
+    ///
+    /// The user only has to log in again (on stdin) if the cached refresh token no longer works.
+    pub async fn refresh_token(
+        &mut self,
+    ) -> Result<Option<DateTime<Utc>>, Box<dyn std::error::Error>> {
+        let cache = if let Some(cache) = &mut self.cache {
+            cache
+        } else {
+            let cache = self.read_cache()?;
+            self.cache.insert(cache)
+        };
+        let (http, endpoints, clock) = (&self.http, &self.endpoints, &self.clock);
+
+        let result = match authentication::force_refresh(http, endpoints, cache).await {
+            Ok(result) => result,
+            Err(e) if cache.get_microsoft_refresh_token().is_empty() => return Err(e),
+            Err(e) => {
+                tracing::warn!("Could not refresh the cached token ({e}), logging in again...");
+                authentication::authenticate(
+                    http,
+                    endpoints,
+                    io::stdin().lock(),
+                    io::stdin().is_terminal(),
+                    self.config.auth_max_attempts,
+                    None,
+                    clock,
+                )
+                .await?
+            }
+        };
+
+        let (RetrieveType::FromRefresh {
+            microsoft_refresh_token,
+            expires_in,
+        }
+        | RetrieveType::FromUserLogin {
+            microsoft_refresh_token,
+            expires_in,
+        }) = result.retrieve_type
+        else {
+            return Ok(None);
+        };
+
+        let expiry_time = clock.expires_at(expires_in);
+        cache.save_minecraft_token(result.minecraft_token, expiry_time, clock)?;
+        cache.save_microsoft_refresh_token(microsoft_refresh_token, clock)?;
+
+        Ok(Some(expiry_time))
+    }
+
+    /// Retrieves the status of the server at `server_address`, as
+    /// [`get_server_info`](get_server_info::get_server_info) does.
+    pub async fn ping(
+        &self,
+        server_address: &str,
+    ) -> Result<ServerStatus, Box<dyn std::error::Error>> {
+        get_server_info::get_server_info(server_address.to_string(), &self.ping_options).await
+    }
+
+    /// Joins the server at `server_address`, as [`connect`](connect::connect) does, leaving any
+    /// server joined before.
+    ///
+    /// The client joins again if the server kicks the player for one of the config's
+    /// `reconnect_reasons`, see [`reconnect_on_kick`](connect::reconnect_on_kick). Events and
+    /// chat then go through the client, see [`next_event`](Self::next_event) and
+    /// [`send_chat`](Self::send_chat).
+    ///
+    /// The player joins as the name given to the builder, or else the name on the profile of the
+    /// config's account, logging in as [`token`](Self::token) does.
+    pub async fn connect(
+        &mut self,
+        server_address: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection = None;
+        self.server_address = Some(server_address.to_string());
+
+        let username = self.username().await?;
+        let policy = self.config.reconnect_policy();
+        let connection =
+            connect::reconnect_on_kick(&policy, || self.join(server_address, &username)).await?;
+        self.connection = Some(connection);
+
+        Ok(())
+    }
+
+    /// Retrieves the name the player joins servers as, fetching the profile of the config's
+    /// account the first time if the builder was not given one.
+    async fn username(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(username) = &self.username {
+            return Ok(username.clone());
+        }
+
+        let token = self.token().await?;
+        let profile = authentication::get_profile(&self.http, &self.endpoints, &token).await?;
+        Ok(self.username.insert(profile.name).clone())
+    }
+
+    /// Joins the server at `server_address` as `username` once, as [`connect`](connect::connect)
+    /// does.
+    async fn join(
+        &self,
+        server_address: &str,
+        username: &str,
+    ) -> Result<Connection<TcpStream>, ConnectionError> {
+        connect::connect(
+            server_address,
+            username.to_string(),
+            PROTOCOL_VERSION,
+            &self.config,
+        )
+        .await
+    }
+
+    /// Retrieves the connection to the server joined with [`connect`](Self::connect), for
+    /// anything the client does not wrap.
+    pub fn connection(&mut self) -> Option<&mut Connection<TcpStream>> {
+        self.connection.as_mut()
+    }
+
+    /// Retrieves the connection, failing if no server has been joined.
+    fn joined(&mut self) -> Result<&mut Connection<TcpStream>, ConnectionError> {
+        self.connection.as_mut().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotConnected, "no server was joined").into()
+        })
+    }
+
+    /// Waits for the next event from the server joined, see [`Connection::next_event`].
+    ///
+    /// If the server kicks the player for one of the config's `reconnect_reasons` (or the
+    /// connection appears dead), the client joins it again and waits for an event from the new
+    /// connection, giving up once it has reconnected `reconnect_max_attempts` times without
+    /// receiving one.
+    pub async fn next_event(&mut self) -> Result<Event, ConnectionError> {
+        let policy = self.config.reconnect_policy();
+        let mut attempts = 0;
+
+        loop {
+            let mut error = match self.joined()?.next_event().await {
+                Err(error) => error,
+                event => return event,
+            };
+            let (Some(server_address), Some(username)) =
+                (self.server_address.clone(), self.username.clone())
+            else {
+                return Err(error);
+            };
+
+            loop {
+                if !policy.wait_to_reconnect(&error, &mut attempts).await {
+                    return Err(error);
+                }
+                match self.join(&server_address, &username).await {
+                    Ok(connection) => {
+                        self.connection = Some(connection);
+                        break;
+                    }
+                    Err(e) => error = e,
+                }
+            }
+        }
+    }
+
+    /// Sends `text` to the chat of the server joined, see [`Connection::send_chat`].
+    pub async fn send_chat(&mut self, text: &str) -> Result<(), ConnectionError> {
+        self.joined()?.send_chat(text).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::McClient;
+    use crate::{
+        authentication::test::{authentication_routes, endpoints, serve},
+        cache::test::{cache_for, TempCache},
+        clock::FixedClock,
+        config::Config,
+        get_server_info::test::serve_status,
+        protocol::{
+            chat::ChatComponent,
+            encoding::EncodedString,
+            packets::play::{chat::test::system_chat_data, Clientbound},
+            test_util::{JoinScript, MockServer},
+            ConnectionError, Event, Packet,
+        },
+    };
+
+    fn client() -> McClient {
+        McClient::builder()
+            .config(Config {
+                resolve_srv: false,
+                cache_enabled: false,
+                ..Config::default()
+            })
+            .username("Steve")
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pings_and_joins_servers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = client();
+
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_status(stream).await;
+
+            let mut server = MockServer::accept(&listener, 764).await;
+            server.join(JoinScript::default()).await;
+            server
+                .send(Packet::new(
+                    Clientbound::SystemChat.id(764),
+                    system_chat_data(r#"{"text":"Welcome!"}"#),
+                ))
+                .await;
+            server.expect(0x05).await
+        };
+        let player = async {
+            let status = client.ping(&address).await.unwrap();
+            assert_eq!(status.version.protocol, 764);
+
+            client.connect(&address).await.unwrap();
+            let event = client.next_event().await.unwrap();
+            client.send_chat("hello").await.unwrap();
+
+            event
+        };
+
+        let (chat, event) = tokio::join!(server, player);
+        match event {
+            Event::SystemChat(chat) => {
+                assert_eq!(chat.content, ChatComponent::text("Welcome!"));
+            }
+            other => panic!("expected a chat message, got {other:?}"),
+        }
+        assert_eq!(&chat.data()[..6], b"\x05hello");
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_matching_kicks() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut client = McClient::builder()
+            .config(Config {
+                resolve_srv: false,
+                cache_enabled: false,
+                reconnect_reasons: vec!["restarting".to_string()],
+                reconnect_delay: 0,
+                ..Config::default()
+            })
+            .username("Steve")
+            .build()
+            .unwrap();
+
+        let server = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            server.join(JoinScript::default()).await;
+            let reason = EncodedString::try_from(r#""Server is restarting""#.to_string());
+            server
+                .send(Packet::new(
+                    Clientbound::Disconnect.id(764),
+                    reason.unwrap().as_slice(),
+                ))
+                .await;
+            drop(server);
+
+            let mut server = MockServer::accept(&listener, 764).await;
+            server.join(JoinScript::default()).await;
+            server
+                .send(Packet::new(
+                    Clientbound::SystemChat.id(764),
+                    system_chat_data(r#"{"text":"Welcome back!"}"#),
+                ))
+                .await;
+            server
+        };
+        let player = async {
+            client.connect(&address).await.unwrap();
+            client.next_event().await.unwrap()
+        };
+
+        let (_server, event) = tokio::join!(server, player);
+        match event {
+            Event::SystemChat(chat) => {
+                assert_eq!(chat.content, ChatComponent::text("Welcome back!"));
+            }
+            other => panic!("expected a chat message, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn joins_as_the_profile_name() {
+        let profile = json!({ "id": "uuid", "name": "Alex" }).to_string();
+        let base = serve(vec![("/profile", 200, profile)]).await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let config = Config {
+            resolve_srv: false,
+            cache_enabled: false,
+            validate_cached_token: false,
+            ..Config::default()
+        };
+        let mut client = McClient::builder()
+            .cache(cache_for(&config.account, "refresh_token"))
+            .config(config)
+            .endpoints(endpoints(&base))
+            .build()
+            .unwrap();
+
+        let server = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            // the handshake, then the login start
+            server.expect(0x00).await;
+            server.expect(0x00).await
+        };
+        let (login_start, _) = tokio::join!(server, client.connect(&address));
+
+        let name = EncodedString::try_from("Alex".to_string()).unwrap();
+        assert!(login_start.data().starts_with(&name.as_slice()));
+    }
+
+    #[tokio::test]
+    async fn checks_tokens_against_the_given_clock() {
+        let base = serve(authentication_routes()).await;
+        let config = Config {
+            cache_enabled: false,
+            ..Config::default()
+        };
+        let now: DateTime<Utc> = DateTime::from_str("2022-07-01T12:00:00Z").unwrap();
+        let temp = TempCache::new("client-clock");
+        let mut cache = cache_for(&config.account, "refresh_token");
+        cache.set_path(temp.path());
+        let mut client = McClient::builder()
+            .cache(cache)
+            .config(config)
+            .endpoints(endpoints(&base))
+            .clock(FixedClock(now))
+            .build()
+            .unwrap();
+
+        let expiry_time = client.refresh_token().await.unwrap();
+        assert_eq!(expiry_time, Some(now + chrono::Duration::hours(1)));
+    }
+
+    #[tokio::test]
+    async fn needs_a_server_for_events() {
+        let mut client = client();
+
+        assert!(client.connection().is_none());
+        assert!(matches!(
+            client.next_event().await,
+            Err(ConnectionError::Io(e)) if e.kind() == std::io::ErrorKind::NotConnected
+        ));
+    }
+}
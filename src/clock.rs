@@ -0,0 +1,100 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time, so that expiry checks can be tested at a chosen instant.
+pub trait Clock {
+    /// Retrieves the current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Retrieves the time at which something lasting `expires_in` seconds from now expires.
+    fn expires_at(&self, expires_in: u32) -> DateTime<Utc> {
+        self.now() + Duration::seconds(i64::from(expires_in))
+    }
+
+    /// Retrieves how long is left until `expires_at`, rounded down to whole seconds, or `None` if
+    /// it has already passed.
+    fn remaining(&self, expires_at: DateTime<Utc>) -> Option<std::time::Duration> {
+        let seconds = (expires_at - self.now()).num_seconds();
+
+        u64::try_from(seconds)
+            .ok()
+            .filter(|seconds| *seconds > 0)
+            .map(std::time::Duration::from_secs)
+    }
+}
+
+/// Formats a time left from [`Clock::remaining`] as `HH:MM:SS`, or as `expired` if there is none.
+pub fn format_remaining(remaining: Option<std::time::Duration>) -> String {
+    let Some(remaining) = remaining else {
+        return String::from("expired");
+    };
+
+    let seconds = remaining.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The clock of the system, which is used outside of tests.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+/// A clock which is frozen at a single instant.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use chrono::{DateTime, Duration};
+
+    use super::{format_remaining, Clock, FixedClock};
+
+    #[test]
+    fn computes_expiry_from_now() {
+        let now = DateTime::from_str("2022-07-01T12:00:00Z").unwrap();
+        let clock = FixedClock(now);
+
+        assert_eq!(clock.now(), now);
+        assert_eq!(clock.expires_at(3600), now + Duration::hours(1));
+    }
+
+    #[test]
+    fn formats_remaining_time() {
+        let now = DateTime::from_str("2022-07-01T12:00:00Z").unwrap();
+        let clock = FixedClock(now);
+        let remaining = |duration| format_remaining(clock.remaining(now + duration));
+
+        assert_eq!(remaining(Duration::milliseconds(59_900)), "00:00:59");
+        assert_eq!(
+            remaining(Duration::seconds(3 * 3600 + 25 * 60 + 7)),
+            "03:25:07"
+        );
+        assert_eq!(remaining(Duration::hours(100)), "100:00:00");
+        assert_eq!(remaining(Duration::zero()), "expired");
+        assert_eq!(remaining(Duration::seconds(-30)), "expired");
+    }
+}
@@ -1,34 +1,553 @@
-use std::fs;
+use std::{fmt::Write as _, fs, io::Write, net::SocketAddr, path::Path, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
-const CONFIG_PATH: &str = "config.toml";
+use crate::{
+    authentication::DEFAULT_AUTH_MAX_ATTEMPTS,
+    cache::{DEFAULT_ACCOUNT, DEFAULT_MAX_ACCOUNTS},
+    connect::{ConnectDelays, ReconnectPolicy},
+    get_server_info::UNIX_SOCKET_PREFIX,
+    protocol::{
+        packets::configuration::ClientInformation, proxy::ProxyProtocol, ResponseFaults,
+        DEFAULT_LOW_HEALTH_THRESHOLD, DEFAULT_RESOURCE_PACK_LIMIT, PLAY_VERSIONS,
+    },
+    resolve::AddressFamily,
+    util::parse_server_address,
+};
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub const CONFIG_PATH: &str = "config.toml";
+
+/// The longest string the protocol allows, in characters, which bounds the client brand.
+const MAX_STRING_LENGTH: usize = 32_767;
+
+/// The comment written above each setting of a generated config.
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("cache_enabled", "If tokens are cached on disk, so signing in is only needed once"),
+    (
+        "validate_cached_token",
+        "If a cached token is checked with Minecraft services before it is used",
+    ),
+    ("account", "The name of the account whose tokens are cached and used"),
+    (
+        "max_cached_accounts",
+        "How many accounts are kept in the cache, evicting the least recently used",
+    ),
+    (
+        "auth_max_attempts",
+        "How many authorization codes may be entered when logging in, before giving up",
+    ),
+    ("server_url", "The address of the server, with or without a port"),
+    (
+        "resolve_srv",
+        "If the server's SRV record is followed, when its address has no port",
+    ),
+    (
+        "address_family",
+        "Which addresses of the server are connected to: \"auto\" (any), \"v4\", or \"v6\"",
+    ),
+    (
+        "locale",
+        "The language sent to the server, in the xx_YY form (e.g., en_US)",
+    ),
+    (
+        "chat_mode",
+        "If the client wants chat messages (0), only command feedback (1), or nothing (2)",
+    ),
+    ("main_hand", "If the player is left handed (0) or right handed (1)"),
+    (
+        "view_distance",
+        "How many chunks out from the player the server sends, from 2 to 32",
+    ),
+    (
+        "client_brand",
+        "The brand the client names itself with to the server, e.g., vanilla or a custom name",
+    ),
+    (
+        "insecure_chat",
+        "If unsigned chat is sent to servers which enforce secure chat",
+    ),
+    (
+        "lenient_decoding",
+        "If packets which can not be decoded are logged with their bytes and skipped, rather than ending the connection",
+    ),
+    (
+        "resource_pack_limit",
+        "How many forced resource packs are declined before giving up on a server which requires one",
+    ),
+    ("auto_respawn", "If the player is respawned as soon as they die"),
+    (
+        "low_health_threshold",
+        "The health (out of 20) below which the player's health counts as low",
+    ),
+    (
+        "reconnect_reasons",
+        "Text in the reasons for being kicked after which the client reconnects, rather than exiting",
+    ),
+    (
+        "reconnect_delay",
+        "How long, in seconds, the client waits before reconnecting after being kicked",
+    ),
+    (
+        "reconnect_max_attempts",
+        "How many times in a row the client reconnects before giving up",
+    ),
+    (
+        "fallback_protocols",
+        "The protocol versions (764 or 765) tried in order when the server does not let the player in on the client's own",
+    ),
+    (
+        "handshake_delay",
+        "How long, in milliseconds, the client waits after connecting before sending the handshake",
+    ),
+    (
+        "status_delay",
+        "How long, in milliseconds, the client waits after the handshake before requesting the status, when pinging",
+    ),
+    (
+        "ping_concurrency",
+        "How many servers are pinged at once, when pinging many servers",
+    ),
+];
+
+/// The comment and an example value of each setting which is unset by default, written commented
+/// out at the end of a generated config.
+const OPTIONAL_SETTINGS: &[(&str, &str, &str)] = &[
+    (
+        "bind_address",
+        "The local address connections are made from, to choose which network interface is used",
+        "\"192.168.1.2:0\"",
+    ),
+    (
+        "handshake_host",
+        "The address sent in the handshake in place of the server's, e.g., a virtual host on a shared proxy",
+        "\"play.example.com\"",
+    ),
+    (
+        "idle_timeout",
+        "How long, in seconds, the client stays connected while receiving nothing but keep alives",
+        "600",
+    ),
+    (
+        "dead_connection_timeout",
+        "How long, in seconds, the client waits on a server which sends nothing at all, not even keep alives, before reconnecting",
+        "60",
+    ),
+    (
+        "anti_afk_interval",
+        "How often, in seconds, the player is turned slightly so servers do not kick them for being idle",
+        "45",
+    ),
+    (
+        "send_rate",
+        "How many packets are sent each second at most, so servers do not kick the player for spamming",
+        "20",
+    ),
+    (
+        "response_delay",
+        "How long, in milliseconds, responses to the server's keep alives and pings are held back, to test how the server handles a slow client",
+        "5000",
+    ),
+    (
+        "response_drop_probability",
+        "The chance, from 0 to 1, that a response to the server's keep alives and pings is dropped, to test how the server handles an unreliable client",
+        "0.5",
+    ),
+    (
+        "proxy_protocol",
+        "The PROXY protocol header (v1 or v2) sent before the handshake, for servers behind a frontend",
+        "\"v2\"",
+    ),
+];
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+// each setting is an independent switch, rather than a state machine
+#[allow(clippy::struct_excessive_bools)]
+// fields missing from older config files take their default value
+#[serde(default)]
 pub struct Config {
     /// If caching is enabled for disk storage
     pub cache_enabled: bool,
+    /// If a cached token is checked with Minecraft services before it is used
+    pub validate_cached_token: bool,
+    /// The name of the account whose tokens are cached and used
+    pub account: String,
+    /// How many accounts are kept in the cache, evicting the least recently used
+    pub max_cached_accounts: usize,
+    /// How many authorization codes may be entered when logging in, before giving up
+    pub auth_max_attempts: u32,
     /// The address of the server
     pub server_url: String,
+    /// If the server's SRV record is followed, when its address has no port
+    pub resolve_srv: bool,
+    /// Which addresses of the server are connected to, for servers with both IPv4 and IPv6
+    /// addresses
+    pub address_family: AddressFamily,
+    /// The local address (e.g., `192.168.1.2:0`) connections are made from, to choose which
+    /// network interface is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<SocketAddr>,
+    /// The address sent in the handshake in place of the one connected to (e.g., a virtual host
+    /// on a shared proxy), if it is overridden, both when joining and when pinging
+    ///
+    /// In online mode, the server checks the player's session with Mojang whatever the handshake
+    /// says, so the override does not change which account is let in. Proxies which forward the
+    /// player's details in the handshake address (e.g., the IP forwarding of `BungeeCord`) may
+    /// reject or misread an overridden one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handshake_host: Option<String>,
+    /// The language sent to the server, in the `xx_YY` form (e.g., `en_US`)
+    pub locale: String,
+    /// If the client wants chat messages (0), only command feedback (1), or nothing (2)
+    pub chat_mode: i32,
+    /// If the player is left handed (0) or right handed (1)
+    pub main_hand: i32,
+    /// How many chunks out from the player the server sends, from 2 to 32
+    pub view_distance: i8,
+    /// The brand the client names itself with to the server (e.g., `vanilla`), on the
+    /// `minecraft:brand` plugin channel during configuration
+    pub client_brand: String,
+    /// If unsigned chat is sent to servers which enforce secure chat
+    pub insecure_chat: bool,
+    /// If packets which can not be decoded are logged with their bytes and skipped, rather than
+    /// ending the connection (unless it can not go on without them)
+    pub lenient_decoding: bool,
+    /// How many forced resource packs are declined before giving up on a server which requires one
+    pub resource_pack_limit: u32,
+    /// If the player is respawned as soon as they die, which suits bots left running unattended
+    pub auto_respawn: bool,
+    /// The health (out of 20) below which the player's health counts as low
+    pub low_health_threshold: f32,
+    /// Text in the reasons for being kicked (e.g., `Server is restarting`) after which the client
+    /// reconnects, rather than exiting
+    pub reconnect_reasons: Vec<String>,
+    /// How long, in seconds, the client waits before reconnecting after being kicked
+    pub reconnect_delay: u64,
+    /// How many times in a row the client reconnects before giving up
+    pub reconnect_max_attempts: u32,
+    /// How long, in seconds, the client stays connected while receiving nothing but keep alives,
+    /// if it ever disconnects for being idle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_timeout: Option<u64>,
+    /// How long, in seconds, the client waits on a server which sends nothing at all (not even keep
+    /// alives) before taking the connection to be dead and reconnecting, if it ever does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_connection_timeout: Option<u64>,
+    /// How often, in seconds, the player is turned slightly so servers do not kick them for being
+    /// idle, if they are
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anti_afk_interval: Option<u64>,
+    /// How many packets are sent each second at most, so servers do not kick the player for
+    /// spamming, if sending is limited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_rate: Option<u32>,
+    /// How long, in milliseconds, responses to the server's keep alives and pings are held back,
+    /// if they are (for testing how the server handles a slow client)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_delay: Option<u64>,
+    /// The chance, from 0 to 1, that a response to the server's keep alives and pings is dropped,
+    /// if any are (for testing how the server handles an unreliable client)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_drop_probability: Option<f64>,
+    /// The protocol versions (each one of [`PLAY_VERSIONS`]) tried in order when the server does
+    /// not let the player in on the client's own
+    pub fallback_protocols: Vec<i32>,
+    /// How long, in milliseconds, the client waits after connecting before sending the handshake
+    pub handshake_delay: u64,
+    /// How long, in milliseconds, the client waits after the handshake before requesting the
+    /// status, when pinging
+    pub status_delay: u64,
+    /// How many servers are pinged at once, when pinging many servers
+    pub ping_concurrency: usize,
+    /// The PROXY protocol header (`v1` or `v2`) sent before the handshake, for servers behind a
+    /// frontend which requires one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_protocol: Option<ProxyProtocol>,
 }
 
 impl std::default::Default for Config {
     fn default() -> Self {
         Self {
             cache_enabled: true,
+            validate_cached_token: false,
+            account: String::from(DEFAULT_ACCOUNT),
+            max_cached_accounts: DEFAULT_MAX_ACCOUNTS,
+            auth_max_attempts: DEFAULT_AUTH_MAX_ATTEMPTS,
             server_url: String::from("localhost:25565"),
+            resolve_srv: true,
+            address_family: AddressFamily::Auto,
+            bind_address: None,
+            handshake_host: None,
+            locale: String::from("en_US"),
+            chat_mode: 0,
+            main_hand: 1,
+            view_distance: 10,
+            client_brand: String::from("vanilla"),
+            insecure_chat: false,
+            lenient_decoding: false,
+            resource_pack_limit: DEFAULT_RESOURCE_PACK_LIMIT,
+            auto_respawn: false,
+            low_health_threshold: DEFAULT_LOW_HEALTH_THRESHOLD,
+            reconnect_reasons: Vec::new(),
+            reconnect_delay: 10,
+            reconnect_max_attempts: 5,
+            idle_timeout: None,
+            dead_connection_timeout: None,
+            anti_afk_interval: None,
+            send_rate: None,
+            response_delay: None,
+            response_drop_probability: None,
+            fallback_protocols: Vec::new(),
+            handshake_delay: 0,
+            status_delay: 0,
+            ping_concurrency: 16,
+            proxy_protocol: None,
+        }
+    }
+}
+
+impl Config {
+    /// Checks that every field holds a value the client can use.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Unix domain socket paths are not host and port addresses
+        if !self.server_url.starts_with(UNIX_SOCKET_PREFIX) {
+            parse_server_address(&self.server_url)?;
+        }
+
+        if !is_valid_locale(&self.locale) {
+            return Err(format!(
+                "locale '{}' is not of the form 'xx_YY' (e.g., 'en_US')",
+                self.locale
+            )
+            .into());
+        }
+
+        if !(0..=2).contains(&self.chat_mode) {
+            return Err(format!(
+                "chat_mode {} must be 0 (enabled), 1 (commands only), or 2 (hidden)",
+                self.chat_mode
+            )
+            .into());
+        }
+
+        if !(0..=1).contains(&self.main_hand) {
+            return Err(
+                format!("main_hand {} must be 0 (left) or 1 (right)", self.main_hand).into(),
+            );
+        }
+
+        if self.client_brand.is_empty() {
+            return Err("client_brand must not be empty".into());
+        }
+        if self.client_brand.chars().count() > MAX_STRING_LENGTH {
+            return Err(
+                format!("client_brand must be at most {MAX_STRING_LENGTH} characters").into(),
+            );
+        }
+
+        if !(2..=32).contains(&self.view_distance) {
+            return Err(format!(
+                "view_distance {} must be from 2 to 32 chunks",
+                self.view_distance
+            )
+            .into());
+        }
+
+        if !(0.0..=20.0).contains(&self.low_health_threshold) {
+            return Err(format!(
+                "low_health_threshold {} must be from 0 to 20",
+                self.low_health_threshold
+            )
+            .into());
+        }
+
+        if self.handshake_host.as_deref().is_some_and(str::is_empty) {
+            return Err("handshake_host must not be empty".into());
+        }
+
+        if self.idle_timeout == Some(0) {
+            return Err("idle_timeout must be at least 1 second".into());
+        }
+
+        if self.dead_connection_timeout == Some(0) {
+            return Err("dead_connection_timeout must be at least 1 second".into());
+        }
+
+        if self.anti_afk_interval == Some(0) {
+            return Err("anti_afk_interval must be at least 1 second".into());
+        }
+
+        if self.send_rate == Some(0) {
+            return Err("send_rate must be at least 1 packet per second".into());
+        }
+
+        if let Some(probability) = self.response_drop_probability {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(
+                    format!("response_drop_probability {probability} must be from 0 to 1").into(),
+                );
+            }
+        }
+
+        if self.max_cached_accounts < 1 {
+            return Err("max_cached_accounts must be at least 1".into());
+        }
+
+        if self.auth_max_attempts < 1 {
+            return Err("auth_max_attempts must be at least 1".into());
+        }
+
+        if self.ping_concurrency < 1 {
+            return Err("ping_concurrency must be at least 1".into());
+        }
+
+        // the player would be kicked once in, as play is only spoken on these versions
+        if let Some(version) = self
+            .fallback_protocols
+            .iter()
+            .find(|version| !PLAY_VERSIONS.contains(version))
+        {
+            return Err(format!(
+                "fallback protocol {version} is not supported, which must be from {} to {}",
+                PLAY_VERSIONS.start(),
+                PLAY_VERSIONS.end()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Builds how long the client waits at each step of opening a connection.
+    pub fn connect_delays(&self) -> ConnectDelays {
+        ConnectDelays {
+            handshake: Duration::from_millis(self.handshake_delay),
+            status: Duration::from_millis(self.status_delay),
+        }
+    }
+
+    /// Builds when the client reconnects to servers which kick the player.
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        ReconnectPolicy {
+            reasons: self.reconnect_reasons.clone(),
+            delay: Duration::from_secs(self.reconnect_delay),
+            max_attempts: self.reconnect_max_attempts,
+        }
+    }
+
+    /// Builds the faults put into the responses to the server's keep alives and pings, if any are.
+    pub fn response_faults(&self) -> Option<ResponseFaults> {
+        if self.response_delay.is_none() && self.response_drop_probability.is_none() {
+            return None;
+        }
+
+        Some(ResponseFaults {
+            delay: Duration::from_millis(self.response_delay.unwrap_or_default()),
+            drop_probability: self.response_drop_probability.unwrap_or_default(),
+            seed: None,
+        })
+    }
+
+    /// Builds the client settings sent to the server during configuration.
+    pub fn client_information(&self) -> ClientInformation {
+        ClientInformation {
+            locale: self.locale.clone(),
+            chat_mode: self.chat_mode,
+            main_hand: self.main_hand,
+            view_distance: self.view_distance,
+            ..ClientInformation::default()
         }
     }
 }
 
-pub fn get() -> Result<Config, Box<dyn std::error::Error>> {
+/// Checks that `locale` looks like a language and region code, such as `en_US` or `fil_PH`.
+fn is_valid_locale(locale: &str) -> bool {
+    let Some((language, region)) = locale.split_once('_') else {
+        return false;
+    };
+
+    [language, region]
+        .iter()
+        .all(|part| (2..=3).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Builds a config file holding every setting at its default value, each with a comment
+/// describing it.
+pub fn generate() -> Result<String, Box<dyn std::error::Error>> {
+    let mut document: toml_edit::Document =
+        toml_edit::easy::to_string_pretty(&Config::default())?.parse()?;
+
+    for (key, description) in DESCRIPTIONS {
+        let decor = document
+            .as_table_mut()
+            .key_decor_mut(key)
+            .ok_or_else(|| format!("the default config has no setting '{key}'"))?;
+        decor.set_prefix(format!("\n# {description}\n"));
+    }
+
+    let mut config = format!(
+        "# The configuration file for the minecraft console client.\n{document}\n\
+         # Settings which are unset by default, and take effect once uncommented\n"
+    );
+    for (key, description, example) in OPTIONAL_SETTINGS {
+        write!(config, "\n# {description}\n# {key} = {example}\n")?;
+    }
+
+    Ok(config)
+}
+
+/// Writes a [generated](generate) config to `path`, failing if a file is already there unless
+/// `force` is set.
+pub fn write_generated(path: &Path, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config = generate()?;
+
+    let mut file = if force {
+        fs::File::create(path)
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+    }
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => format!(
+            "{} already exists, pass --force to overwrite it",
+            path.display()
+        ),
+        _ => format!("could not write config to {}: {e}", path.display()),
+    })?;
+    file.write_all(config.as_bytes())?;
+
+    Ok(())
+}
+
+/// Parses the contents of a config file, ignoring settings it does not know unless `strict` is
+/// set, in which case the first of them is an error (e.g., to catch typos).
+pub fn parse(config: &str, strict: bool) -> Result<Config, Box<dyn std::error::Error>> {
+    if strict {
+        let document: toml_edit::Document = config.parse()?;
+        let is_known = |key: &str| {
+            DESCRIPTIONS.iter().any(|(known, _)| *known == key)
+                || OPTIONAL_SETTINGS.iter().any(|(known, _, _)| *known == key)
+        };
+
+        let unknown = document.iter().find(|(key, _)| !is_known(key));
+        if let Some((key, _)) = unknown {
+            return Err(format!("unknown setting '{key}' in the config").into());
+        }
+    }
+
+    Ok(toml_edit::easy::from_str(config)?)
+}
+
+/// Reads the config from [`CONFIG_PATH`], creating it with the default settings if there is
+/// none, and rejecting unknown settings if `strict` is set (see [`parse`]).
+pub fn get(strict: bool) -> Result<Config, Box<dyn std::error::Error>> {
     let config = match fs::read_to_string(CONFIG_PATH) {
-        Ok(config) => toml_edit::easy::from_str(&config)?,
+        Ok(config) => parse(&config, strict)?,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             // create config
-            println!(
-                "Existing config could not be found, creating new config at {}",
-                CONFIG_PATH
+            tracing::info!(
+                "Existing config could not be found, creating new config at {CONFIG_PATH}"
             );
             let config = Config::default();
 
@@ -42,6 +561,238 @@ pub fn get() -> Result<Config, Box<dyn std::error::Error>> {
         }
         Err(e) => return Err(Box::new(e)),
     };
+    config.validate()?;
 
     Ok(config)
 }
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use crate::protocol::{
+        packets::configuration::ClientInformation, proxy::ProxyProtocol, Packet,
+    };
+
+    #[test]
+    fn validates_locale() {
+        for locale in ["en_US", "en_us", "fil_PH"] {
+            let config = Config {
+                locale: locale.to_string(),
+                ..Config::default()
+            };
+            assert!(config.validate().is_ok(), "{locale} should be valid");
+        }
+
+        for locale in ["", "english", "en-US", "e_US", "en_U5"] {
+            let config = Config {
+                locale: locale.to_string(),
+                ..Config::default()
+            };
+            assert!(config.validate().is_err(), "{locale} should be invalid");
+        }
+    }
+
+    #[test]
+    fn validates_client_brand() {
+        for (brand, valid) in [
+            (String::from("my-bot"), true),
+            (String::new(), false),
+            ("a".repeat(32_767), true),
+            ("a".repeat(32_768), false),
+        ] {
+            let config = Config {
+                client_brand: brand,
+                ..Config::default()
+            };
+            assert_eq!(config.validate().is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn validates_server_address() {
+        for server_url in ["example.com", "[::1]:25566", "unix:/run/minecraft.sock"] {
+            let config = Config {
+                server_url: server_url.to_string(),
+                ..Config::default()
+            };
+            assert!(config.validate().is_ok(), "{server_url} should be valid");
+        }
+
+        let config = Config {
+            server_url: "example.com:port".to_string(),
+            ..Config::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validates_auth_max_attempts() {
+        let config = Config {
+            auth_max_attempts: 0,
+            ..Config::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validates_ping_concurrency() {
+        let config = Config {
+            ping_concurrency: 0,
+            ..Config::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validates_fallback_protocols() {
+        for (fallback_protocols, valid) in [(vec![765], true), (vec![765, 763], false)] {
+            let config = Config {
+                fallback_protocols,
+                ..Config::default()
+            };
+
+            assert_eq!(config.validate().is_ok(), valid);
+        }
+    }
+
+    #[test]
+    fn validates_client_settings() {
+        for (chat_mode, main_hand) in [(-1, 1), (3, 1), (0, -1), (0, 2)] {
+            let config = Config {
+                chat_mode,
+                main_hand,
+                ..Config::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn sends_configured_client_settings() {
+        let config = Config {
+            chat_mode: 2,
+            main_hand: 0,
+            ..Config::default()
+        };
+        let client_information = ClientInformation {
+            view_distance: 4,
+            ..config.client_information()
+        };
+        let packet = Packet::try_from(client_information).unwrap();
+
+        // locale, view distance, chat mode, chat colors, skin parts, main hand, filtering,
+        // listing
+        assert_eq!(packet.data(), b"\x05en_US\x04\x02\x01\x7f\x00\x00\x01");
+    }
+
+    #[test]
+    fn sends_configured_view_distance() {
+        let config = Config {
+            view_distance: 2,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let packet = Packet::try_from(config.client_information()).unwrap();
+        // the view distance follows the locale
+        assert_eq!(packet.data()[6], 2);
+
+        for view_distance in [1, 33] {
+            let config = Config {
+                view_distance,
+                ..Config::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn sends_configured_locale() {
+        let config = Config {
+            locale: String::from("de_DE"),
+            ..Config::default()
+        };
+        let packet = Packet::try_from(config.client_information()).unwrap();
+
+        assert!(packet.data().starts_with(b"\x05de_DE"));
+    }
+
+    #[test]
+    fn fills_missing_fields_with_defaults() {
+        let config: Config =
+            toml_edit::easy::from_str("cache_enabled = false\nserver_url = \"example.com\"")
+                .unwrap();
+
+        assert_eq!(config.locale, "en_US");
+        assert!(!config.cache_enabled);
+        assert_eq!(config.proxy_protocol, None);
+    }
+
+    #[test]
+    fn rejects_unknown_settings_when_strict() {
+        let config = "cache_enabled = false\nsever_url = \"example.com\"";
+
+        let error = super::parse(config, true).unwrap_err();
+        assert!(error.to_string().contains("'sever_url'"), "{error}");
+
+        let config = super::parse(config, false).unwrap();
+        assert!(!config.cache_enabled);
+        assert_eq!(config.server_url, Config::default().server_url);
+    }
+
+    #[test]
+    fn generates_default_config() {
+        let generated = super::generate().unwrap();
+        let config: Config = toml_edit::easy::from_str(&generated).unwrap();
+        assert_eq!(config, Config::default());
+
+        for (key, _) in super::DESCRIPTIONS {
+            assert!(
+                generated.contains(&format!("\n{key} = ")),
+                "{key} is missing"
+            );
+        }
+
+        // every optional setting is valid once uncommented
+        let uncommented: String = generated
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting) if setting.contains(" = ") => format!("{setting}\n"),
+                _ => format!("{line}\n"),
+            })
+            .collect();
+        let config = super::parse(&uncommented, true).unwrap();
+        assert_eq!(config.idle_timeout, Some(600));
+        assert_eq!(config.proxy_protocol, Some(ProxyProtocol::V2));
+        assert!(config.bind_address.is_some());
+    }
+
+    #[test]
+    fn refuses_to_overwrite_config() {
+        let path = std::env::temp_dir().join(format!(
+            "minecraft-console-client-test-config-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, "server_url = \"example.com\"").unwrap();
+
+        let refused = super::write_generated(&path, false);
+        let kept = std::fs::read_to_string(&path).unwrap();
+        let forced = super::write_generated(&path, true);
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(refused.unwrap_err().to_string().contains("--force"));
+        assert_eq!(kept, "server_url = \"example.com\"");
+        assert!(forced.is_ok());
+        assert_eq!(written, super::generate().unwrap());
+    }
+
+    #[test]
+    fn reads_proxy_protocol() {
+        let config: Config = toml_edit::easy::from_str("proxy_protocol = \"v2\"").unwrap();
+
+        assert_eq!(config.proxy_protocol, Some(ProxyProtocol::V2));
+    }
+}
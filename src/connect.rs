@@ -0,0 +1,818 @@
+use std::{future::Future, io, net::SocketAddr, time::Duration};
+
+use tokio::net::{TcpSocket, TcpStream};
+
+use crate::{
+    config::Config,
+    protocol::{packets::ServerStatus, Connection, ConnectionError, State},
+    resolve::{self, AddressFamily, SystemResolver},
+};
+
+/// The most protocol versions [`try_protocols`] tries, however many fallbacks are configured.
+const MAX_PROTOCOL_ATTEMPTS: usize = 8;
+
+/// How long the client waits at each step of opening a connection, to connect more like a person
+/// than a fast bot (e.g., to probe servers which fingerprint how quickly clients connect).
+///
+/// No delay is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectDelays {
+    /// How long the client waits after connecting before sending the handshake.
+    pub handshake: Duration,
+    /// How long the client waits after the handshake before requesting the status, when pinging.
+    pub status: Duration,
+}
+
+/// When the client reconnects to a server which kicked the player, see [`reconnect_on_kick`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Text in the reasons for being kicked (ignoring case, e.g., "Server is restarting") after
+    /// which the client reconnects. Blank reasons are ignored, rather than matching every kick.
+    pub reasons: Vec<String>,
+    /// How long the client waits before each reconnect.
+    pub delay: Duration,
+    /// How many times in a row the client reconnects before giving up.
+    pub max_attempts: u32,
+}
+
+impl ReconnectPolicy {
+    /// Checks if the client reconnects after `error`: a kick for one of the
+    /// [`reasons`](Self::reasons), or the connection appearing dead, as the server most likely
+    /// went away without a word.
+    pub fn reconnects_after(&self, error: &ConnectionError) -> bool {
+        match error {
+            ConnectionError::Disconnected { reason_plain, .. } => {
+                let lowercase = reason_plain.to_lowercase();
+                self.reasons
+                    .iter()
+                    .map(|reason| reason.trim())
+                    .filter(|reason| !reason.is_empty())
+                    .any(|reason| lowercase.contains(&reason.to_lowercase()))
+            }
+            ConnectionError::ConnectionDead(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Waits to reconnect after `error`, counting the reconnect in `attempts`, and returns
+    /// `true`, unless the client does not reconnect after it or has already reconnected
+    /// [`max_attempts`](Self::max_attempts) times.
+    pub async fn wait_to_reconnect(&self, error: &ConnectionError, attempts: &mut u32) -> bool {
+        if *attempts >= self.max_attempts || !self.reconnects_after(error) {
+            return false;
+        }
+        *attempts += 1;
+
+        tracing::info!(
+            "{error}, reconnecting in {}s (attempt {attempts} of {})...",
+            self.delay.as_secs(),
+            self.max_attempts
+        );
+        tokio::time::sleep(self.delay).await;
+        true
+    }
+}
+
+/// Waits for `delay`, returning straight away if there is none.
+pub async fn wait(delay: Duration) {
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Connects to the server at `server_address` and logs in as `username`, returning the
+/// connection once it has reached the Play state.
+///
+/// If the server does not let the player in on `protocol_version`, each of the config's
+/// `fallback_protocols` is tried in turn, see [`try_protocols`].
+pub async fn connect(
+    server_address: &str,
+    username: String,
+    protocol_version: i32,
+    config: &Config,
+) -> Result<Connection<TcpStream>, ConnectionError> {
+    try_protocols(
+        protocol_version,
+        &config.fallback_protocols,
+        |protocol_version| {
+            connect_with_protocol(server_address, username.clone(), protocol_version, config)
+        },
+    )
+    .await
+}
+
+/// Connects to the server and logs in as [`connect`] does, on `protocol_version` alone.
+async fn connect_with_protocol(
+    server_address: &str,
+    username: String,
+    protocol_version: i32,
+    config: &Config,
+) -> Result<Connection<TcpStream>, ConnectionError> {
+    let (host, port) =
+        resolve::resolve(&SystemResolver, server_address, config.resolve_srv).await?;
+    let stream = open_stream(&host, port, config.bind_address, config.address_family).await?;
+    let socket_addr = stream.peer_addr()?;
+    let local_addr = stream.local_addr()?;
+
+    let mut connection = Connection::new(stream, protocol_version);
+    connection.set_resource_pack_limit(config.resource_pack_limit);
+    connection.set_client_brand(Some(config.client_brand.clone()));
+    connection.set_auto_respawn(config.auto_respawn);
+    connection.set_insecure_chat(config.insecure_chat);
+    connection.set_lenient_decoding(config.lenient_decoding);
+    connection.set_low_health_threshold(config.low_health_threshold);
+    connection.set_idle_timeout(config.idle_timeout.map(Duration::from_secs));
+    connection.set_dead_connection_timeout(config.dead_connection_timeout.map(Duration::from_secs));
+    connection.set_anti_afk_interval(config.anti_afk_interval.map(Duration::from_secs));
+    connection.set_send_rate(config.send_rate);
+    connection.set_response_faults(config.response_faults());
+    if let Some(proxy_protocol) = config.proxy_protocol {
+        connection
+            .send_proxy_header(proxy_protocol, local_addr, socket_addr)
+            .await?;
+    }
+    wait(config.connect_delays().handshake).await;
+    let handshake_host = config
+        .handshake_host
+        .clone()
+        .unwrap_or_else(|| socket_addr.ip().to_string());
+    // the handshake is sent in the same write as the login start
+    connection.handshake_buffered(handshake_host, socket_addr.port(), false)?;
+    connection.login(username, None).await?;
+
+    if connection.state() == State::Configuration {
+        connection.configure(config.client_information()).await?;
+    }
+
+    Ok(connection)
+}
+
+/// Runs `attempt` with `protocol_version`, and then with each of the `fallbacks` in order until
+/// one succeeds, returning the error of the last if none do.
+///
+/// Each protocol version is only tried once, and at most [`MAX_PROTOCOL_ATTEMPTS`] are tried.
+/// Only errors another protocol version may fix are retried, see [`protocol_may_fix`].
+pub async fn try_protocols<T, A, AttemptFuture>(
+    protocol_version: i32,
+    fallbacks: &[i32],
+    mut attempt: A,
+) -> Result<T, ConnectionError>
+where
+    A: FnMut(i32) -> AttemptFuture,
+    AttemptFuture: Future<Output = Result<T, ConnectionError>>,
+{
+    let mut tried = Vec::new();
+    let mut protocol_versions = std::iter::once(protocol_version)
+        .chain(fallbacks.iter().copied())
+        .filter(|version| {
+            let untried = !tried.contains(version);
+            tried.push(*version);
+            untried
+        })
+        .take(MAX_PROTOCOL_ATTEMPTS)
+        .peekable();
+
+    loop {
+        let version = protocol_versions
+            .next()
+            .expect("there is always at least one protocol version");
+        match attempt(version).await {
+            Err(e) if protocol_may_fix(&e) => match protocol_versions.peek() {
+                Some(next) => {
+                    tracing::info!("Could not join on protocol {version} ({e}), trying {next}...");
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Checks if joining on another protocol version may get past `error`: the server kicking the
+/// player (e.g., as an outdated client), or sending a packet the protocol version does not expect
+/// while logging in or configuring.
+///
+/// Rejected sessions, and everything else (e.g., the server being unreachable), are the same
+/// whichever protocol version is spoken.
+fn protocol_may_fix(error: &ConnectionError) -> bool {
+    match error {
+        ConnectionError::Disconnected { .. } => !error.is_auth_failure(),
+        ConnectionError::UnexpectedPacket { state, .. } => {
+            matches!(state, State::Login | State::Configuration)
+        }
+        _ => false,
+    }
+}
+
+/// Opens a TCP connection to `host` on `port`, from the local `bind_address` if there is one
+/// (e.g., to choose which of several network interfaces is used).
+///
+/// Only the addresses of the `family` are tried, in the order they were resolved.
+pub async fn open_stream(
+    host: &str,
+    port: u16,
+    bind_address: Option<SocketAddr>,
+    family: AddressFamily,
+) -> io::Result<TcpStream> {
+    let addresses = family.filter(tokio::net::lookup_host((host, port)).await?);
+    let Some(bind_address) = bind_address else {
+        if addresses.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{host} has no address of the configured family ({family:?})"),
+            ));
+        }
+
+        return TcpStream::connect(addresses.as_slice()).await;
+    };
+
+    // only an address of the same family as the bind address can be reached from it
+    let address = addresses
+        .into_iter()
+        .find(|address| address.is_ipv4() == bind_address.is_ipv4())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::AddrNotAvailable,
+                format!("{host} has no address reachable from the bind address {bind_address}"),
+            )
+        })?;
+
+    let socket = if bind_address.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket
+        .bind(bind_address)
+        .map_err(|e| io::Error::new(e.kind(), format!("could not bind to {bind_address}: {e}")))?;
+
+    socket.connect(address).await
+}
+
+/// Checks whether unsigned chat may be sent to a server with the given `status`.
+///
+/// Servers which enforce secure chat kick players who send unsigned messages, so chat to them is
+/// refused unless `insecure_chat` is set.
+pub fn check_unsigned_chat(
+    status: &ServerStatus,
+    insecure_chat: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if status.enforces_secure_chat != Some(true) {
+        tracing::info!("Chat messages are sent unsigned");
+        return Ok(());
+    }
+
+    if !insecure_chat {
+        return Err(
+            "server enforces secure chat, which is unsupported; pass --insecure-chat \
+            to send unsigned chat anyway"
+                .into(),
+        );
+    }
+
+    tracing::warn!("Sending unsigned chat to a server which enforces secure chat");
+    Ok(())
+}
+
+/// Runs `join` with the Minecraft `token`, and if the server rejects the session (e.g., because
+/// the token expired during a long-running session), retrieves a new token with `refresh` and
+/// retries exactly once.
+///
+/// Any other error, or a second rejection, is returned as-is.
+pub async fn join_with_token_refresh<T, J, JoinFuture, R, RefreshFuture>(
+    token: String,
+    mut join: J,
+    refresh: R,
+) -> Result<T, Box<dyn std::error::Error>>
+where
+    J: FnMut(String) -> JoinFuture,
+    JoinFuture: Future<Output = Result<T, ConnectionError>>,
+    R: FnOnce() -> RefreshFuture,
+    RefreshFuture: Future<Output = Result<String, Box<dyn std::error::Error>>>,
+{
+    match join(token).await {
+        Err(e) if e.is_auth_failure() => {
+            tracing::info!("Server rejected the session ({e}), refreshing token and retrying...");
+
+            // `refresh` can only be called once, so this can never retry more than once
+            let token = refresh().await?;
+            Ok(join(token).await?)
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Runs `run`, and runs it again each time it fails with an error `policy` reconnects after
+/// (see [`ReconnectPolicy::reconnects_after`]), until the policy's attempts run out.
+///
+/// Kicks for any other reason (e.g., a ban), and any other error, are returned as-is.
+pub async fn reconnect_on_kick<T, R, RunFuture>(
+    policy: &ReconnectPolicy,
+    mut run: R,
+) -> Result<T, ConnectionError>
+where
+    R: FnMut() -> RunFuture,
+    RunFuture: Future<Output = Result<T, ConnectionError>>,
+{
+    let mut attempts = 0;
+
+    loop {
+        let error = match run().await {
+            Err(error) => error,
+            result => return result,
+        };
+        if !policy.wait_to_reconnect(&error, &mut attempts).await {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, io, time::Duration};
+
+    use super::{
+        check_unsigned_chat, connect, join_with_token_refresh, open_stream, reconnect_on_kick,
+        try_protocols, ReconnectPolicy, MAX_PROTOCOL_ATTEMPTS,
+    };
+    use crate::{
+        config::Config,
+        protocol::{
+            encoding::{Decode, EncodedString, VarInt},
+            packets::{
+                login,
+                play::{
+                    game_event::{test::game_event_data, BEGIN_RAINING},
+                    Clientbound,
+                },
+                ServerStatus,
+            },
+            test_util::{self, JoinScript, MockServer},
+            ConnectionError, Event, Packet, State,
+        },
+        resolve::AddressFamily,
+    };
+
+    /// The reason a vanilla server gives when it can not verify the session.
+    const UNVERIFIED: &str = r#"{"translate":"multiplayer.disconnect.unverified_username"}"#;
+
+    #[tokio::test]
+    async fn retries_once_after_auth_failure() {
+        let attempts = RefCell::new(Vec::new());
+
+        let result = join_with_token_refresh(
+            "expired".to_string(),
+            |token| {
+                attempts.borrow_mut().push(token.clone());
+                async move {
+                    if token == "expired" {
+                        Err(ConnectionError::disconnected_json(UNVERIFIED))
+                    } else {
+                        Ok(token)
+                    }
+                }
+            },
+            || async { Ok("refreshed".to_string()) },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "refreshed");
+        assert_eq!(*attempts.borrow(), ["expired", "refreshed"]);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_second_auth_failure() {
+        let attempts = RefCell::new(0);
+
+        let result = join_with_token_refresh(
+            "expired".to_string(),
+            |_| {
+                *attempts.borrow_mut() += 1;
+                async { Err::<(), _>(ConnectionError::disconnected_json(UNVERIFIED)) }
+            },
+            || async { Ok("refreshed".to_string()) },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(*attempts.borrow(), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_refresh_on_other_errors() {
+        let result = join_with_token_refresh(
+            "token".to_string(),
+            |_| async { Err::<(), _>(ConnectionError::disconnected_json("Server closed")) },
+            || async { panic!("should not refresh") },
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    /// A policy reconnecting after kicks for `reasons`, at most three times in a row.
+    fn policy(reasons: &[&str]) -> ReconnectPolicy {
+        ReconnectPolicy {
+            reasons: reasons.iter().map(ToString::to_string).collect(),
+            delay: Duration::from_secs(10),
+            max_attempts: 3,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_on_matching_kick() {
+        let attempts = RefCell::new(0);
+
+        let result = reconnect_on_kick(&policy(&["restarting"]), || {
+            *attempts.borrow_mut() += 1;
+            let attempt = *attempts.borrow();
+            async move {
+                match attempt {
+                    1 => Err(ConnectionError::disconnected_json(
+                        r#"{"text":"Server is Restarting"}"#,
+                    )),
+                    _ => Ok(attempt),
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_on_dead_connection() {
+        let attempts = RefCell::new(0);
+
+        let result = reconnect_on_kick(&policy(&[]), || {
+            *attempts.borrow_mut() += 1;
+            let attempt = *attempts.borrow();
+            async move {
+                match attempt {
+                    1 => Err(ConnectionError::ConnectionDead(Duration::from_secs(30))),
+                    _ => Ok(attempt),
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_reconnect_on_other_kicks() {
+        let attempts = RefCell::new(0);
+
+        let result = reconnect_on_kick(&policy(&["restarting"]), || {
+            *attempts.borrow_mut() += 1;
+            async { Err::<(), _>(ConnectionError::disconnected_json("\"Banned\"")) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(ConnectionError::Disconnected { .. })));
+        assert_eq!(*attempts.borrow(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_blank_reconnect_reasons() {
+        let attempts = RefCell::new(0);
+
+        let result = reconnect_on_kick(&policy(&["", "  "]), || {
+            *attempts.borrow_mut() += 1;
+            async { Err::<(), _>(ConnectionError::disconnected_json("\"Banned\"")) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(ConnectionError::Disconnected { .. })));
+        assert_eq!(*attempts.borrow(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_reconnecting_after_max_attempts() {
+        let attempts = RefCell::new(0);
+
+        let result = reconnect_on_kick(&policy(&["restarting"]), || {
+            *attempts.borrow_mut() += 1;
+            async {
+                Err::<(), _>(ConnectionError::disconnected_json(
+                    "\"Server is restarting\"",
+                ))
+            }
+        })
+        .await;
+
+        assert!(matches!(result, Err(ConnectionError::Disconnected { .. })));
+        // the first join, then three reconnects
+        assert_eq!(*attempts.borrow(), 4);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_other_protocols() {
+        let attempts = RefCell::new(Vec::new());
+
+        let result = try_protocols(764, &[764, 765], |protocol_version| {
+            attempts.borrow_mut().push(protocol_version);
+            async move {
+                let (mut client, mut server) = test_util::connect(protocol_version);
+                let serve = async {
+                    // the handshake, then the login
+                    server.expect(0x00).await;
+                    if protocol_version == 764 {
+                        server.expect(0x00).await;
+                        let reason = EncodedString::try_from(r#""Outdated server!""#.to_string());
+                        server
+                            .send(Packet::new(
+                                login::DISCONNECT_ID,
+                                reason.unwrap().as_slice(),
+                            ))
+                            .await;
+                    } else {
+                        server.accept_login().await;
+                    }
+                };
+                let join = async {
+                    client
+                        .handshake("localhost".to_string(), 25565, false)
+                        .await?;
+                    client.login("Steve".to_string(), None).await?;
+                    Ok(protocol_version)
+                };
+
+                let (result, ()) = tokio::join!(join, serve);
+                result
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 765);
+        assert_eq!(*attempts.borrow(), [764, 765]);
+    }
+
+    #[tokio::test]
+    async fn stops_trying_protocols() {
+        let attempts = RefCell::new(Vec::new());
+        let fallbacks: Vec<i32> = (0..20).collect();
+
+        let result = try_protocols(764, &fallbacks, |protocol_version| {
+            attempts.borrow_mut().push(protocol_version);
+            async move {
+                Err::<(), _>(ConnectionError::disconnected_json(&format!(
+                    "\"Not {protocol_version}\""
+                )))
+            }
+        })
+        .await;
+
+        // the error of the last attempt is returned
+        match result {
+            Err(ConnectionError::Disconnected { reason_plain, .. }) => {
+                assert_eq!(reason_plain, "Not 6");
+            }
+            other => panic!("expected a disconnect, got {other:?}"),
+        }
+        assert_eq!(attempts.borrow().len(), MAX_PROTOCOL_ATTEMPTS);
+
+        // rejected sessions are not retried
+        attempts.borrow_mut().clear();
+        let result = try_protocols(764, &[765], |protocol_version| {
+            attempts.borrow_mut().push(protocol_version);
+            async { Err::<(), _>(ConnectionError::disconnected_json(UNVERIFIED)) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(*attempts.borrow(), [764]);
+    }
+
+    #[tokio::test]
+    async fn only_falls_back_when_another_protocol_may_help() {
+        let attempts = RefCell::new(Vec::new());
+        let errors = [
+            || ConnectionError::from(io::Error::from(io::ErrorKind::ConnectionRefused)),
+            || ConnectionError::ResourcePackRequired("https://example.com/pack.zip".to_string()),
+            || ConnectionError::UnexpectedPacket {
+                state: State::Play,
+                id: 0x7f,
+            },
+        ];
+        for error in errors {
+            attempts.borrow_mut().clear();
+            let result = try_protocols(764, &[765], |protocol_version| {
+                attempts.borrow_mut().push(protocol_version);
+                async move { Err::<(), _>(error()) }
+            })
+            .await;
+            assert!(result.is_err());
+            assert_eq!(*attempts.borrow(), [764]);
+        }
+
+        // a packet the protocol version does not expect during login is
+        attempts.borrow_mut().clear();
+        let result = try_protocols(764, &[765], |protocol_version| {
+            attempts.borrow_mut().push(protocol_version);
+            async {
+                Err::<(), _>(ConnectionError::UnexpectedPacket {
+                    state: State::Login,
+                    id: 0x7f,
+                })
+            }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(*attempts.borrow(), [764, 765]);
+    }
+
+    #[tokio::test]
+    async fn connects_from_bind_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let bind_address = Some("127.0.0.1:0".parse().unwrap());
+        let stream = open_stream("127.0.0.1", port, bind_address, AddressFamily::Auto)
+            .await
+            .unwrap();
+        let (_, client_address) = listener.accept().await.unwrap();
+        assert_eq!(stream.local_addr().unwrap(), client_address);
+    }
+
+    #[tokio::test]
+    async fn reports_bind_failures() {
+        // an address reserved for documentation, which no interface has
+        let bind_address = Some("192.0.2.1:0".parse().unwrap());
+        let error = open_stream("127.0.0.1", 25565, bind_address, AddressFamily::Auto)
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("could not bind to 192.0.2.1:0"));
+    }
+
+    #[tokio::test]
+    async fn joins_mock_server() {
+        let compressed = JoinScript {
+            compression_threshold: Some(0),
+            ..JoinScript::default()
+        };
+        let scripts = [
+            (764, JoinScript::default()),
+            (764, compressed),
+            (765, compressed),
+        ];
+
+        for (protocol_version, script) in scripts {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let config = Config {
+                resolve_srv: false,
+                ..Config::default()
+            };
+
+            let serve = async {
+                let mut server = MockServer::accept(&listener, protocol_version).await;
+                server.join(script).await;
+                server
+            };
+            let (client, mut server) = tokio::join!(
+                connect(&address, "Steve".to_string(), protocol_version, &config),
+                serve
+            );
+            let mut client = client.unwrap();
+            assert_eq!(client.state(), State::Play);
+
+            // the Login (play) is handled before the event which follows it
+            server
+                .send(Packet::new(
+                    Clientbound::GameEvent.id(protocol_version),
+                    game_event_data(BEGIN_RAINING, 0.0),
+                ))
+                .await;
+            let event = client.next_event().await.unwrap();
+            assert!(matches!(event, Event::RainStarted), "got {event:?}");
+            // configuration sent the dimension types
+            assert!(
+                client.world().dimension.is_some(),
+                "protocol {protocol_version}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_mock_server_encryption() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let config = Config {
+            resolve_srv: false,
+            ..Config::default()
+        };
+
+        let serve = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            server
+                .join(JoinScript {
+                    encryption: true,
+                    ..JoinScript::default()
+                })
+                .await;
+        };
+        let (client, ()) =
+            tokio::join!(connect(&address, "Steve".to_string(), 764, &config), serve);
+
+        assert!(matches!(
+            client,
+            Err(ConnectionError::EncryptionUnsupported)
+        ));
+    }
+
+    #[tokio::test]
+    async fn connects_only_to_configured_family() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(open_stream("127.0.0.1", port, None, AddressFamily::V4)
+            .await
+            .is_ok());
+        let error = open_stream("127.0.0.1", port, None, AddressFamily::V6)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AddrNotAvailable);
+    }
+
+    /// Builds the status of a server which reports `enforces_secure_chat`.
+    fn status(enforces_secure_chat: Option<bool>) -> ServerStatus {
+        let json = r#"{"version":{"name":"1.20.2","protocol":764}}"#;
+
+        ServerStatus {
+            enforces_secure_chat,
+            ..serde_json::from_str(json).unwrap()
+        }
+    }
+
+    #[test]
+    fn allows_unsigned_chat_without_secure_chat() {
+        assert!(check_unsigned_chat(&status(None), false).is_ok());
+        assert!(check_unsigned_chat(&status(Some(false)), false).is_ok());
+    }
+
+    #[test]
+    fn refuses_unsigned_chat_with_secure_chat() {
+        assert!(check_unsigned_chat(&status(Some(true)), false).is_err());
+    }
+
+    #[test]
+    fn forces_unsigned_chat_with_secure_chat() {
+        assert!(check_unsigned_chat(&status(Some(true)), true).is_ok());
+    }
+
+    #[tokio::test]
+    async fn sends_handshake_host_override() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server_address = address.to_string();
+        let config = Config {
+            resolve_srv: false,
+            handshake_host: Some("play.example.com".to_string()),
+            ..Config::default()
+        };
+
+        let serve = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            server.expect(0x00).await
+        };
+        let (result, handshake) = tokio::join!(
+            connect(&server_address, "Steve".to_string(), 764, &config),
+            serve
+        );
+        // the server leaves after the handshake
+        assert!(result.is_err());
+
+        let mut data = handshake.data();
+        VarInt::decode(&mut data).unwrap();
+        assert_eq!(String::decode(&mut data).unwrap(), "play.example.com");
+        assert_eq!(u16::decode(&mut data).unwrap(), address.port());
+    }
+
+    #[tokio::test]
+    async fn sends_client_brand() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let config = Config {
+            resolve_srv: false,
+            client_brand: "my-bot".to_string(),
+            ..Config::default()
+        };
+
+        let serve = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            server.expect(0x00).await;
+            server.accept_login().await;
+            server.expect(0x01).await
+        };
+        let (result, brand) =
+            tokio::join!(connect(&address, "Steve".to_string(), 764, &config), serve);
+        // the server leaves after the brand
+        assert!(result.is_err());
+
+        let mut data = brand.data();
+        assert_eq!(String::decode(&mut data).unwrap(), "minecraft:brand");
+        assert_eq!(String::decode(&mut data).unwrap(), "my-bot");
+    }
+}
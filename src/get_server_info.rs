@@ -1,32 +1,765 @@
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
+use std::{
+    fmt::Write,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
 };
 
-use crate::protocol::{
-    packets::{Handshake, Status},
-    Packet,
+use futures::{stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    connect::{self, ConnectDelays},
+    protocol::{
+        capture::CaptureWriter,
+        chat::ChatComponent,
+        encoding::Decode,
+        packets::{login, PingRequest, ServerStatus, Status, PONG_RESPONSE_ID, STATUS_RESPONSE_ID},
+        Connection, ConnectionError,
+    },
+    resolve::{self, AddressFamily, SystemResolver},
 };
 
-/// Retrieves some information about a server
-pub async fn get_server_info(server_address: String) -> Result<(), Box<dyn std::error::Error>> {
-    let mut stream = TcpStream::connect(server_address).await?;
+/// The prefix of a server address which connects over a Unix domain socket at the given path,
+/// rather than over TCP.
+pub const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+/// How long the server is given to echo a ping, unless [`PingOptions::ping_timeout`] says
+/// otherwise.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options for how a server is pinged.
+#[derive(Debug, Clone)]
+pub struct PingOptions {
+    /// The protocol version the handshake is sent with, where -1 (the default) is the
+    /// convention when pinging.
+    pub protocol_version: i32,
+    /// Where the packets of the ping are recorded to, if anywhere.
+    pub capture: Option<PathBuf>,
+    /// If sent packets are recorded in the capture, as well as received packets.
+    pub capture_sent: bool,
+    /// If the server's SRV record is followed, when its address has no port.
+    pub resolve_srv: bool,
+    /// The local address the ping is sent from, if a particular one is needed.
+    pub bind_address: Option<SocketAddr>,
+    /// Which of the server's addresses the ping may be sent to.
+    pub address_family: AddressFamily,
+    /// How long the ping waits before the handshake and the status request.
+    pub delays: ConnectDelays,
+    /// The address sent in the handshake in place of the one connected to (e.g., a virtual host
+    /// of a proxy on a shared IP), if it is overridden.
+    pub handshake_host: Option<String>,
+    /// How long the server is given to echo the ping after its status, before the latency is
+    /// left as `None`.
+    pub ping_timeout: Duration,
+}
+
+impl std::default::Default for PingOptions {
+    fn default() -> Self {
+        Self {
+            protocol_version: -1,
+            capture: None,
+            capture_sent: false,
+            resolve_srv: true,
+            bind_address: None,
+            address_family: AddressFamily::Auto,
+            delays: ConnectDelays::default(),
+            handshake_host: None,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+        }
+    }
+}
+
+impl PingOptions {
+    /// Starts capturing the packets of `connection`, if a capture was requested.
+    fn start_capture<S>(&self, connection: &mut Connection<S>) -> std::io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if let Some(path) = &self.capture {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            connection.set_capture(CaptureWriter::new(
+                file,
+                connection.protocol_version(),
+                self.capture_sent,
+            )?);
+        }
+
+        Ok(())
+    }
+}
+
+/// Retrieves some information about a server.
+///
+/// The `server_address` is either a `host:port` to connect to over TCP, or (on Unix platforms)
+/// `unix:/path/to/socket` to connect over a Unix domain socket. The handshake carries the
+/// address connected to, unless [`PingOptions::handshake_host`] overrides it.
+pub async fn get_server_info(
+    server_address: String,
+    options: &PingOptions,
+) -> Result<ServerStatus, Box<dyn std::error::Error>> {
+    if let Some(path) = server_address.strip_prefix(UNIX_SOCKET_PREFIX) {
+        return get_server_info_unix(path, options).await;
+    }
+
+    let (host, port) =
+        resolve::resolve(&SystemResolver, &server_address, options.resolve_srv).await?;
+    let stream =
+        connect::open_stream(&host, port, options.bind_address, options.address_family).await?;
     let socket_addr = stream.peer_addr()?;
 
-    // write handshake
-    // protocol_version set to `-1` is the convention when pinging
-    let packet: Packet =
-        Handshake::new(-1, socket_addr.ip().to_string(), socket_addr.port(), true)?.into();
-    stream.write_all(&Vec::try_from(packet)?).await?;
+    let mut connection = Connection::new(stream, options.protocol_version);
+    options.start_capture(&mut connection)?;
+    let handshake_host = options
+        .handshake_host
+        .clone()
+        .unwrap_or_else(|| socket_addr.ip().to_string());
+    Ok(get_status(connection, handshake_host, socket_addr.port(), options).await?)
+}
+
+/// Pings every server in `server_addresses`, with at most `concurrency` pings in flight at once.
+///
+/// Returns the result for each server, in the order they were given. When more than one server
+/// is pinged, each is captured to its own file next to [`PingOptions::capture`], named after the
+/// server.
+pub async fn ping_many(
+    server_addresses: Vec<String>,
+    options: &PingOptions,
+    concurrency: usize,
+) -> Vec<(String, Result<ServerStatus, Box<dyn std::error::Error>>)> {
+    let per_server_captures = server_addresses.len() > 1;
+
+    stream::iter(server_addresses)
+        .map(|server_address| async move {
+            let mut options = options.clone();
+            if let (true, Some(path)) = (per_server_captures, &options.capture) {
+                options.capture = Some(capture_path(path, &server_address));
+            }
+
+            let status = get_server_info(server_address.clone(), &options).await;
+            (server_address, status)
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Names the capture of the ping of `server_address` after it, next to `path` (e.g.,
+/// `ping.cap` becomes `ping.play.example.com_25565.cap`).
+fn capture_path(path: &Path, server_address: &str) -> PathBuf {
+    let server: String = server_address
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(server);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+
+    path.with_file_name(name)
+}
+
+/// The metrics written by [`prometheus_metrics`], with their help text.
+const METRICS: [(&str, &str); 4] = [
+    (
+        "mc_server_online",
+        "Whether the server answered the ping (1) or not (0).",
+    ),
+    ("mc_players_online", "How many players are online."),
+    (
+        "mc_players_max",
+        "The most players which may be online, or -1 if the server does not say.",
+    ),
+    (
+        "mc_ping_latency_ms",
+        "How long the server took to answer a ping, in milliseconds.",
+    ),
+];
+
+/// Formats the `results` of pinging servers (e.g., from [`ping_many`]) as a line of readable
+/// text for each server.
+pub fn text_results<E: std::fmt::Display>(results: &[(String, Result<ServerStatus, E>)]) -> String {
+    let mut text = String::new();
+    for (server_address, status) in results {
+        // writing to a string can not fail
+        let _ = match status {
+            Ok(status) => writeln!(
+                text,
+                "{server_address}: {} (protocol {}, {}/{} players): {}",
+                status.version.name,
+                status.version.protocol,
+                status.players_online(),
+                status.players_max(),
+                status.description.to_plain()
+            ),
+            Err(e) => writeln!(text, "{server_address}: failed to ping: {e}"),
+        };
+    }
+
+    text
+}
+
+/// Formats the `results` of pinging servers (e.g., from [`ping_many`]) as metrics in the
+/// Prometheus text format, labelled by the address of each server.
+///
+/// Servers which could not be pinged are only reported as offline, and the latency is left out
+/// for servers which did not answer the ping after their status.
+pub fn prometheus_metrics<E>(results: &[(String, Result<ServerStatus, E>)]) -> String {
+    let servers: Vec<_> = results
+        .iter()
+        .map(|(server_address, status)| {
+            (
+                escape_label(server_address),
+                metric_values(status.as_ref().ok()),
+            )
+        })
+        .collect();
+
+    let mut metrics = String::new();
+    for (index, (name, help)) in METRICS.iter().enumerate() {
+        // writing to a string can not fail
+        let _ = writeln!(metrics, "# HELP {name} {help}\n# TYPE {name} gauge");
+
+        for (address, values) in &servers {
+            if let Some(value) = &values[index] {
+                let _ = writeln!(metrics, "{name}{{address=\"{address}\"}} {value}");
+            }
+        }
+    }
+
+    metrics
+}
+
+/// Retrieves the value of each of the [`METRICS`] for a server with the given `status`, or for
+/// a server which could not be pinged if there is none.
+fn metric_values(status: Option<&ServerStatus>) -> [Option<String>; 4] {
+    let Some(status) = status else {
+        return [Some(String::from("0")), None, None, None];
+    };
+
+    [
+        Some(String::from("1")),
+        Some(status.players_online().to_string()),
+        Some(status.players_max().to_string()),
+        status
+            .latency
+            .map(|latency| (latency.as_secs_f64() * 1000.0).to_string()),
+    ]
+}
+
+/// Escapes `value` to be used as a label value in the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(unix)]
+async fn get_server_info_unix(
+    path: &str,
+    options: &PingOptions,
+) -> Result<ServerStatus, Box<dyn std::error::Error>> {
+    let stream = tokio::net::UnixStream::connect(path).await?;
+
+    // there is no host or port to report, so report what a local server would expect
+    let mut connection = Connection::new(stream, options.protocol_version);
+    options.start_capture(&mut connection)?;
+    let handshake_host = options
+        .handshake_host
+        .clone()
+        .unwrap_or_else(|| String::from("localhost"));
+    Ok(get_status(connection, handshake_host, 25565, options).await?)
+}
+
+#[cfg(not(unix))]
+async fn get_server_info_unix(
+    _path: &str,
+    _options: &PingOptions,
+) -> Result<ServerStatus, Box<dyn std::error::Error>> {
+    Err("Unix domain sockets are only supported on Unix platforms".into())
+}
+
+/// Retrieves the status of the server over an established `connection`, reporting
+/// `server_address` and `server_port` in the handshake, after waiting for each of the
+/// [`PingOptions::delays`].
+///
+/// The server is then pinged to measure its latency. Some servers close the connection after
+/// sending their status, or never answer the ping, so a failed ping only leaves the latency as
+/// `None`.
+pub async fn get_status<S>(
+    mut connection: Connection<S>,
+    server_address: String,
+    server_port: u16,
+    options: &PingOptions,
+) -> Result<ServerStatus, ConnectionError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let delays = options.delays;
+    connect::wait(delays.handshake).await;
+    connection
+        .handshake(server_address, server_port, true)
+        .await?;
+    connect::wait(delays.status).await;
 
     // follow up with status request packet (0x00)
-    let packet: Packet = Status::default().into();
-    stream.write_all(&Vec::try_from(packet)?).await?;
+    connection.write_packet(Status::default()).await?;
 
     // read response packet
-    let mut buf = [0; 256];
-    let res = stream.read(&mut buf).await?;
-    println!("Amount read: {res} = {buf:?}");
+    let packet = connection.read_packet().await?;
+    let mut status = match ServerStatus::decode(packet.data()) {
+        Ok(status) if packet.id() == STATUS_RESPONSE_ID => status,
+        decoded => {
+            // some anti-DDoS frontends kick the client instead of answering, with the login
+            // Disconnect packet or one of their own, but a status sent under another id is no kick
+            let may_be_kick = packet.id() == login::DISCONNECT_ID || decoded.is_err();
+            if let Some(reason) = kick_reason(packet.data()).filter(|_| may_be_kick) {
+                return Err(ConnectionError::disconnected_json(&reason));
+            }
+
+            return Err(match decoded {
+                Err(e) if packet.id() == STATUS_RESPONSE_ID => e.into(),
+                _ => ConnectionError::UnexpectedPacket {
+                    state: connection.state(),
+                    id: packet.id(),
+                },
+            });
+        }
+    };
+    status.latency = ping(&mut connection, options.ping_timeout).await.ok();
+
+    Ok(status)
+}
+
+/// Reads the reason of a packet which is shaped like a disconnect (a string of chat component
+/// JSON), if it is one.
+fn kick_reason(mut data: &[u8]) -> Option<String> {
+    let reason = String::decode(&mut data).ok()?;
+    serde_json::from_str::<ChatComponent>(&reason).ok()?;
+
+    Some(reason)
+}
+
+/// Measures how long the server takes to echo a ping, after the status has been received,
+/// giving up after `timeout`.
+async fn ping<S>(
+    connection: &mut Connection<S>,
+    timeout: Duration,
+) -> Result<Duration, ConnectionError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let payload = chrono::Utc::now().timestamp_millis();
+    let start = std::time::Instant::now();
+    connection.write_packet(PingRequest { payload }).await?;
+
+    let packet = tokio::time::timeout(timeout, connection.read_packet())
+        .await
+        .map_err(|_| ConnectionError::ResponseTimeout(timeout))??;
+    if packet.id() != PONG_RESPONSE_ID || i64::decode(&mut packet.data())? != payload {
+        return Err(ConnectionError::UnexpectedPacket {
+            state: connection.state(),
+            id: packet.id(),
+        });
+    }
+
+    Ok(start.elapsed())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{path::Path, time::Duration};
+
+    use crate::{
+        connect::ConnectDelays,
+        protocol::{
+            encoding::{Decode, EncodedString, VarInt},
+            packets::ServerStatus,
+            Connection, ConnectionError, Packet,
+        },
+    };
+
+    const STATUS_JSON: &str =
+        r#"{"version":{"name":"1.20.2","protocol":764},"description":"A Minecraft Server"}"#;
+
+    /// Answers a single status request on `stream`, then closes the connection without
+    /// answering the ping.
+    pub async fn serve_status<S>(stream: S)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        answer_status(&mut Connection::new(stream, 764)).await;
+    }
+
+    /// Answers a status request on `server`, as a server would.
+    async fn answer_status<S>(server: &mut Connection<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        // handshake, then status request
+        assert_eq!(server.read_packet().await.unwrap().id(), 0x00);
+        assert_eq!(server.read_packet().await.unwrap().id(), 0x00);
+
+        let json = EncodedString::try_from(STATUS_JSON.to_string()).unwrap();
+        server
+            .write_packet(Packet::new(0x00, json.as_slice()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn gets_status() {
+        let (client, server) = tokio::io::duplex(1024);
+
+        let options = super::PingOptions::default();
+        let (status, ()) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            serve_status(server)
+        );
+
+        // the server closed the connection instead of answering the ping
+        let status = status.unwrap();
+        assert_eq!(status.version.name, "1.20.2");
+        assert_eq!(status.latency, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_before_handshake_and_status() {
+        let (client, server) = tokio::io::duplex(1024);
+        let delays = ConnectDelays {
+            handshake: Duration::from_millis(300),
+            status: Duration::from_millis(500),
+        };
+        let start = tokio::time::Instant::now();
+        let server = async {
+            let mut server = Connection::new(server, 764);
+            assert_eq!(server.read_packet().await.unwrap().id(), 0x00);
+            let handshake = tokio::time::Instant::now();
+            assert_eq!(server.read_packet().await.unwrap().id(), 0x00);
+
+            (handshake - start, handshake.elapsed())
+        };
+
+        let options = super::PingOptions {
+            delays,
+            ..super::PingOptions::default()
+        };
+        let (_, (handshake, status)) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            server
+        );
+
+        assert!(handshake >= delays.handshake);
+        assert!(status >= delays.status);
+    }
+
+    #[tokio::test]
+    async fn reports_kicks_instead_of_status() {
+        for (id, reason) in [
+            (0x00, r#"{"text":"Too many connections, try again later"}"#),
+            (0x1a, r#""Blocked by the firewall""#),
+        ] {
+            let (client, server) = tokio::io::duplex(1024);
+            let server = async {
+                let mut server = Connection::new(server, 764);
+                server.read_packet().await.unwrap();
+                server.read_packet().await.unwrap();
+
+                let reason = EncodedString::try_from(reason.to_string()).unwrap();
+                server
+                    .write_packet(Packet::new(id, reason.as_slice()))
+                    .await
+                    .unwrap();
+            };
+
+            let options = super::PingOptions::default();
+            let (status, ()) = tokio::join!(
+                super::get_status(
+                    Connection::new(client, -1),
+                    "localhost".to_string(),
+                    25565,
+                    &options
+                ),
+                server
+            );
+
+            match status {
+                Err(ConnectionError::Disconnected { reason_json, .. }) => assert_eq!(
+                    reason_json,
+                    serde_json::from_str::<serde_json::Value>(reason).unwrap()
+                ),
+                other => panic!("expected a kick, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_other_packets() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = async {
+            let mut server = Connection::new(server, 764);
+            server.read_packet().await.unwrap();
+            server.read_packet().await.unwrap();
+            server
+                .write_packet(Packet::new(0x05, vec![1, 2]))
+                .await
+                .unwrap();
+        };
+
+        let options = super::PingOptions::default();
+        let (status, ()) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            server
+        );
+
+        assert!(matches!(
+            status,
+            Err(ConnectionError::UnexpectedPacket { id: 0x05, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn does_not_take_statuses_under_other_ids_for_kicks() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = async {
+            let mut server = Connection::new(server, 764);
+            server.read_packet().await.unwrap();
+            server.read_packet().await.unwrap();
+
+            let json = EncodedString::try_from(STATUS_JSON.to_string()).unwrap();
+            server
+                .write_packet(Packet::new(0x1a, json.as_slice()))
+                .await
+                .unwrap();
+        };
+
+        let options = super::PingOptions::default();
+        let (status, ()) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            server
+        );
+
+        assert!(matches!(
+            status,
+            Err(ConnectionError::UnexpectedPacket { id: 0x1a, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn measures_latency() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = async {
+            let mut server = Connection::new(server, 764);
+            answer_status(&mut server).await;
+
+            // echo the ping
+            let ping = server.read_packet().await.unwrap();
+            assert_eq!(ping.id(), 0x01);
+            server
+                .write_packet(Packet::new(0x01, ping.data().to_vec()))
+                .await
+                .unwrap();
+        };
+
+        let options = super::PingOptions::default();
+        let (status, ()) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            server
+        );
+
+        assert!(status.unwrap().latency.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_on_unanswered_pings() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = async {
+            let mut server = Connection::new(server, 764);
+            answer_status(&mut server).await;
+
+            // read the ping, but stay connected without answering it
+            server.read_packet().await.unwrap();
+            server
+        };
+
+        let options = super::PingOptions::default();
+        let (status, _server) = tokio::join!(
+            super::get_status(
+                Connection::new(client, -1),
+                "localhost".to_string(),
+                25565,
+                &options
+            ),
+            server
+        );
+
+        let status = status.unwrap();
+        assert_eq!(status.version.name, "1.20.2");
+        assert_eq!(status.latency, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn gets_status_over_unix_socket() {
+        let path = std::env::temp_dir().join(format!(
+            "minecraft-console-client-test-{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_status(stream).await;
+        };
+        let options = super::PingOptions::default();
+        let (status, ()) = tokio::join!(
+            super::get_server_info(format!("unix:{}", path.display()), &options),
+            server
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let status = status.unwrap();
+        assert_eq!(status.version.protocol, 764);
+        assert_eq!(status.description.to_plain(), "A Minecraft Server");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn pings_many_in_order() {
+        let path = std::env::temp_dir().join(format!(
+            "minecraft-console-client-test-many-{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+        let server = async {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve_status(stream).await;
+            }
+        };
+        let addresses = vec![
+            format!("unix:{}", path.display()),
+            "unix:/nonexistent/minecraft.sock".to_string(),
+            format!("unix:{}", path.display()),
+        ];
+        let options = super::PingOptions::default();
+        let (results, ()) = tokio::join!(super::ping_many(addresses.clone(), &options, 2), server);
+        std::fs::remove_file(&path).unwrap();
+
+        let returned: Vec<&String> = results.iter().map(|(address, _)| address).collect();
+        assert_eq!(returned, addresses.iter().collect::<Vec<_>>());
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_err());
+        assert!(results[2].1.is_ok());
+    }
+
+    #[test]
+    fn names_captures_after_servers() {
+        assert_eq!(
+            super::capture_path(Path::new("captures/ping.cap"), "play.example.com:25565"),
+            Path::new("captures/ping.play.example.com_25565.cap")
+        );
+        assert_eq!(
+            super::capture_path(Path::new("ping"), "unix:/run/mc.sock"),
+            Path::new("ping.unix__run_mc.sock")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_handshake_host_override() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let options = super::PingOptions {
+            resolve_srv: false,
+            handshake_host: Some("lobby.example.com".to_string()),
+            ..super::PingOptions::default()
+        };
+
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut server = Connection::new(stream, 764);
+            server.read_packet().await.unwrap()
+        };
+        let (status, handshake) = tokio::join!(
+            super::get_server_info(address.to_string(), &options),
+            server
+        );
+        // the server leaves after the handshake
+        assert!(status.is_err());
+
+        let mut data = handshake.data();
+        VarInt::decode(&mut data).unwrap();
+        assert_eq!(String::decode(&mut data).unwrap(), "lobby.example.com");
+        assert_eq!(u16::decode(&mut data).unwrap(), address.port());
+    }
+
+    #[test]
+    fn formats_prometheus_metrics() {
+        let mut status: ServerStatus = serde_json::from_str(
+            r#"{"version":{"name":"1.20.2","protocol":764},"players":{"max":20,"online":3}}"#,
+        )
+        .unwrap();
+        status.latency = Some(Duration::from_micros(12_500));
+        let results = [
+            (String::from("mc.example.com"), Ok(status)),
+            (String::from("down\"host"), Err("connection refused")),
+        ];
 
-    Ok(())
+        assert_eq!(
+            super::prometheus_metrics(&results),
+            "# HELP mc_server_online Whether the server answered the ping (1) or not (0).
+# TYPE mc_server_online gauge
+mc_server_online{address=\"mc.example.com\"} 1
+mc_server_online{address=\"down\\\"host\"} 0
+# HELP mc_players_online How many players are online.
+# TYPE mc_players_online gauge
+mc_players_online{address=\"mc.example.com\"} 3
+# HELP mc_players_max The most players which may be online, or -1 if the server does not say.
+# TYPE mc_players_max gauge
+mc_players_max{address=\"mc.example.com\"} 20
+# HELP mc_ping_latency_ms How long the server took to answer a ping, in milliseconds.
+# TYPE mc_ping_latency_ms gauge
+mc_ping_latency_ms{address=\"mc.example.com\"} 12.5
+"
+        );
+    }
 }
@@ -0,0 +1,158 @@
+//! The HTTP requests made during authentication, behind a trait so that authentication is not
+//! bound to `reqwest`.
+
+use std::future::Future;
+
+use serde::de::DeserializeOwned;
+
+/// A response to an HTTP request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    /// Whether the status is a success (2xx).
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Deserializes the JSON body, failing if the status is not a success.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Box<dyn std::error::Error>> {
+        if !self.is_success() {
+            return Err(format!("request failed with status {}", self.status).into());
+        }
+
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+/// A way of sending the HTTP requests authentication needs.
+pub trait HttpBackend {
+    /// Sends a POST request to `url` with the url-encoded `form` as its body.
+    fn post_form(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> impl Future<Output = Result<Response, Box<dyn std::error::Error>>>;
+
+    /// Sends a POST request to `url` with `body` as its JSON body.
+    fn post_json(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> impl Future<Output = Result<Response, Box<dyn std::error::Error>>>;
+
+    /// Sends a GET request to `url`, authorized with the bearer token `token`.
+    fn get(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> impl Future<Output = Result<Response, Box<dyn std::error::Error>>>;
+}
+
+/// Reads the status and body of a `reqwest` response.
+async fn read_response(
+    response: reqwest::Response,
+) -> Result<Response, Box<dyn std::error::Error>> {
+    Ok(Response {
+        status: response.status().as_u16(),
+        body: response.text().await?,
+    })
+}
+
+impl HttpBackend for reqwest::Client {
+    async fn post_form(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        read_response(self.post(url).form(form).send().await?).await
+    }
+
+    async fn post_json(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        read_response(self.post(url).json(body).send().await?).await
+    }
+
+    async fn get(&self, url: &str, token: &str) -> Result<Response, Box<dyn std::error::Error>> {
+        read_response(self.get(url).bearer_auth(token).send().await?).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::{HttpBackend, Response};
+
+    /// Answers requests from memory, with the status and body given for each URL (and a 404 for
+    /// any other URL), recording the URLs requested.
+    #[derive(Default)]
+    pub struct FakeBackend {
+        pub routes: HashMap<String, Response>,
+        pub requested: Mutex<Vec<String>>,
+    }
+
+    impl FakeBackend {
+        /// Creates a backend answering each URL in `routes` with its status and body.
+        pub fn new(routes: impl IntoIterator<Item = (String, u16, String)>) -> Self {
+            Self {
+                routes: routes
+                    .into_iter()
+                    .map(|(url, status, body)| (url, Response { status, body }))
+                    .collect(),
+                requested: Mutex::default(),
+            }
+        }
+
+        fn respond(&self, url: &str) -> Response {
+            self.requested.lock().unwrap().push(url.to_string());
+
+            self.routes.get(url).cloned().unwrap_or(Response {
+                status: 404,
+                body: String::new(),
+            })
+        }
+    }
+
+    impl HttpBackend for FakeBackend {
+        async fn post_form(
+            &self,
+            url: &str,
+            _form: &[(&str, &str)],
+        ) -> Result<Response, Box<dyn std::error::Error>> {
+            Ok(self.respond(url))
+        }
+
+        async fn post_json(
+            &self,
+            url: &str,
+            _body: &serde_json::Value,
+        ) -> Result<Response, Box<dyn std::error::Error>> {
+            Ok(self.respond(url))
+        }
+
+        async fn get(
+            &self,
+            url: &str,
+            _token: &str,
+        ) -> Result<Response, Box<dyn std::error::Error>> {
+            Ok(self.respond(url))
+        }
+    }
+
+    #[test]
+    fn rejects_json_of_failed_response() {
+        let response = Response {
+            status: 500,
+            body: "{}".to_string(),
+        };
+
+        assert!(response.json::<serde_json::Value>().is_err());
+    }
+}
@@ -0,0 +1,22 @@
+#![deny(clippy::pedantic)]
+// these only fire for exported items, which the crate did not have while it was binary-only
+#![allow(
+    clippy::missing_errors_doc,
+    clippy::missing_panics_doc,
+    clippy::must_use_candidate
+)]
+pub mod authentication;
+pub mod cache;
+pub mod cli;
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod connect;
+pub mod get_server_info;
+pub mod http;
+pub mod logging;
+pub mod probe;
+pub mod protocol;
+pub mod replay;
+pub mod resolve;
+pub mod util;
@@ -0,0 +1,85 @@
+//! How the client's informational messages and warnings are reported.
+//!
+//! The client logs with [`tracing`]; results (e.g., a server's status) are printed to stdout
+//! directly, so they are never filtered out.
+
+use tracing::{level_filters::LevelFilter, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// The most verbose level logged, which under `quiet` only lets warnings and errors through, and
+/// under `verbose` also lets debug messages (e.g., how long each step of signing in took)
+/// through.
+///
+/// `quiet` wins when both are set.
+pub fn level(quiet: bool, verbose: bool) -> LevelFilter {
+    if quiet {
+        LevelFilter::WARN
+    } else if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    }
+}
+
+/// Builds the subscriber which writes log messages up to `level` (see [`level`]) to
+/// `make_writer`.
+pub fn subscriber<W>(level: LevelFilter, make_writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .without_time()
+        .with_writer(make_writer)
+        .finish()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::protocol::capture::test::SharedBuffer;
+
+    /// Logs a debug message, an informational message, and an error, returning what was
+    /// written.
+    fn log(quiet: bool, verbose: bool) -> String {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = super::subscriber(super::level(quiet, verbose), move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("Minecraft login took 40ms");
+            tracing::info!("Cached token was valid!");
+            tracing::error!("failed to connect");
+        });
+
+        let written = buffer.0.lock().unwrap().clone();
+        String::from_utf8(written).unwrap()
+    }
+
+    #[test]
+    fn logs_informational_messages() {
+        let written = log(false, false);
+
+        assert!(!written.contains("Minecraft login took 40ms"));
+        assert!(written.contains("Cached token was valid!"));
+        assert!(written.contains("failed to connect"));
+    }
+
+    #[test]
+    fn filters_informational_messages_when_quiet() {
+        let written = log(true, false);
+
+        assert!(!written.contains("Cached token was valid!"));
+        assert!(written.contains("failed to connect"));
+    }
+
+    #[test]
+    fn logs_debug_messages_when_verbose() {
+        let written = log(false, true);
+        assert!(written.contains("Minecraft login took 40ms"));
+        assert!(written.contains("Cached token was valid!"));
+
+        // quiet wins over verbose
+        assert!(!log(true, true).contains("Cached token was valid!"));
+    }
+}
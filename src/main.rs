@@ -1,55 +1,229 @@
 #![deny(clippy::pedantic)]
-mod authentication;
-mod cache;
-mod config;
-mod get_server_info;
-mod protocol;
+use std::{io, path::Path, time::Duration};
 
-use std::io;
-
-use reqwest::Client;
+use minecraft_console_client::{
+    cli,
+    client::McClient,
+    clock::{self, Clock, SystemClock},
+    config, get_server_info, logging, probe,
+    protocol::{capture::CaptureReader, Event},
+    replay, util,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = Client::new();
+    let args = cli::Args::parse(std::env::args().skip(1))?;
+    tracing::subscriber::set_global_default(logging::subscriber(
+        logging::level(args.quiet, args.verbose),
+        io::stderr,
+    ))?;
 
-    // get config and cache
-    let config = config::get()?;
+    // generating a config must work without one
+    if let Some(cli::Command::GenerateConfig(path)) = &args.command {
+        let path = path
+            .as_deref()
+            .unwrap_or_else(|| Path::new(config::CONFIG_PATH));
+        config::write_generated(path, args.force)?;
+        println!("Wrote the default config to {}", path.display());
 
-    // only read cache if enabled in config
-    let fs_cache = config
-        .cache_enabled
-        .then(cache::Cache::get)
-        .unwrap_or(Ok(None))?;
-    let fs_cache_exists = fs_cache.is_some();
-    let mut cache = fs_cache.unwrap_or_default();
+        return Ok(());
+    }
 
-    // get minecraft token
-    let authenticate_cache = if fs_cache_exists { Some(&cache) } else { None };
-    let authenticate_result =
-        authentication::authenticate(&client, io::stdin().lock(), authenticate_cache).await?;
-    let token = authenticate_result.minecraft_token;
-
-    match authenticate_result.retrieve_type {
-        authentication::RetrieveType::FromCache => (),
-        authentication::RetrieveType::FromUserLogin {
-            microsoft_refresh_token,
-            expires_in,
-        } => {
-            if config.cache_enabled {
-                // save to cache
-                cache.save_minecraft_token(
-                    token.clone(),
-                    chrono::Utc::now() + chrono::Duration::seconds(i64::from(expires_in)),
-                )?;
-                cache.save_microsoft_refresh_token(microsoft_refresh_token)?;
-            }
+    // replaying a capture needs no server or account
+    if let Some(path) = &args.replay {
+        return replay(path);
+    }
+
+    let mut config = config::get(args.strict_config)?;
+    args.apply(&mut config);
+    let mut client = McClient::builder()
+        .config(config)
+        .capture(args.capture.clone(), args.capture_sent)
+        .build()?;
+
+    // pinging and probing servers need no account
+    let concurrency = client.config().ping_concurrency;
+    if let Some(output) = diagnose(&args, client.ping_options(), concurrency).await {
+        match &args.out {
+            Some(path) => util::write_output(path, &output, args.append)
+                .map_err(|e| format!("could not write results to {}: {e}", path.display()))?,
+            None => print!("{output}"),
+        }
+
+        return Ok(());
+    }
+
+    let cache_enabled = client.config().cache_enabled;
+
+    // importing accounts needs no server
+    if let Some(path) = &args.import {
+        if !cache_enabled {
+            return Err(
+                "caching is disabled in the config, so accounts can not be imported".into(),
+            );
+        }
+
+        let cache = client.cache()?;
+        let imported = cache.import_mcc_sessions(&std::fs::read_to_string(path)?, &SystemClock)?;
+        cache.save(&SystemClock)?;
+        println!(
+            "Imported {} accounts: {}",
+            imported.len(),
+            imported.join(", ")
+        );
+
+        return Ok(());
+    }
+
+    // showing the cache needs no server
+    if args.command == Some(cli::Command::CacheShow) {
+        if !cache_enabled {
+            return Err("caching is disabled in the config, so there is no cache to show".into());
+        }
+        // whether the cache exists is only known once it is read
+        client.cache()?;
+        if !client.cache_exists() {
+            println!("There is no cache yet, as no account has logged in");
+            return Ok(());
+        }
+
+        print!("{}", client.cache()?.describe(&SystemClock));
+        return Ok(());
+    }
+
+    // refreshing needs no server
+    if args.command == Some(cli::Command::Refresh) {
+        if !cache_enabled {
+            return Err(
+                "caching is disabled in the config, so there is no token to refresh".into(),
+            );
         }
+
+        if let Some(expiry_time) = client.refresh_token().await? {
+            println!(
+                "Refreshed token, which expires at {expiry_time} (in {})",
+                clock::format_remaining(SystemClock.remaining(expiry_time))
+            );
+        }
+        return Ok(());
     }
 
-    println!("Got authentication token: {}", token);
+    // get minecraft token
+    let token = client.token().await?;
+
+    println!("Got authentication token: {token}");
     // retrieve server version
-    get_server_info::get_server_info(config.server_url).await?;
+    let status = client.ping(&client.config().server_url).await?;
+    println!(
+        "Server is running {} (protocol {}): {}",
+        status.version.name,
+        status.version.protocol,
+        status.description.to_plain()
+    );
+    if status.is_geyser() {
+        tracing::info!("Server appears to be a Geyser bridge for Bedrock players");
+    }
+    if let Some(latency) = status.latency {
+        println!("Server answered a ping in {}ms", latency.as_millis());
+    }
+
+    play(&mut client).await
+}
+
+/// Joins the server in the config, printing the chat until the connection ends.
+///
+/// Kicks for one of the `reconnect_reasons`, and connections that go quiet, are reconnected
+/// by the client rather than ending this.
+async fn play(client: &mut McClient) -> Result<(), Box<dyn std::error::Error>> {
+    let server_url = client.config().server_url.clone();
+    client.connect(&server_url).await?;
+    println!("Joined {server_url}");
+
+    loop {
+        match client.next_event().await? {
+            Event::SystemChat(chat) if !chat.overlay => println!("{}", chat.content.to_plain()),
+            Event::PlayerChat(chat) => println!("{}", chat.message),
+            Event::Died(message) => println!("Died: {}", message.to_plain()),
+            event => tracing::debug!("{event:?}"),
+        }
+    }
+}
+
+/// Decodes every packet of the capture at `path`, printing each packet and then a summary.
+fn replay(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let capture = CaptureReader::new(io::BufReader::new(std::fs::File::open(path)?))?;
+    let summary = replay::replay(capture, io::stdout().lock())?;
+    println!(
+        "Replayed {} packets: {} decoded, {} failed, {} unknown",
+        summary.decoded + summary.failed + summary.unknown,
+        summary.decoded,
+        summary.failed,
+        summary.unknown
+    );
 
     Ok(())
 }
+
+/// Pings or probes the servers named in `args`, returning the results, or `None` if `args`
+/// names none.
+async fn diagnose(
+    args: &cli::Args,
+    ping_options: &get_server_info::PingOptions,
+    concurrency: usize,
+) -> Option<String> {
+    let timeout = args
+        .probe_timeout
+        .map_or(probe::DEFAULT_PROBE_TIMEOUT, Duration::from_secs);
+    // a server which never answers the ping after its status is given up on just as soon
+    let ping_options = &get_server_info::PingOptions {
+        ping_timeout: timeout,
+        ..ping_options.clone()
+    };
+
+    if let Some(server_address) = &args.probe_all_versions {
+        let options = probe::ProbeOptions {
+            login: args.probe_login,
+            timeout,
+            concurrency,
+        };
+        return Some(probe(server_address, ping_options, &options).await);
+    }
+
+    if args.ping.is_empty() {
+        return None;
+    }
+    Some(ping(args.ping.clone(), ping_options, concurrency, args.format).await)
+}
+
+/// Pings each of `server_addresses`, at most `concurrency` at once, returning their statuses
+/// formatted as `format` says.
+async fn ping(
+    server_addresses: Vec<String>,
+    ping_options: &get_server_info::PingOptions,
+    concurrency: usize,
+    format: cli::OutputFormat,
+) -> String {
+    let results = get_server_info::ping_many(server_addresses, ping_options, concurrency).await;
+
+    match format {
+        cli::OutputFormat::Text => get_server_info::text_results(&results),
+        cli::OutputFormat::Prometheus => get_server_info::prometheus_metrics(&results),
+    }
+}
+
+/// Probes the server at `server_address` on the protocol version of every release, returning
+/// which it accepts as a table.
+async fn probe(
+    server_address: &str,
+    ping_options: &get_server_info::PingOptions,
+    options: &probe::ProbeOptions,
+) -> String {
+    let protocol_versions: Vec<i32> = probe::RELEASE_PROTOCOLS
+        .iter()
+        .map(|(_, protocol_version)| *protocol_version)
+        .collect();
+    let probes =
+        probe::probe_versions(server_address, &protocol_versions, ping_options, options).await;
+
+    probe::compatibility_matrix(&probes)
+}
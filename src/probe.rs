@@ -0,0 +1,452 @@
+//! Probing which protocol versions a server accepts, for admins debugging version support.
+
+use std::{fmt::Write, future::Future, time::Duration};
+
+use futures::{stream, StreamExt};
+
+use crate::{
+    connect,
+    get_server_info::{self, PingOptions},
+    protocol::{Connection, ConnectionError},
+    resolve::{self, SystemResolver},
+};
+
+/// The protocol version of each release from 1.8 onwards, with the releases which speak it.
+pub const RELEASE_PROTOCOLS: &[(&str, i32)] = &[
+    ("1.8-1.8.9", 47),
+    ("1.9", 107),
+    ("1.9.1", 108),
+    ("1.9.2", 109),
+    ("1.9.3-1.9.4", 110),
+    ("1.10-1.10.2", 210),
+    ("1.11", 315),
+    ("1.11.1-1.11.2", 316),
+    ("1.12", 335),
+    ("1.12.1", 338),
+    ("1.12.2", 340),
+    ("1.13", 393),
+    ("1.13.1", 401),
+    ("1.13.2", 404),
+    ("1.14", 477),
+    ("1.14.1", 480),
+    ("1.14.2", 485),
+    ("1.14.3", 490),
+    ("1.14.4", 498),
+    ("1.15", 573),
+    ("1.15.1", 575),
+    ("1.15.2", 578),
+    ("1.16", 735),
+    ("1.16.1", 736),
+    ("1.16.2", 751),
+    ("1.16.3", 753),
+    ("1.16.4-1.16.5", 754),
+    ("1.17", 755),
+    ("1.17.1", 756),
+    ("1.18-1.18.1", 757),
+    ("1.18.2", 758),
+    ("1.19", 759),
+    ("1.19.1-1.19.2", 760),
+    ("1.19.3", 761),
+    ("1.19.4", 762),
+    ("1.20-1.20.1", 763),
+    ("1.20.2", 764),
+    ("1.20.3-1.20.4", 765),
+    ("1.20.5-1.20.6", 766),
+];
+
+/// How long each probe may take before it counts as failed, unless told otherwise.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The username logins are started as, when they are probed.
+const PROBE_USERNAME: &str = "Probe";
+
+/// How a server answered the probe of a protocol version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    /// The server speaks the protocol version: its status reports it as its own, or it carried
+    /// on with the login.
+    Accepted,
+    /// The server answered, but does not speak the protocol version, saying what it speaks or
+    /// why it disconnected the login.
+    Rejected(String),
+    /// The server could not be probed (e.g., it did not answer in time), with why.
+    Failed(String),
+}
+
+impl std::fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accepted => write!(f, "accepted"),
+            Self::Rejected(reason) => write!(f, "rejected ({reason})"),
+            Self::Failed(e) => write!(f, "failed ({e})"),
+        }
+    }
+}
+
+/// How a server answered the probes of one protocol version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub protocol_version: i32,
+    /// How the server answered a status request.
+    pub status: Compatibility,
+    /// How the server answered a login start, if logins were probed.
+    pub login: Option<Compatibility>,
+}
+
+/// Options for how a server is probed.
+#[derive(Debug)]
+pub struct ProbeOptions {
+    /// If a login is started on each protocol version, as well as the status being requested.
+    ///
+    /// Offline-mode servers let the probing player in, so they briefly join before the probe
+    /// disconnects.
+    pub login: bool,
+    /// How long each probe may take.
+    pub timeout: Duration,
+    /// How many probes are in flight at once.
+    pub concurrency: usize,
+}
+
+impl std::default::Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            login: false,
+            timeout: DEFAULT_PROBE_TIMEOUT,
+            concurrency: 16,
+        }
+    }
+}
+
+/// Probes the server at `server_address` on each of `protocol_versions`, connecting as
+/// `ping_options` says.
+///
+/// Returns how the server answered each protocol version, in the order they were given.
+pub async fn probe_versions(
+    server_address: &str,
+    protocol_versions: &[i32],
+    ping_options: &PingOptions,
+    options: &ProbeOptions,
+) -> Vec<Probe> {
+    stream::iter(protocol_versions.iter().copied())
+        .map(|protocol_version| async move {
+            let status = within(
+                options.timeout,
+                probe_status(server_address, protocol_version, ping_options),
+            )
+            .await;
+            let login = if options.login {
+                Some(
+                    within(
+                        options.timeout,
+                        probe_login(server_address, protocol_version, ping_options),
+                    )
+                    .await,
+                )
+            } else {
+                None
+            };
+
+            Probe {
+                protocol_version,
+                status,
+                login,
+            }
+        })
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Waits for `probe`, counting it as failed if it takes longer than `timeout`.
+async fn within<F>(timeout: Duration, probe: F) -> Compatibility
+where
+    F: Future<Output = Compatibility>,
+{
+    tokio::time::timeout(timeout, probe)
+        .await
+        .unwrap_or_else(|_| {
+            Compatibility::Failed(format!("no answer within {}s", timeout.as_secs_f64()))
+        })
+}
+
+/// Requests the status of the server with a handshake on `protocol_version`, which the server
+/// accepts if it reports the protocol version as its own.
+async fn probe_status(
+    server_address: &str,
+    protocol_version: i32,
+    ping_options: &PingOptions,
+) -> Compatibility {
+    let options = PingOptions {
+        protocol_version,
+        // each probe would overwrite the capture of the last
+        capture: None,
+        ..ping_options.clone()
+    };
+
+    match get_server_info::get_server_info(server_address.to_string(), &options).await {
+        Ok(status) if status.version.protocol == protocol_version => Compatibility::Accepted,
+        Ok(status) => Compatibility::Rejected(format!(
+            "server speaks {} (protocol {})",
+            status.version.name, status.version.protocol
+        )),
+        Err(e) => Compatibility::Failed(e.to_string()),
+    }
+}
+
+/// Starts a login on `protocol_version`, which the server accepts if it does anything but
+/// disconnect the player.
+async fn probe_login(
+    server_address: &str,
+    protocol_version: i32,
+    ping_options: &PingOptions,
+) -> Compatibility {
+    match start_login(server_address, protocol_version, ping_options).await {
+        // online-mode servers only ask to encrypt once they have accepted the protocol version
+        Ok(()) | Err(ConnectionError::EncryptionUnsupported) => Compatibility::Accepted,
+        Err(ConnectionError::Disconnected { reason_plain, .. }) => {
+            Compatibility::Rejected(reason_plain)
+        }
+        Err(e) => Compatibility::Failed(e.to_string()),
+    }
+}
+
+/// Connects to the server and logs in as [`PROBE_USERNAME`] on `protocol_version`, leaving as
+/// soon as the login is done.
+async fn start_login(
+    server_address: &str,
+    protocol_version: i32,
+    ping_options: &PingOptions,
+) -> Result<(), ConnectionError> {
+    let (host, port) =
+        resolve::resolve(&SystemResolver, server_address, ping_options.resolve_srv).await?;
+    let stream = connect::open_stream(
+        &host,
+        port,
+        ping_options.bind_address,
+        ping_options.address_family,
+    )
+    .await?;
+    let socket_addr = stream.peer_addr()?;
+
+    let mut connection = Connection::new(stream, protocol_version);
+    let handshake_host = ping_options
+        .handshake_host
+        .clone()
+        .unwrap_or_else(|| socket_addr.ip().to_string());
+    connection
+        .handshake(handshake_host, socket_addr.port(), false)
+        .await?;
+    connection
+        .login(PROBE_USERNAME.to_string(), None)
+        .await
+        .map(|_| ())
+}
+
+/// Formats `probes` (e.g., from [`probe_versions`]) as a table with a row for each protocol
+/// version, naming the releases which speak it.
+pub fn compatibility_matrix(probes: &[Probe]) -> String {
+    let rows: Vec<_> = probes
+        .iter()
+        .map(|probe| {
+            let release = RELEASE_PROTOCOLS
+                .iter()
+                .find(|(_, protocol_version)| *protocol_version == probe.protocol_version)
+                .map_or("unknown", |(release, _)| release);
+            let login = probe.login.as_ref().map(ToString::to_string);
+
+            (
+                probe.protocol_version,
+                release,
+                probe.status.to_string(),
+                login,
+            )
+        })
+        .collect();
+    let release_width = rows.iter().map(|row| row.1.len()).fold(7, usize::max);
+    let status_width = rows.iter().map(|row| row.2.len()).fold(6, usize::max);
+
+    let mut matrix = format!("protocol  {:release_width$}  status", "release");
+    if rows.iter().any(|row| row.3.is_some()) {
+        // writing to a string can not fail
+        let _ = write!(matrix, "{:pad$}  login", "", pad = status_width - 6);
+    }
+    matrix.push('\n');
+
+    for (protocol_version, release, status, login) in rows {
+        let _ = match login {
+            Some(login) => writeln!(
+                matrix,
+                "{protocol_version:>8}  {release:release_width$}  {status:status_width$}  {login}"
+            ),
+            None => writeln!(
+                matrix,
+                "{protocol_version:>8}  {release:release_width$}  {status}"
+            ),
+        };
+    }
+
+    matrix
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{compatibility_matrix, probe_versions, Compatibility, Probe, ProbeOptions};
+    use crate::{
+        get_server_info::PingOptions,
+        protocol::{
+            encoding::{Decode, EncodedString, VarInt},
+            packets::login,
+            test_util::MockServer,
+            Packet,
+        },
+    };
+
+    /// Serves every connection to `listener` as a 1.20.2 server would: reporting protocol 764
+    /// in its status, and only letting players in on it.
+    async fn serve_only_764(listener: tokio::net::TcpListener) {
+        loop {
+            let mut server = MockServer::accept(&listener, 764).await;
+            tokio::spawn(async move {
+                let handshake = server.expect(0x00).await;
+                let mut data = handshake.data();
+                let protocol_version = i32::from(VarInt::decode(&mut data).unwrap());
+                String::decode(&mut data).unwrap();
+                u16::decode(&mut data).unwrap();
+                let is_status = i32::from(VarInt::decode(&mut data).unwrap()) == 1;
+
+                if is_status {
+                    server.expect(0x00).await;
+                    let status = r#"{"version":{"name":"1.20.2","protocol":764}}"#;
+                    let status = EncodedString::try_from(status.to_string()).unwrap();
+                    server.send(Packet::new(0x00, status.as_slice())).await;
+                } else if protocol_version == 764 {
+                    server.accept_login().await;
+                } else {
+                    server.expect(0x00).await;
+                    let reason = EncodedString::try_from(r#""Outdated client!""#.to_string());
+                    server
+                        .send(Packet::new(
+                            login::DISCONNECT_ID,
+                            reason.unwrap().as_slice(),
+                        ))
+                        .await;
+                }
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn probes_versions_a_server_accepts() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve_only_764(listener));
+
+        let options = ProbeOptions {
+            login: true,
+            timeout: Duration::from_secs(5),
+            concurrency: 2,
+        };
+        let ping_options = PingOptions {
+            resolve_srv: false,
+            ..PingOptions::default()
+        };
+        let probes = probe_versions(&address, &[763, 764, 765], &ping_options, &options).await;
+
+        let statuses: Vec<_> = probes.iter().map(|probe| &probe.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                &Compatibility::Rejected("server speaks 1.20.2 (protocol 764)".to_string()),
+                &Compatibility::Accepted,
+                &Compatibility::Rejected("server speaks 1.20.2 (protocol 764)".to_string()),
+            ]
+        );
+        let logins: Vec<_> = probes.iter().map(|probe| probe.login.clone()).collect();
+        assert_eq!(
+            logins,
+            [
+                Some(Compatibility::Rejected("Outdated client!".to_string())),
+                Some(Compatibility::Accepted),
+                Some(Compatibility::Rejected("Outdated client!".to_string())),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_handshake_host_in_login_probes() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let ping_options = PingOptions {
+            resolve_srv: false,
+            handshake_host: Some("lobby.example.com".to_string()),
+            ..PingOptions::default()
+        };
+
+        let server = async {
+            let mut server = MockServer::accept(&listener, 764).await;
+            let handshake = server.expect(0x00).await;
+            server.accept_login().await;
+            handshake
+        };
+        let (login, handshake) =
+            tokio::join!(super::start_login(&address, 764, &ping_options), server);
+        login.unwrap();
+
+        let mut data = handshake.data();
+        VarInt::decode(&mut data).unwrap();
+        assert_eq!(String::decode(&mut data).unwrap(), "lobby.example.com");
+    }
+
+    #[tokio::test]
+    async fn fails_probes_which_time_out() {
+        // the connection is accepted by the OS, but nothing ever answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let options = ProbeOptions {
+            timeout: Duration::from_millis(50),
+            ..ProbeOptions::default()
+        };
+        let ping_options = PingOptions {
+            resolve_srv: false,
+            ..PingOptions::default()
+        };
+        let probes = probe_versions(&address, &[764], &ping_options, &options).await;
+
+        assert_eq!(
+            probes,
+            [Probe {
+                protocol_version: 764,
+                status: Compatibility::Failed("no answer within 0.05s".to_string()),
+                login: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn formats_compatibility_matrix() {
+        let probes = [
+            Probe {
+                protocol_version: 763,
+                status: Compatibility::Rejected("server speaks 1.20.2 (protocol 764)".to_string()),
+                login: Some(Compatibility::Rejected("Outdated client!".to_string())),
+            },
+            Probe {
+                protocol_version: 764,
+                status: Compatibility::Accepted,
+                login: Some(Compatibility::Accepted),
+            },
+        ];
+
+        assert_eq!(
+            compatibility_matrix(&probes),
+            "\
+protocol  release      status                                          login
+     763  1.20-1.20.1  rejected (server speaks 1.20.2 (protocol 764))  rejected (Outdated client!)
+     764  1.20.2       accepted                                        accepted
+"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! A file format recording the packets of a connection, for debugging and building test fixtures.
+//!
+//! A capture starts with the [`MAGIC`] bytes and the protocol version of the connection (`i32`),
+//! followed by one record per packet:
+//!
+//! | Field      | Type  | Notes                                         |
+//! |------------|-------|-----------------------------------------------|
+//! | Direction  | `u8`  | 0 if received from the server, 1 if sent      |
+//! | State      | `u8`  | the [`State`] the connection was in            |
+//! | Timestamp  | `u64` | milliseconds since the Unix epoch             |
+//! | Length     | `u32` | the length of the packet id and data          |
+//! | Packet     | bytes | the packet id (as a var-int) and data, uncompressed |
+//!
+//! All integers are big-endian.
+
+use std::{
+    io::{self, Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    encoding::{Decode, VarInt},
+    Packet, State, MAX_PACKET_LENGTH,
+};
+
+/// The bytes every capture file starts with.
+pub const MAGIC: &[u8; 8] = b"MCCCAP\x00\x01";
+
+/// Which way a captured packet travelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from the server.
+    Clientbound,
+    /// Sent to the server.
+    Serverbound,
+}
+
+/// A single captured packet.
+#[derive(Debug)]
+pub struct Record {
+    pub direction: Direction,
+    /// The state the connection was in when the packet was sent or received.
+    pub state: State,
+    /// When the packet was captured, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub packet: Packet,
+}
+
+/// Writes packets to a capture.
+pub struct CaptureWriter {
+    writer: Box<dyn Write + Send>,
+    /// If packets sent to the server are captured, as well as received packets.
+    include_sent: bool,
+}
+
+impl CaptureWriter {
+    /// Starts a capture of a connection speaking `protocol_version`, writing the header to
+    /// `writer`.
+    pub fn new<W>(mut writer: W, protocol_version: i32, include_sent: bool) -> io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&protocol_version.to_be_bytes())?;
+
+        Ok(Self {
+            writer: Box::new(writer),
+            include_sent,
+        })
+    }
+
+    /// Appends a packet to the capture, stamped with the current time. Sent packets are skipped
+    /// unless the capture includes them.
+    pub fn write(&mut self, direction: Direction, state: State, packet: &Packet) -> io::Result<()> {
+        if direction == Direction::Serverbound && !self.include_sent {
+            return Ok(());
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| {
+                u64::try_from(since_epoch.as_millis()).unwrap_or(u64::MAX)
+            });
+
+        let body = [VarInt::from(packet.id()).as_slice(), packet.data()].concat();
+        let length = u32::try_from(body.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        self.writer.write_all(&[
+            match direction {
+                Direction::Clientbound => 0,
+                Direction::Serverbound => 1,
+            },
+            state.to_id(),
+        ])?;
+        self.writer.write_all(&timestamp.to_be_bytes())?;
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(&body)?;
+        self.writer.flush()
+    }
+}
+
+/// Reads packets back from a capture.
+pub struct CaptureReader<R> {
+    reader: R,
+    /// The protocol version of the captured connection.
+    protocol_version: i32,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Opens a capture, reading the header from `reader`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a capture file (or from an unsupported version)",
+            ));
+        }
+
+        let protocol_version = i32::decode(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            protocol_version,
+        })
+    }
+
+    /// Retrieves the protocol version of the captured connection.
+    pub fn protocol_version(&self) -> i32 {
+        self.protocol_version
+    }
+
+    /// Reads the next record, or `None` once the capture has ended.
+    pub fn read(&mut self) -> io::Result<Option<Record>> {
+        let mut header = [0; 2];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let direction = match header[0] {
+            0 => Direction::Clientbound,
+            1 => Direction::Serverbound,
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid direction {other}"),
+                ))
+            }
+        };
+        let state = State::from_id(header[1]).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid state {}", header[1]),
+            )
+        })?;
+        let timestamp = u64::decode(&mut self.reader)?;
+        let length = u32::decode(&mut self.reader)?;
+        // a corrupt length is caught before it is allocated for
+        if length > MAX_PACKET_LENGTH.unsigned_abs() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("record length {length} is longer than any packet may be"),
+            ));
+        }
+
+        let mut body = vec![0; length as usize];
+        self.reader.read_exact(&mut body)?;
+        let mut body = body.as_slice();
+        let id = VarInt::decode(&mut body)?;
+
+        Ok(Some(Record {
+            direction,
+            state,
+            timestamp,
+            packet: Packet::from_parts(i32::from(id), body.to_vec()),
+        }))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::{CaptureReader, CaptureWriter, Direction};
+    use crate::protocol::{Packet, State};
+
+    /// A writer whose contents can be read after it has been handed to a [`CaptureWriter`].
+    #[derive(Clone, Default)]
+    pub struct SharedBuffer(pub Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let buffer = SharedBuffer::default();
+        let mut writer = CaptureWriter::new(buffer.clone(), 764, true).unwrap();
+        writer
+            .write(
+                Direction::Serverbound,
+                State::Handshaking,
+                &Packet::new(0x00, vec![1, 2]),
+            )
+            .unwrap();
+        writer
+            .write(
+                Direction::Clientbound,
+                State::Play,
+                &Packet::new(0x24, vec![3; 200]),
+            )
+            .unwrap();
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let mut reader = CaptureReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.protocol_version(), 764);
+
+        let first = reader.read().unwrap().unwrap();
+        assert_eq!(first.direction, Direction::Serverbound);
+        assert_eq!(first.state, State::Handshaking);
+        assert_eq!(first.packet.id(), 0x00);
+        assert_eq!(first.packet.data(), [1, 2]);
+
+        let second = reader.read().unwrap().unwrap();
+        assert_eq!(second.direction, Direction::Clientbound);
+        assert_eq!(second.state, State::Play);
+        assert_eq!(second.packet.data(), [3; 200]);
+        assert!(second.timestamp >= first.timestamp);
+
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn skips_sent_packets_unless_included() {
+        let buffer = SharedBuffer::default();
+        let mut writer = CaptureWriter::new(buffer.clone(), 764, false).unwrap();
+        writer
+            .write(
+                Direction::Serverbound,
+                State::Status,
+                &Packet::new(0x00, vec![]),
+            )
+            .unwrap();
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let mut reader = CaptureReader::new(bytes.as_slice()).unwrap();
+        assert!(reader.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_oversized_records() {
+        let buffer = SharedBuffer::default();
+        CaptureWriter::new(buffer.clone(), 764, true).unwrap();
+        let mut bytes = buffer.0.lock().unwrap().clone();
+        // a clientbound play record, at time 0, claiming to be 4 GiB long
+        bytes.extend_from_slice(&[0, 4]);
+        bytes.extend_from_slice(&0_u64.to_be_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+
+        let mut reader = CaptureReader::new(bytes.as_slice()).unwrap();
+        let error = reader.read().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(CaptureReader::new(b"[config]\nkey = 1".as_slice()).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+use std::io::{self, Read};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    encoding::Decode,
+    nbt::{read_nbt, NbtFormat, NbtTag},
+};
+
+/// The first protocol version (1.20.3) which sends chat components as NBT, rather than JSON.
+pub const NBT_COMPONENT_VERSION: i32 = 765;
+
+/// A piece of formatted text, as used for chat messages, disconnect reasons, and the server
+/// description.
+///
+/// On the wire, a component may be a plain string, an object with formatting and children, or an
+/// array of components (the first being the root and the rest its `extra`); every form
+/// deserializes into this type. Components holding only text serialize as plain strings, and any
+/// others as objects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Raw", into = "Raw")]
+pub struct ChatComponent {
+    /// The literal text of the component.
+    pub text: String,
+    /// A translation key to display instead of `text` (e.g., `multiplayer.disconnect.banned`).
+    pub translate: Option<String>,
+    /// The arguments substituted into the translation.
+    pub with: Vec<ChatComponent>,
+    /// The color of the text, either a named color (e.g., `red`) or a `#rrggbb` hex code.
+    pub color: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    /// The components displayed after this one, inheriting its formatting.
+    pub extra: Vec<ChatComponent>,
+}
+
+/// The fields of the object form of a component.
+#[derive(Serialize, Deserialize)]
+struct Fields {
+    // vanilla clients need some content, so even empty text is written
+    #[serde(default)]
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    translate: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    with: Vec<ChatComponent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    underlined: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    obfuscated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extra: Vec<ChatComponent>,
+}
+
+/// A component in any of the forms it may take on the wire.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Raw {
+    Text(String),
+    Object(Fields),
+    Array(Vec<ChatComponent>),
+}
+
+impl From<Raw> for ChatComponent {
+    fn from(raw: Raw) -> Self {
+        match raw {
+            Raw::Text(text) => Self::text(text),
+            Raw::Object(fields) => Self {
+                text: fields.text,
+                translate: fields.translate,
+                with: fields.with,
+                color: fields.color,
+                bold: fields.bold,
+                italic: fields.italic,
+                underlined: fields.underlined,
+                strikethrough: fields.strikethrough,
+                obfuscated: fields.obfuscated,
+                extra: fields.extra,
+            },
+            Raw::Array(components) => {
+                let mut components = components.into_iter();
+                let mut root = components.next().unwrap_or_default();
+                root.extra.extend(components);
+
+                root
+            }
+        }
+    }
+}
+
+impl From<ChatComponent> for Raw {
+    fn from(component: ChatComponent) -> Self {
+        if component == ChatComponent::text(component.text.as_str()) {
+            return Self::Text(component.text);
+        }
+
+        Self::Object(Fields {
+            text: component.text,
+            translate: component.translate,
+            with: component.with,
+            color: component.color,
+            bold: component.bold,
+            italic: component.italic,
+            underlined: component.underlined,
+            strikethrough: component.strikethrough,
+            obfuscated: component.obfuscated,
+            extra: component.extra,
+        })
+    }
+}
+
+impl ChatComponent {
+    /// Decodes a component from a packet, sent as a string of JSON before protocol 765 and as
+    /// [NBT](Self::from_nbt) from then on.
+    ///
+    /// Components in the status response are always JSON, as part of the status document.
+    pub fn decode<R: Read>(reader: &mut R, protocol_version: i32) -> io::Result<Self> {
+        if protocol_version >= NBT_COMPONENT_VERSION {
+            let nbt = read_nbt(reader, NbtFormat::for_protocol(protocol_version))?;
+            return Ok(Self::from_nbt(&nbt));
+        }
+
+        let json = String::decode(reader)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Creates a component holding only plain `text`.
+    pub fn text<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Converts a component sent as NBT, which takes the same forms as JSON: a string tag, a
+    /// compound of fields, or a list of components.
+    ///
+    /// Numbers (e.g., the arguments of a translation) become their text, and any other tag, or
+    /// field of the wrong type, is left out.
+    pub fn from_nbt(tag: &NbtTag) -> Self {
+        let fields = match tag {
+            // lists mixing strings or numbers with compounds wrap each in a compound, under an
+            // empty key
+            NbtTag::Compound(fields) if fields.len() == 1 && fields.contains_key("") => {
+                return Self::from_nbt(&fields[""]);
+            }
+            NbtTag::Compound(fields) => fields,
+            NbtTag::List(components) => {
+                let mut components = components.iter().map(Self::from_nbt);
+                let mut root = components.next().unwrap_or_default();
+                root.extra.extend(components);
+
+                return root;
+            }
+            NbtTag::String(text) => return Self::text(text.as_str()),
+            NbtTag::Byte(value) => return Self::text(value.to_string()),
+            NbtTag::Short(value) => return Self::text(value.to_string()),
+            NbtTag::Int(value) => return Self::text(value.to_string()),
+            NbtTag::Long(value) => return Self::text(value.to_string()),
+            NbtTag::Float(value) => return Self::text(value.to_string()),
+            NbtTag::Double(value) => return Self::text(value.to_string()),
+            _ => return Self::default(),
+        };
+
+        let string = |key: &str| fields.get(key).and_then(NbtTag::as_str).map(String::from);
+        let flag = |key: &str| match fields.get(key) {
+            Some(NbtTag::Byte(value)) => Some(*value != 0),
+            _ => None,
+        };
+        let components = |key: &str| {
+            fields
+                .get(key)
+                .and_then(NbtTag::as_list)
+                .map_or_else(Vec::new, |list| list.iter().map(Self::from_nbt).collect())
+        };
+
+        Self {
+            text: string("text").unwrap_or_default(),
+            translate: string("translate"),
+            with: components("with"),
+            color: string("color"),
+            bold: flag("bold"),
+            italic: flag("italic"),
+            underlined: flag("underlined"),
+            strikethrough: flag("strikethrough"),
+            obfuscated: flag("obfuscated"),
+            extra: components("extra"),
+        }
+    }
+
+    /// Renders the component and its children as plain text, without any formatting.
+    ///
+    /// Translations are not looked up, so a translated component renders as its key followed by
+    /// its arguments.
+    pub fn to_plain(&self) -> String {
+        let mut plain = String::new();
+        self.write_plain(&mut plain);
+
+        plain
+    }
+
+    fn write_plain(&self, plain: &mut String) {
+        match &self.translate {
+            Some(key) if self.with.is_empty() => plain.push_str(key),
+            Some(key) => {
+                let with: Vec<String> = self.with.iter().map(Self::to_plain).collect();
+                plain.push_str(key);
+                plain.push_str(" [");
+                plain.push_str(&with.join(", "));
+                plain.push(']');
+            }
+            None => plain.push_str(&self.text),
+        }
+
+        for extra in &self.extra {
+            extra.write_plain(plain);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChatComponent;
+    use crate::protocol::{
+        encoding::EncodedString,
+        nbt::test::{compound, int, list, network, string},
+    };
+
+    #[test]
+    fn deserializes_string_form() {
+        let component: ChatComponent = serde_json::from_str(r#""A Minecraft Server""#).unwrap();
+
+        assert_eq!(component, ChatComponent::text("A Minecraft Server"));
+    }
+
+    #[test]
+    fn deserializes_array_form() {
+        let component: ChatComponent = serde_json::from_str(
+            r#"[{"text":"A ","color":"gold"},"Minecraft ",{"text":"Server"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(component.color.as_deref(), Some("gold"));
+        assert_eq!(component.extra.len(), 2);
+        assert_eq!(component.to_plain(), "A Minecraft Server");
+
+        let component: ChatComponent = serde_json::from_str("[]").unwrap();
+        assert_eq!(component, ChatComponent::default());
+    }
+
+    #[test]
+    fn serializes_plain_text_as_string() {
+        let json = serde_json::to_string(&ChatComponent::text("A Minecraft Server")).unwrap();
+        assert_eq!(json, r#""A Minecraft Server""#);
+
+        let mut component = ChatComponent::text("");
+        component.color = Some("red".to_string());
+        component.extra.push(ChatComponent::text("!"));
+        let json = serde_json::to_string(&component).unwrap();
+        assert_eq!(json, r#"{"text":"","color":"red","extra":["!"]}"#);
+    }
+
+    #[test]
+    fn renders_extra_as_plain_text() {
+        let component: ChatComponent = serde_json::from_str(
+            r#"{"text":"Hello ","color":"gold","extra":[{"text":"world","bold":true},"!"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(component.color.as_deref(), Some("gold"));
+        assert_eq!(component.to_plain(), "Hello world!");
+    }
+
+    #[test]
+    fn renders_translations_as_key() {
+        let component: ChatComponent = serde_json::from_str(
+            r#"{"translate":"multiplayer.player.joined","with":[{"text":"Steve"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(component.to_plain(), "multiplayer.player.joined [Steve]");
+    }
+
+    #[test]
+    fn decodes_nbt_components() {
+        let data = network(compound(vec![
+            ("translate", string("chat.type.text")),
+            (
+                "with",
+                list(vec![
+                    compound(vec![("", string("Steve"))]),
+                    compound(vec![("", int(3))]),
+                ]),
+            ),
+            ("color", string("gold")),
+            ("bold", (0x01, vec![1])),
+            (
+                "extra",
+                list(vec![
+                    compound(vec![("text", string("!"))]),
+                    compound(vec![("", string("?"))]),
+                ]),
+            ),
+        ]));
+
+        let component = ChatComponent::decode(&mut data.as_slice(), 765).unwrap();
+        assert_eq!(component.translate.as_deref(), Some("chat.type.text"));
+        assert_eq!(
+            component.with,
+            [ChatComponent::text("Steve"), ChatComponent::text("3")]
+        );
+        assert_eq!(component.color.as_deref(), Some("gold"));
+        assert_eq!(component.bold, Some(true));
+        assert_eq!(component.italic, None);
+        assert_eq!(component.to_plain(), "chat.type.text [Steve, 3]!?");
+
+        let data = network(string("Hello"));
+        assert_eq!(
+            ChatComponent::decode(&mut data.as_slice(), 765).unwrap(),
+            ChatComponent::text("Hello")
+        );
+    }
+
+    #[test]
+    fn decodes_json_and_nbt_alike() {
+        let json = EncodedString::try_from(
+            r#"{"text":"Welcome, ","color":"gold","extra":[{"text":"Steve","bold":true},"!"]}"#
+                .to_string(),
+        )
+        .unwrap()
+        .as_slice();
+        let nbt = network(compound(vec![
+            ("text", string("Welcome, ")),
+            ("color", string("gold")),
+            (
+                "extra",
+                list(vec![
+                    compound(vec![("text", string("Steve")), ("bold", (0x01, vec![1]))]),
+                    compound(vec![("", string("!"))]),
+                ]),
+            ),
+        ]));
+
+        let from_json = ChatComponent::decode(&mut json.as_slice(), 764).unwrap();
+        let from_nbt = ChatComponent::decode(&mut nbt.as_slice(), 765).unwrap();
+        assert_eq!(from_json, from_nbt);
+        assert_eq!(from_nbt.to_plain(), "Welcome, Steve!");
+
+        // each is read to its end, leaving what follows it in the packet
+        let data = [nbt.as_slice(), &[1]].concat();
+        let mut reader = data.as_slice();
+        ChatComponent::decode(&mut reader, 765).unwrap();
+        assert_eq!(reader, [1]);
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{self, Read, Write};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use super::{encoding::VarInt, MAX_PACKET_LENGTH};
+
+/// Builds the body of a frame (everything after the packet length) on a connection with
+/// compression enabled, compressing `uncompressed` (the packet id and data) if it is at least
+/// `threshold` bytes long.
+pub fn compress(uncompressed: &[u8], threshold: usize) -> io::Result<Vec<u8>> {
+    if uncompressed.len() < threshold {
+        // a data length of 0 signals the packet is not compressed
+        return Ok([VarInt::from(0).as_slice(), uncompressed].concat());
+    }
+
+    let data_length = i32::try_from(uncompressed.len())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(uncompressed)?;
+    let compressed = encoder.finish()?;
+
+    Ok([VarInt::from(data_length).as_slice(), &compressed].concat())
+}
+
+/// Reads the body of a frame (everything after the packet length) on a connection with
+/// compression enabled, returning the packet id and data.
+pub fn decompress(mut frame: &[u8]) -> io::Result<Vec<u8>> {
+    let data_length = i32::from(VarInt::read(&mut frame)?);
+
+    if data_length == 0 {
+        return Ok(frame.to_vec());
+    }
+
+    if data_length > MAX_PACKET_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("data length {data_length} is longer than any packet may be"),
+        ));
+    }
+
+    let data_length = usize::try_from(data_length).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid negative data length {data_length}"),
+        )
+    })?;
+
+    // reading a byte past the declared length is enough to tell the packet lied about it, without
+    // inflating all of a packet which would never end
+    let mut uncompressed = Vec::with_capacity(data_length);
+    ZlibDecoder::new(frame)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut uncompressed)?;
+
+    if uncompressed.len() != data_length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "decompressed {} bytes, but the packet declared {data_length}",
+                uncompressed.len()
+            ),
+        ));
+    }
+
+    Ok(uncompressed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compress, decompress};
+
+    #[test]
+    fn leaves_small_packets_uncompressed() {
+        let frame = compress(&[0x01, 0x02], 256).unwrap();
+
+        assert_eq!(frame, [0x00, 0x01, 0x02]);
+        assert_eq!(decompress(&frame).unwrap(), [0x01, 0x02]);
+    }
+
+    #[test]
+    fn round_trips_large_packets() {
+        let packet = vec![0x2a; 1024];
+        let frame = compress(&packet, 256).unwrap();
+
+        // data length of 1024 as a var-int, then the compressed bytes
+        assert_eq!(frame[0..2], [0b1000_0000, 0b0000_1000]);
+        assert!(frame.len() < packet.len());
+        assert_eq!(decompress(&frame).unwrap(), packet);
+    }
+
+    #[test]
+    fn rejects_mismatched_length() {
+        let mut frame = compress(&[0x2a; 300], 256).unwrap();
+        // claim 301 bytes instead of 300
+        frame[0] += 1;
+
+        assert!(decompress(&frame).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_packets() {
+        // a data length of 2097152 (one more than a packet may be) as a var-int
+        let frame = [0x80, 0x80, 0x80, 0x01];
+        assert!(decompress(&frame).is_err());
+
+        // a packet inflating to far more than it declared is cut off just past its data length
+        let frame = compress(&vec![0x2a; 1_000_000], 256).unwrap();
+        let mut shrunk = compress(&[0x2a; 300], 256).unwrap()[..2].to_vec();
+        shrunk.extend_from_slice(&frame[3..]);
+        assert!(decompress(&shrunk).is_err());
+    }
+}
@@ -0,0 +1,1155 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    time::Duration,
+};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+mod play;
+mod rate_limit;
+mod response_faults;
+mod split;
+
+use rate_limit::RateLimiter;
+use response_faults::FaultInjector;
+pub use response_faults::ResponseFaults;
+pub use split::{ReadHalf, WriteHalf};
+
+use super::{
+    capture::{CaptureWriter, Direction},
+    compression,
+    encoding::{Decode, VarInt},
+    packets::{
+        configuration::{
+            self, AcknowledgeFinishConfiguration, ClientBrand, ClientInformation, DimensionType,
+            FeatureFlags, KnownPacks, RegistryData, UpdateTags,
+        },
+        login::{
+            self, LoginAcknowledged, LoginPluginRequest, LoginPluginResponse, LoginStart,
+            LoginSuccess, SetCompression,
+        },
+        Handshake,
+    },
+    proxy::ProxyProtocol,
+    ConnectionError, Packet, State, World, MAX_PACKET_LENGTH,
+};
+
+/// How many forced resource packs are declined before the server is taken to require one, unless
+/// set with [`Connection::set_resource_pack_limit`].
+pub const DEFAULT_RESOURCE_PACK_LIMIT: u32 = 3;
+
+/// The most packets a bundle may hold, as in the vanilla client, after which the bundle is taken
+/// to have ended so a missing closing delimiter can not hold packets back forever.
+const MAX_BUNDLE_SIZE: usize = 4096;
+
+/// The most packets held back while awaiting a response, after which the oldest are dropped so a
+/// response which never comes can not pile up packets forever.
+const MAX_HELD_BACK: usize = 4096;
+
+/// The most requests which timed out whose late responses are looked out for, to be dropped
+/// rather than handled as if the server had sent them unasked.
+const MAX_LATE_RESPONSES: usize = 16;
+
+/// The shortest time between automatic respawns, so a server which kills players as they spawn
+/// does not keep the client respawning in a loop.
+pub const MIN_RESPAWN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The most signed chat messages received before they are acknowledged, as in the vanilla
+/// client.
+const MAX_UNACKNOWLEDGED_MESSAGES: i32 = 64;
+
+/// The health below which [`Event::LowHealth`](super::Event::LowHealth) is returned, unless set
+/// with [`Connection::set_low_health_threshold`], so three hearts.
+pub const DEFAULT_LOW_HEALTH_THRESHOLD: f32 = 6.0;
+
+/// A handler registered with [`Connection::on_packet`], which is given the body of each packet
+/// it handles.
+pub type PacketHandler = Box<dyn FnMut(&[u8]) + Send>;
+
+/// What finds the response to a request sent with [`Connection::send_and_await`].
+type ResponseMatcher = Box<dyn FnMut(&Packet) -> bool + Send>;
+
+/// Where the player is in being respawned automatically after dying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RespawnState {
+    /// The player has not died since they last had health, or was not respawned automatically.
+    Alive,
+    /// The player was respawned automatically, and has not yet been given health again.
+    Respawned,
+    /// The player died again too soon after being respawned automatically, so is respawned at
+    /// this instant.
+    Scheduled(tokio::time::Instant),
+}
+
+/// A connection to a Minecraft server, reading and writing framed packets over any async byte
+/// stream (e.g., a [`tokio::net::TcpStream`]).
+pub struct Connection<S> {
+    stream: S,
+    /// The protocol version spoken on this connection.
+    protocol_version: i32,
+    /// The state the connection is in, which decides how packet ids are interpreted.
+    state: State,
+    /// Packets of at least this many bytes are compressed, once the server has enabled
+    /// compression.
+    ///
+    /// Compression is enabled during login and stays enabled in every state after it, so this is
+    /// never reset when the state changes.
+    compression_threshold: Option<usize>,
+    /// What is known about the world, once in the Play state.
+    world: World,
+    /// Where packets are recorded to, if they are being captured.
+    capture: Option<CaptureWriter>,
+    /// The handlers registered for packets, by the state and id of the packets they handle.
+    handlers: HashMap<(State, i32), PacketHandler>,
+    /// How many forced resource packs have been declined on this connection.
+    forced_packs_declined: u32,
+    /// How many forced resource packs are declined before the server is taken to require one.
+    resource_pack_limit: u32,
+    /// The brand the client names itself with at the start of configuration, if it does.
+    client_brand: Option<String>,
+    /// If the player is respawned as soon as they die.
+    auto_respawn: bool,
+    /// When the player was last respawned automatically.
+    last_respawn: Option<tokio::time::Instant>,
+    /// Where the player is in being respawned automatically after dying.
+    respawn_state: RespawnState,
+    /// The health below which the player's health counts as low.
+    low_health_threshold: f32,
+    /// How long the connection may go without receiving anything but keep alives before the
+    /// client disconnects, if it ever does.
+    idle_timeout: Option<Duration>,
+    /// When a packet other than a keep alive was last received during play.
+    last_activity: Option<tokio::time::Instant>,
+    /// How long the connection may go without receiving anything before it is taken to be dead,
+    /// if it ever is.
+    dead_connection_timeout: Option<Duration>,
+    /// When any packet was last received during play.
+    last_received: Option<tokio::time::Instant>,
+    /// The transaction id of the latest request for command suggestions.
+    suggestions_transaction: i32,
+    /// If unsigned chat messages are sent even to servers which enforce secure chat.
+    insecure_chat: bool,
+    /// If play packets which can not be decoded are logged and skipped, rather than ending the
+    /// connection (unless the connection can not go on without them).
+    lenient_decoding: bool,
+    /// How often the player is turned slightly so the server does not kick them for being idle,
+    /// if they are.
+    anti_afk_interval: Option<Duration>,
+    /// When the player is next turned to keep them from being idle.
+    next_anti_afk: Option<tokio::time::Instant>,
+    /// How far the player was last turned to keep them from being idle, in degrees: away from
+    /// where the server faced them (positive), back again (negative), or not at all since.
+    anti_afk_turn: f32,
+    /// The id of the latest teleport, until its confirmation has been sent.
+    unconfirmed_teleport: Option<i32>,
+    /// The index of the latest chat message each player sent, by their UUID.
+    chat_indices: HashMap<u128, i32>,
+    /// How many signed chat messages have been received since they were last acknowledged.
+    unacknowledged_messages: i32,
+    /// The packets of the latest bundle which have not been handled yet.
+    bundle: VecDeque<Packet>,
+    /// The packets of a bundle whose closing delimiter has not been read yet, if reading stopped
+    /// part-way through one (e.g., [`next_event`](Self::next_event) timing out).
+    open_bundle: Option<Vec<Packet>>,
+    /// The packets read during play while awaiting a response, which are handled in order before
+    /// any more are read.
+    held_back: VecDeque<Packet>,
+    /// What finds the responses to the requests which timed out, so they are dropped if they
+    /// arrive after all.
+    late_responses: VecDeque<ResponseMatcher>,
+    /// The dimension types the server sent during configuration, by name, which give the height
+    /// of the world the player joins.
+    dimension_types: Vec<(String, DimensionType)>,
+    /// The experimental features the server enabled during configuration.
+    feature_flags: Vec<String>,
+    /// The ids of the entries in each tag the server sent, by tag, by registry.
+    tags: HashMap<String, HashMap<String, Vec<i32>>>,
+    /// What paces the packets sent with [`write_packet`](Self::write_packet), if they are paced.
+    rate_limiter: Option<RateLimiter>,
+    /// What delays or drops the responses to keep alives and pings, if they are faulted.
+    fault_injector: Option<FaultInjector>,
+    /// The bytes written with [`write_packet_buffered`](Self::write_packet_buffered) which have
+    /// not yet been flushed to the stream.
+    write_buffer: Vec<u8>,
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Creates a new connection over `stream`, speaking the given `protocol_version`.
+    pub fn new(stream: S, protocol_version: i32) -> Self {
+        Self {
+            stream,
+            protocol_version,
+            state: State::Handshaking,
+            compression_threshold: None,
+            world: World::default(),
+            capture: None,
+            handlers: HashMap::new(),
+            forced_packs_declined: 0,
+            resource_pack_limit: DEFAULT_RESOURCE_PACK_LIMIT,
+            client_brand: None,
+            auto_respawn: false,
+            last_respawn: None,
+            respawn_state: RespawnState::Alive,
+            low_health_threshold: DEFAULT_LOW_HEALTH_THRESHOLD,
+            idle_timeout: None,
+            dead_connection_timeout: None,
+            last_received: None,
+            last_activity: None,
+            suggestions_transaction: 0,
+            insecure_chat: false,
+            lenient_decoding: false,
+            anti_afk_interval: None,
+            next_anti_afk: None,
+            anti_afk_turn: 0.0,
+            unconfirmed_teleport: None,
+            chat_indices: HashMap::new(),
+            unacknowledged_messages: 0,
+            bundle: VecDeque::new(),
+            open_bundle: None,
+            held_back: VecDeque::new(),
+            late_responses: VecDeque::new(),
+            dimension_types: Vec::new(),
+            feature_flags: Vec::new(),
+            tags: HashMap::new(),
+            rate_limiter: None,
+            fault_injector: None,
+            write_buffer: Vec::new(),
+        }
+    }
+
+    /// Creates a new connection which is already in the given `state`, for tests which start
+    /// part-way through a session.
+    #[cfg(test)]
+    pub(crate) fn with_state(stream: S, protocol_version: i32, state: State) -> Self {
+        Self {
+            state,
+            ..Self::new(stream, protocol_version)
+        }
+    }
+
+    /// Sets the threshold packets are compressed from, as Set Compression does, for tests which
+    /// play the server's side of a login.
+    #[cfg(test)]
+    pub(crate) fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    /// Retrieves the protocol version in use on this connection.
+    ///
+    /// All version-dependent encoding and decoding reads the version from here.
+    pub fn protocol_version(&self) -> i32 {
+        self.protocol_version
+    }
+
+    /// Consumes the connection, returning the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Retrieves the index of the latest chat message the player with the UUID `sender` sent,
+    /// if they have sent any since the client joined.
+    pub fn last_chat_index(&self, sender: u128) -> Option<i32> {
+        self.chat_indices.get(&sender).copied()
+    }
+
+    /// Retrieves the state the connection is in.
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Records every packet received on the connection (and sent, if the capture includes them)
+    /// to `capture`.
+    pub fn set_capture(&mut self, capture: CaptureWriter) {
+        self.capture = Some(capture);
+    }
+
+    /// Registers `handler` to be called with the body of every packet with the given `id`
+    /// received in `state`, replacing any handler already registered for it.
+    ///
+    /// The packets it handles are consumed by the handler: they are never returned from
+    /// [`read_packet`](Self::read_packet), so the built-in handling of them (e.g., answering keep
+    /// alives) no longer happens. Packets without a handler are handled as usual.
+    ///
+    /// Handlers are called synchronously while the connection is reading, so they can not borrow
+    /// the connection or await; to act on a packet (e.g., by sending a response), a handler can
+    /// instead send what it needs over a channel to the task which owns the connection. Handlers
+    /// must be `Send`, so the connection can still be moved between tasks.
+    pub fn on_packet<F>(&mut self, id: i32, state: State, handler: F)
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.handlers.insert((state, id), Box::new(handler));
+    }
+
+    /// Sets how many forced resource packs are declined during play before the server is taken to
+    /// require one, and [`ConnectionError::ResourcePackRequired`] is returned. Optional packs are
+    /// always declined.
+    pub fn set_resource_pack_limit(&mut self, limit: u32) {
+        self.resource_pack_limit = limit;
+    }
+
+    /// Sets the brand (e.g., `vanilla`) sent on the `minecraft:brand` plugin channel at the start
+    /// of [`configure`](Self::configure), or `None` to send no brand.
+    pub fn set_client_brand(&mut self, brand: Option<String>) {
+        self.client_brand = brand;
+    }
+
+    /// Sets whether the player is respawned as soon as they die during play, at most once every
+    /// [`MIN_RESPAWN_INTERVAL`].
+    pub fn set_auto_respawn(&mut self, auto_respawn: bool) {
+        self.auto_respawn = auto_respawn;
+    }
+
+    /// Sets the health below which [`Event::LowHealth`](super::Event::LowHealth) is returned
+    /// during play.
+    pub fn set_low_health_threshold(&mut self, threshold: f32) {
+        self.low_health_threshold = threshold;
+    }
+
+    /// Sets how long the connection may go during play without receiving anything but keep
+    /// alives, after which the client disconnects and
+    /// [`next_event`](Self::next_event) returns [`ConnectionError::IdleTimeout`], or `None` to
+    /// stay connected however idle the connection is.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// Sets how long the connection may go during play without receiving any packet at all,
+    /// keep alives included, after which [`next_event`](Self::next_event) returns
+    /// [`ConnectionError::ConnectionDead`], or `None` to wait on a silent server forever.
+    pub fn set_dead_connection_timeout(&mut self, timeout: Option<Duration>) {
+        self.dead_connection_timeout = timeout;
+    }
+
+    /// Sets whether [`send_chat`](Self::send_chat) sends unsigned messages to servers which
+    /// enforce secure chat, which kick players for them.
+    pub fn set_insecure_chat(&mut self, insecure_chat: bool) {
+        self.insecure_chat = insecure_chat;
+    }
+
+    /// Sets whether play packets which can not be decoded (e.g., laid out as another version
+    /// lays them out) are logged with a hex dump of their body and skipped by
+    /// [`next_event`](Self::next_event), rather than returned as an error.
+    ///
+    /// Packets the connection can not go on without (e.g., Disconnect) still fail, see
+    /// [`Clientbound::is_critical`](crate::protocol::packets::play::Clientbound::is_critical).
+    pub fn set_lenient_decoding(&mut self, lenient_decoding: bool) {
+        self.lenient_decoding = lenient_decoding;
+    }
+
+    /// Sets how often the player is turned slightly during play, so servers which kick idle
+    /// players do not kick them, or `None` to leave the player idle.
+    ///
+    /// The player is only turned once the server has said where they are, and never within an
+    /// interval of the server teleporting them, so as not to fight the teleport. The interval is
+    /// checked as packets are received, which servers do at least as often as they send keep
+    /// alives.
+    pub fn set_anti_afk_interval(&mut self, interval: Option<Duration>) {
+        self.anti_afk_interval = interval;
+    }
+
+    /// Sets how many packets [`write_packet`](Self::write_packet) sends each second at most, so
+    /// servers do not kick the player for spamming (e.g., chat), or `None` to send packets as
+    /// fast as they are written.
+    ///
+    /// Up to a second's worth of packets are sent at once, before the rest are paced. Keep alive
+    /// responses are never held back, as the server disconnects clients which answer them late.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is `Some(0)`.
+    pub fn set_send_rate(&mut self, packets_per_second: Option<u32>) {
+        assert_ne!(
+            packets_per_second,
+            Some(0),
+            "the send rate must be positive"
+        );
+        self.rate_limiter = packets_per_second.map(RateLimiter::new);
+    }
+
+    /// Sets the faults put into the responses to the server's keep alives and pings, to test how
+    /// the server handles a client which answers late or not at all, or `None` to answer straight
+    /// away.
+    ///
+    /// Nothing else is read while a response is delayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the drop probability is not from 0 to 1.
+    pub fn set_response_faults(&mut self, faults: Option<ResponseFaults>) {
+        if let Some(faults) = &faults {
+            assert!(
+                (0.0..=1.0).contains(&faults.drop_probability),
+                "the drop probability must be from 0 to 1"
+            );
+        }
+        self.fault_injector = faults.map(FaultInjector::new);
+    }
+
+    /// Waits out the [response faults](Self::set_response_faults) of a response to the server,
+    /// then decides if it is sent rather than dropped.
+    async fn should_respond(&mut self) -> bool {
+        let Some(fault_injector) = &mut self.fault_injector else {
+            return true;
+        };
+
+        let send = fault_injector.should_send().await;
+        if !send {
+            tracing::debug!("Dropped a response to the server");
+        }
+        send
+    }
+
+    /// Sends the response to a keep alive straight away, unless the [response
+    /// faults](Self::set_response_faults) drop it.
+    async fn respond<P>(&mut self, response: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        if !self.should_respond().await {
+            return Ok(());
+        }
+
+        self.write_packet_unpaced(response).await
+    }
+
+    /// Sends the pong answering a ping, paced as [`write_packet`](Self::write_packet) paces
+    /// packets, unless the [response faults](Self::set_response_faults) drop it.
+    async fn respond_to_ping<P>(&mut self, pong: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        if !self.should_respond().await {
+            return Ok(());
+        }
+
+        self.write_packet(pong).await
+    }
+
+    /// Retrieves what is known about the world.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Retrieves the identifiers of the experimental features the server enabled during
+    /// configuration (e.g., `minecraft:update_1_21`), which is empty if it enabled none.
+    pub fn feature_flags(&self) -> &[String] {
+        &self.feature_flags
+    }
+
+    /// Retrieves the ids of the entries of the `registry` (e.g., `minecraft:block`) which are in
+    /// `tag` (e.g., `minecraft:mineable/pickaxe`), or `None` if the server has not sent the tag.
+    pub fn tag(&self, registry: &str, tag: &str) -> Option<&[i32]> {
+        self.tags.get(registry)?.get(tag).map(Vec::as_slice)
+    }
+
+    /// Holds on to the tags of an Update Tags packet, in place of those of each registry it has
+    /// tags for.
+    fn update_tags(&mut self, tags: UpdateTags) {
+        self.tags.extend(tags.registries);
+    }
+
+    /// Writes a PROXY protocol header announcing a connection from `source` to `destination`,
+    /// for servers behind a frontend which requires one. Must be sent before the handshake.
+    pub async fn send_proxy_header(
+        &mut self,
+        version: ProxyProtocol,
+        source: SocketAddr,
+        destination: SocketAddr,
+    ) -> Result<(), ConnectionError> {
+        self.write_buffer
+            .extend_from_slice(&version.header(source, destination));
+
+        self.flush().await
+    }
+
+    /// Writes the handshake packet for this connection's protocol version, moving the server
+    /// into the status state if `is_status` is set, or the login state otherwise.
+    pub async fn handshake(
+        &mut self,
+        server_address: String,
+        server_port: u16,
+        is_status: bool,
+    ) -> Result<(), ConnectionError> {
+        self.handshake_buffered(server_address, server_port, is_status)?;
+
+        self.flush().await
+    }
+
+    /// Buffers the handshake as [`handshake`](Self::handshake) does, without flushing it, so it
+    /// can be sent together with the packet which follows it (e.g., the login start).
+    pub fn handshake_buffered(
+        &mut self,
+        server_address: String,
+        server_port: u16,
+        is_status: bool,
+    ) -> Result<(), ConnectionError> {
+        let handshake = Handshake::new(
+            self.protocol_version,
+            server_address,
+            server_port,
+            is_status,
+        )?;
+
+        self.write_packet_buffered(handshake)?;
+        self.state = if is_status {
+            State::Status
+        } else {
+            State::Login
+        };
+
+        Ok(())
+    }
+
+    /// Logs in as `username`, after a login [`handshake`](Self::handshake).
+    ///
+    /// Handles compression and login plugin requests along the way, and on success moves the
+    /// connection into the Configuration state (acknowledging the login, on protocol 764 and
+    /// onwards) or otherwise straight into the Play state.
+    pub async fn login(
+        &mut self,
+        username: String,
+        uuid: Option<u128>,
+    ) -> Result<LoginSuccess, ConnectionError> {
+        self.write_packet(LoginStart::new(self.protocol_version, username, uuid)?)
+            .await?;
+
+        loop {
+            let packet = self.read_packet().await?;
+
+            match packet.id() {
+                login::DISCONNECT_ID => {
+                    let reason = String::decode(&mut packet.data())?;
+                    return Err(ConnectionError::disconnected_json(&reason));
+                }
+                login::ENCRYPTION_REQUEST_ID => return Err(ConnectionError::EncryptionUnsupported),
+                login::LOGIN_SUCCESS_ID => {
+                    let success = LoginSuccess::decode(packet.data(), self.protocol_version)?;
+
+                    if self.protocol_version >= login::CONFIGURATION_VERSION {
+                        self.write_packet(LoginAcknowledged::default()).await?;
+                        self.state = State::Configuration;
+                    } else {
+                        self.state = State::Play;
+                    }
+
+                    return Ok(success);
+                }
+                login::SET_COMPRESSION_ID => {
+                    let threshold = SetCompression::decode(packet.data())?.threshold;
+                    // a negative threshold disables compression
+                    self.compression_threshold = usize::try_from(threshold).ok();
+                }
+                login::LOGIN_PLUGIN_REQUEST_ID => {
+                    let request = LoginPluginRequest::decode(packet.data())?;
+                    self.write_packet(LoginPluginResponse {
+                        message_id: request.message_id,
+                        data: None,
+                    })
+                    .await?;
+                }
+                id => {
+                    return Err(ConnectionError::UnexpectedPacket {
+                        state: self.state,
+                        id,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Runs the Configuration state until the server finishes it, answering keep alives and pings
+    /// along the way, then acknowledges and moves the connection into the Play state.
+    ///
+    /// The client brand, if one is [set](Self::set_client_brand), and then the
+    /// `client_information` are sent to the server first, as the vanilla client does.
+    pub async fn configure(
+        &mut self,
+        client_information: ClientInformation,
+    ) -> Result<(), ConnectionError> {
+        if let Some(brand) = self.client_brand.clone() {
+            self.write_packet(ClientBrand::new(brand, self.protocol_version)?)
+                .await?;
+        }
+        self.write_packet(Packet::try_from(client_information)?)
+            .await?;
+
+        loop {
+            let packet = self.read_packet().await?;
+
+            // packets without a decoder are skipped
+            let Some(kind) =
+                configuration::Clientbound::from_id(packet.id(), self.protocol_version)
+            else {
+                continue;
+            };
+
+            match kind {
+                configuration::Clientbound::Disconnect => {
+                    let disconnect =
+                        configuration::Disconnect::decode(packet.data(), self.protocol_version)?;
+                    return Err(ConnectionError::disconnected(&disconnect.reason));
+                }
+                configuration::Clientbound::FinishConfiguration => {
+                    self.write_packet(AcknowledgeFinishConfiguration {
+                        protocol_version: self.protocol_version,
+                    })
+                    .await?;
+                    self.state = State::Play;
+
+                    return Ok(());
+                }
+                configuration::Clientbound::KeepAlive => {
+                    let keep_alive =
+                        configuration::KeepAlive::decode(packet.data(), self.protocol_version)?;
+                    self.respond(keep_alive).await?;
+                }
+                configuration::Clientbound::Ping => {
+                    let ping = configuration::Ping::decode(packet.data(), self.protocol_version)?;
+                    self.respond_to_ping(ping).await?;
+                }
+                configuration::Clientbound::RegistryData => {
+                    // only the dimension types are needed, and not enough to give up over
+                    match RegistryData::decode(packet.data(), self.protocol_version) {
+                        Ok(registry) if !registry.dimension_types.is_empty() => {
+                            self.dimension_types = registry.dimension_types;
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::warn!("Could not decode registry data: {e}"),
+                    }
+                }
+                configuration::Clientbound::FeatureFlags => {
+                    self.feature_flags = FeatureFlags::decode(packet.data())?.flags;
+                }
+                configuration::Clientbound::UpdateTags => {
+                    self.update_tags(UpdateTags::decode(packet.data())?);
+                }
+                configuration::Clientbound::SelectKnownPacks => {
+                    self.write_packet(KnownPacks::default()).await?;
+                }
+            }
+        }
+    }
+
+    /// Writes a single packet to the stream, flushing it along with any buffered packets.
+    ///
+    /// Waits first if packets are being sent faster than the rate set with
+    /// [`set_send_rate`](Self::set_send_rate).
+    pub async fn write_packet<P>(&mut self, packet: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        self.write_packet_unpaced(packet).await
+    }
+
+    /// Writes a single packet as [`write_packet`](Self::write_packet) does, but straight away
+    /// however fast packets are being sent, for responses the server times out waiting for.
+    async fn write_packet_unpaced<P>(&mut self, packet: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        self.write_packet_buffered(packet)?;
+
+        self.flush().await
+    }
+
+    /// Buffers a single packet, which is not written to the stream until the next
+    /// [`flush`](Self::flush) (or [`write_packet`](Self::write_packet)), for batching packets
+    /// into a single write.
+    pub fn write_packet_buffered<P>(&mut self, packet: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        let packet = packet.into();
+        if let Some(capture) = &mut self.capture {
+            capture.write(Direction::Serverbound, self.state, &packet)?;
+        }
+
+        let bytes = encode_frame(packet, self.compression_threshold)?;
+        self.write_buffer.extend_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    /// Writes every buffered packet to the stream, and flushes the stream.
+    ///
+    /// The buffer is emptied even if writing fails (or is cancelled) part-way, as writing what
+    /// was already sent a second time would corrupt the stream.
+    pub async fn flush(&mut self) -> Result<(), ConnectionError> {
+        let mut buffer = std::mem::take(&mut self.write_buffer);
+        let written = self.stream.write_all(&buffer).await;
+        // keep the allocation for the packets buffered next
+        buffer.clear();
+        self.write_buffer = buffer;
+
+        written?;
+        self.stream.flush().await?;
+
+        Ok(())
+    }
+
+    /// Reads a single packet from the stream, waiting until the full packet has arrived.
+    ///
+    /// Packets with a handler registered by [`on_packet`](Self::on_packet) are given to it, and
+    /// reading continues with the next packet.
+    pub async fn read_packet(&mut self) -> Result<Packet, ConnectionError> {
+        loop {
+            let packet = self.read_frame().await?;
+
+            match self.handlers.get_mut(&(self.state, packet.id())) {
+                Some(handler) => handler(packet.data()),
+                None => return Ok(packet),
+            }
+        }
+    }
+
+    /// Reads the next packet from the stream, recording it if the connection is captured.
+    async fn read_frame(&mut self) -> Result<Packet, ConnectionError> {
+        let packet = read_frame(&mut self.stream, self.compression_threshold).await?;
+
+        if let Some(capture) = &mut self.capture {
+            capture.write(Direction::Clientbound, self.state, &packet)?;
+        }
+
+        Ok(packet)
+    }
+}
+
+/// Frames `packet` to be written to the stream, compressing it if compression is enabled with
+/// the given `compression_threshold`.
+fn encode_frame(
+    packet: Packet,
+    compression_threshold: Option<usize>,
+) -> Result<Vec<u8>, ConnectionError> {
+    Ok(match compression_threshold {
+        Some(threshold) => {
+            let uncompressed = [VarInt::from(packet.id()).as_slice(), packet.data()].concat();
+            let frame = compression::compress(&uncompressed, threshold)?;
+            let frame_length = i32::try_from(frame.len())?;
+
+            [VarInt::from(frame_length).as_slice(), &frame].concat()
+        }
+        None => Vec::try_from(packet)?,
+    })
+}
+
+/// Reads the next framed packet from `stream`, decompressing it if compression is enabled.
+async fn read_frame<R>(
+    stream: &mut R,
+    compression_threshold: Option<usize>,
+) -> Result<Packet, ConnectionError>
+where
+    R: AsyncRead + Unpin,
+{
+    let length = i32::from(VarInt::read_async(stream).await?);
+    if !(1..=MAX_PACKET_LENGTH).contains(&length) {
+        return Err(ConnectionError::InvalidPacketLength(length));
+    }
+
+    // the length was checked to be positive above
+    #[allow(clippy::cast_sign_loss)]
+    let mut frame = vec![0; length as usize];
+    stream.read_exact(&mut frame).await?;
+
+    let body = match compression_threshold {
+        Some(_) => compression::decompress(&frame)?,
+        None => frame,
+    };
+
+    let mut reader = body.as_slice();
+    let id = VarInt::read(&mut reader)?;
+
+    Ok(Packet::from_parts(i32::from(id), reader.to_vec()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::Connection;
+    use crate::protocol::{
+        encoding::VarInt,
+        packets::{
+            configuration::{
+                test::{feature_flags_data, update_tags_data},
+                ClientInformation,
+            },
+            login, Handshake,
+        },
+        proxy::ProxyProtocol,
+        test_util, ConnectionError, Packet, State,
+    };
+
+    /// Builds the body of a Login Success packet for the player "Steve", for protocols from 759.
+    fn login_success() -> Packet {
+        let data = [[0; 16].as_slice(), b"\x05Steve", &[0]].concat();
+        Packet::new(login::LOGIN_SUCCESS_ID, data)
+    }
+
+    /// Reads every packet written to `server` until the client side is closed.
+    async fn read_all_packets(server: tokio::io::DuplexStream, protocol_version: i32) -> Vec<i32> {
+        let mut server = Connection::new(server, protocol_version);
+
+        let mut ids = Vec::new();
+        while let Ok(packet) = server.read_packet().await {
+            ids.push(packet.id());
+        }
+
+        ids
+    }
+
+    #[test]
+    fn reports_protocol_version() {
+        let (stream, _) = tokio::io::duplex(64);
+        let connection = Connection::new(stream, 760);
+
+        assert_eq!(connection.protocol_version(), 760);
+    }
+
+    #[tokio::test]
+    async fn handshake_uses_protocol_version() {
+        let (stream, mut server) = tokio::io::duplex(64);
+        let mut connection = Connection::new(stream, 760);
+
+        connection
+            .handshake("localhost".to_string(), 25565, true)
+            .await
+            .unwrap();
+        assert_eq!(connection.state(), State::Status);
+        drop(connection);
+
+        let mut written = Vec::new();
+        server.read_to_end(&mut written).await.unwrap();
+
+        let expected = Handshake::new(760, "localhost".to_string(), 25565, true).unwrap();
+        assert_eq!(written, expected.into_bytes().unwrap());
+    }
+
+    #[tokio::test]
+    async fn prepends_proxy_header() {
+        let (stream, mut server) = tokio::io::duplex(128);
+        let mut connection = Connection::new(stream, 764);
+
+        connection
+            .send_proxy_header(
+                ProxyProtocol::V1,
+                "192.168.0.1:56324".parse().unwrap(),
+                "10.0.0.2:25565".parse().unwrap(),
+            )
+            .await
+            .unwrap();
+        connection
+            .handshake("10.0.0.2".to_string(), 25565, false)
+            .await
+            .unwrap();
+        drop(connection);
+
+        let mut written = Vec::new();
+        server.read_to_end(&mut written).await.unwrap();
+
+        let header = b"PROXY TCP4 192.168.0.1 10.0.0.2 56324 25565\r\n";
+        let handshake = Handshake::new(764, "10.0.0.2".to_string(), 25565, false).unwrap();
+        assert_eq!(
+            written,
+            [header.as_slice(), &handshake.into_bytes().unwrap()].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn calls_registered_handlers() {
+        let (mut client, mut server) = test_util::play();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        client.on_packet(0x7e, State::Play, move |data| {
+            sender.send(data.to_vec()).unwrap();
+        });
+
+        // the same id in another state is not handled
+        client.on_packet(0x7f, State::Configuration, |_| panic!("wrong state"));
+
+        server.send(Packet::new(0x7e, vec![1, 2])).await;
+        server.send(Packet::new(0x7e, vec![3])).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let packet = client.read_packet().await.unwrap();
+        assert_eq!(packet.id(), 0x7f);
+        assert_eq!(receiver.try_recv().unwrap(), [1, 2]);
+        assert_eq!(receiver.try_recv().unwrap(), [3]);
+    }
+
+    #[tokio::test]
+    async fn round_trips_packets() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = Connection::new(client, 760);
+        let mut server = Connection::new(server, 760);
+
+        client
+            .write_packet(Packet::new(0x05, vec![1, 2, 3]))
+            .await
+            .unwrap();
+
+        let packet = server.read_packet().await.unwrap();
+        assert_eq!(packet.id(), 0x05);
+        assert_eq!(packet.data(), [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn buffers_writes_until_flushed() {
+        use futures::FutureExt;
+
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = Connection::new(client, 760);
+        let mut server = Connection::new(server, 760);
+
+        client
+            .write_packet_buffered(Packet::new(0x05, vec![1, 2, 3]))
+            .unwrap();
+        assert!(server.read_packet().now_or_never().is_none());
+
+        client.flush().await.unwrap();
+        let packet = server.read_packet().await.unwrap();
+        assert_eq!(packet.data(), [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn empties_write_buffer_when_flush_fails() {
+        let (client, server) = tokio::io::duplex(16);
+        let mut client = Connection::new(client, 760);
+        client
+            .write_packet_buffered(Packet::new(0x05, vec![0; 64]))
+            .unwrap();
+
+        // the server leaves once part of the packet has been written
+        let leave = async move {
+            tokio::task::yield_now().await;
+            drop(server);
+        };
+        let (flushed, ()) = tokio::join!(client.flush(), leave);
+
+        assert!(flushed.is_err());
+        assert!(client.write_buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_length() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut client = Connection::new(client, 760);
+
+        // a declared length of zero can not hold a packet id
+        server.write_all(&[0]).await.unwrap();
+
+        assert!(matches!(
+            client.read_packet().await,
+            Err(ConnectionError::InvalidPacketLength(0))
+        ));
+    }
+
+    #[tokio::test]
+    async fn acknowledges_login_on_configuration_versions() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Connection::new(client, 764);
+        let mut server_writer = Connection::new(server, 764);
+        server_writer.write_packet(login_success()).await.unwrap();
+
+        let success = client.login("Steve".to_string(), None).await.unwrap();
+        assert_eq!(success.username, "Steve");
+        assert_eq!(client.state(), State::Configuration);
+        drop(client);
+
+        // login start, then login acknowledged
+        let ids = read_all_packets(server_writer.into_inner(), 764).await;
+        assert_eq!(ids, [0x00, 0x03]);
+    }
+
+    #[tokio::test]
+    async fn does_not_acknowledge_login_on_older_versions() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Connection::new(client, 763);
+        let mut server_writer = Connection::new(server, 763);
+        server_writer.write_packet(login_success()).await.unwrap();
+
+        client.login("Steve".to_string(), None).await.unwrap();
+        assert_eq!(client.state(), State::Play);
+        drop(client);
+
+        // only login start
+        let ids = read_all_packets(server_writer.into_inner(), 763).await;
+        assert_eq!(ids, [0x00]);
+    }
+
+    #[tokio::test]
+    async fn applies_compression_during_login() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Connection::new(client, 764);
+        let mut server_writer = Connection::new(server, 764);
+        server_writer
+            .write_packet(Packet::new(
+                login::SET_COMPRESSION_ID,
+                VarInt::from(0).as_slice().to_vec(),
+            ))
+            .await
+            .unwrap();
+        // the server is now compressing too
+        server_writer.compression_threshold = Some(0);
+        server_writer.write_packet(login_success()).await.unwrap();
+
+        client.login("Steve".to_string(), None).await.unwrap();
+        assert_eq!(client.compression_threshold, Some(0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_sent_packets() {
+        let (mut client, mut server) = test_util::play();
+        client.set_send_rate(Some(10));
+
+        let start = tokio::time::Instant::now();
+        for _ in 0..30 {
+            client
+                .write_packet(Packet::new(0x7f, vec![]))
+                .await
+                .unwrap();
+        }
+        // the first 10 are sent at once, then the other 20 at 10 a second
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+
+        client.set_send_rate(None);
+        client
+            .write_packet(Packet::new(0x7f, vec![]))
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+
+        for _ in 0..31 {
+            server.expect(0x7f).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_compression_into_configuration() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Connection::new(client, 764);
+        let mut server = Connection::new(server, 764);
+
+        let script = async {
+            server.read_packet().await.unwrap();
+            server
+                .write_packet(Packet::new(
+                    login::SET_COMPRESSION_ID,
+                    VarInt::from(0).as_slice().to_vec(),
+                ))
+                .await
+                .unwrap();
+            // every packet from here on is compressed, in both directions
+            server.compression_threshold = Some(0);
+            server.write_packet(login_success()).await.unwrap();
+            server
+                .write_packet(Packet::new(0x03, 7i64.to_be_bytes().to_vec()))
+                .await
+                .unwrap();
+            server
+                .write_packet(Packet::new(0x02, vec![]))
+                .await
+                .unwrap();
+
+            // login acknowledged, client information, the keep alive, then acknowledge finish
+            let mut ids = Vec::new();
+            for _ in 0..4 {
+                let packet = server.read_packet().await.unwrap();
+                if packet.id() == 0x03 && !packet.data().is_empty() {
+                    assert_eq!(packet.data(), 7i64.to_be_bytes());
+                }
+                ids.push(packet.id());
+            }
+            assert_eq!(ids, [0x03, 0x00, 0x03, 0x02]);
+        };
+        let run = async {
+            client.login("Steve".to_string(), None).await.unwrap();
+            client
+                .configure(ClientInformation::default())
+                .await
+                .unwrap();
+        };
+        tokio::join!(script, run);
+
+        assert_eq!(client.state(), State::Play);
+        assert_eq!(client.compression_threshold, Some(0));
+    }
+
+    #[tokio::test]
+    async fn moves_from_configuration_to_play() {
+        let (mut client, mut server) = test_util::connect(764);
+
+        let script = async {
+            server.accept_login().await;
+            // a keep alive must be answered before the configuration finishes
+            server
+                .send(Packet::new(0x03, 7i64.to_be_bytes().to_vec()))
+                .await;
+            // a registry data packet which cannot be decoded is skipped
+            server.send(Packet::new(0x05, vec![0xff; 8])).await;
+            server
+                .send(Packet::new(
+                    0x07,
+                    feature_flags_data(&["minecraft:vanilla", "minecraft:update_1_21"]),
+                ))
+                .await;
+            server
+                .send(Packet::new(0x08, update_tags_data(&[1, 2, 3])))
+                .await;
+            server.send(Packet::new(0x02, vec![])).await;
+
+            server.expect(0x00).await;
+            let keep_alive = server.expect(0x03).await;
+            assert_eq!(keep_alive.data(), 7i64.to_be_bytes());
+            server.expect(0x02).await;
+        };
+        let run = async {
+            client.login("Steve".to_string(), None).await.unwrap();
+            assert_eq!(client.state(), State::Configuration);
+            client
+                .configure(ClientInformation::default())
+                .await
+                .unwrap();
+        };
+        tokio::join!(script, run);
+
+        assert_eq!(client.state(), State::Play);
+        assert_eq!(
+            client.feature_flags(),
+            ["minecraft:vanilla", "minecraft:update_1_21"]
+        );
+        assert_eq!(
+            client.tag("minecraft:block", "minecraft:mineable/pickaxe"),
+            Some([1, 2, 3].as_slice())
+        );
+        assert_eq!(client.tag("minecraft:block", "minecraft:logs"), None);
+    }
+
+    #[tokio::test]
+    async fn reports_login_disconnect() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Connection::new(client, 764);
+        let mut server_writer = Connection::new(server, 764);
+        server_writer
+            .write_packet(Packet::new(
+                login::DISCONNECT_ID,
+                b"\x0e{\"text\":\"bye\"}".to_vec(),
+            ))
+            .await
+            .unwrap();
+
+        let result = client.login("Steve".to_string(), None).await;
+        assert!(
+            matches!(result, Err(ConnectionError::Disconnected { reason_plain, .. }) if reason_plain == "bye")
+        );
+    }
+}
@@ -0,0 +1,2107 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+use super::{
+    Connection, RespawnState, MAX_BUNDLE_SIZE, MAX_HELD_BACK, MAX_LATE_RESPONSES,
+    MAX_UNACKNOWLEDGED_MESSAGES, MIN_RESPAWN_INTERVAL,
+};
+use crate::protocol::{
+    chat::{ChatComponent, NBT_COMPONENT_VERSION},
+    encoding::Decode,
+    packets::configuration::{DimensionType, UpdateTags},
+    packets::play::{
+        self,
+        game_event::{BEGIN_RAINING, CHANGE_GAME_MODE, END_RAINING},
+        AcknowledgeBlockChange, BlockUpdate, ChatCommand, ChatMessage, ChunkData, ClientCommand,
+        Clientbound, CombatDeath, CommandSuggestions, CommandSuggestionsRequest, Commands,
+        ConfirmTeleportation, GameEvent, Login, MessageAcknowledgment, OpenScreen, PlayerChat,
+        PlayerInfoRemove, PlayerInfoUpdate, PluginMessage, RemoveEntities, ResourcePack,
+        ResourcePackResponse, Respawn, ServerData, SetActionBarText, SetCenterChunk,
+        SetContainerSlot, SetDefaultSpawnPosition, SetExperience, SetHealth, SetPassengers,
+        SetPlayerRotation, SetSubtitleText, SetTitleText, SpawnEntity, Statistics,
+        SynchronizePlayerPosition, SystemChat, UnloadChunk, UpdateEntityPosition,
+        UpdateEntityPositionAndRotation, UpdateEntityRotation,
+    },
+    ConnectionError, Entity, Event, Packet,
+};
+use crate::util;
+
+/// How far the player is turned to keep them from being idle, in degrees.
+const ANTI_AFK_TURN: f32 = 1.0;
+
+/// The most bytes of a packet which could not be decoded dumped in the warning about it, as
+/// packets can be megabytes long. The whole packet is dumped at the trace level.
+const MAX_WARNED_DUMP_LENGTH: usize = 256;
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Sends `text` to the chat, unsigned, running it as a command if it starts with `/`.
+    ///
+    /// Servers which enforce secure chat kick players who send unsigned messages, so messages to
+    /// them fail with [`ConnectionError::SecureChatEnforced`] unless
+    /// [`set_insecure_chat`](Self::set_insecure_chat) allows them. Commands are still sent, as
+    /// only the arguments of some commands (e.g., `/msg`) need signing.
+    pub async fn send_chat(&mut self, text: &str) -> Result<(), ConnectionError> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        if let Some(command) = text.strip_prefix('/') {
+            let command =
+                ChatCommand::new(command.to_string(), timestamp, self.protocol_version())?;
+            return self.write_packet(command).await;
+        }
+
+        if self.world.enforces_secure_chat && !self.insecure_chat {
+            return Err(ConnectionError::SecureChatEnforced);
+        }
+
+        let message = ChatMessage::new(text.to_string(), timestamp, self.protocol_version())?;
+        self.write_packet(message).await
+    }
+
+    /// Asks the server how the command `text` (including its leading `/`) could be completed,
+    /// returning the transaction id of the request.
+    ///
+    /// The answer is returned from [`next_event`](Self::next_event) as [`Event::Suggestions`],
+    /// unless another request is made first, in which case only the answer to the latest
+    /// request is.
+    pub async fn request_suggestions(&mut self, text: &str) -> Result<i32, ConnectionError> {
+        self.suggestions_transaction = self.suggestions_transaction.wrapping_add(1);
+        let transaction_id = self.suggestions_transaction;
+        self.write_packet(CommandSuggestionsRequest::new(
+            transaction_id,
+            text.to_string(),
+        )?)
+        .await?;
+
+        Ok(transaction_id)
+    }
+
+    /// Sends the raw `data` on the plugin `channel` (e.g., `minecraft:brand`), for plugins and
+    /// mods which listen on it.
+    pub async fn send_plugin_message(
+        &mut self,
+        channel: &str,
+        data: &[u8],
+    ) -> Result<(), ConnectionError> {
+        self.write_packet(PluginMessage::new(
+            channel,
+            data.to_vec(),
+            self.protocol_version(),
+        )?)
+        .await
+    }
+
+    /// Asks the server for the player's statistics, which are returned from
+    /// [`next_event`](Self::next_event) as [`Event::Statistics`].
+    pub async fn request_statistics(&mut self) -> Result<(), ConnectionError> {
+        self.write_packet(ClientCommand::REQUEST_STATISTICS).await
+    }
+
+    /// Asks the server to respawn the player, after they died.
+    pub async fn respawn(&mut self) -> Result<(), ConnectionError> {
+        self.write_packet(ClientCommand::PERFORM_RESPAWN).await
+    }
+
+    /// Respawns the player if automatic respawning is enabled, or schedules the respawn for
+    /// later if they were already respawned automatically within the last
+    /// [`MIN_RESPAWN_INTERVAL`].
+    ///
+    /// Both the player's health dropping to 0 and the death screen mean the player died, so only
+    /// the first of them since the player last had health respawns them.
+    async fn auto_respawn(&mut self) -> Result<(), ConnectionError> {
+        if self.respawn_state != RespawnState::Alive || !self.auto_respawn {
+            return Ok(());
+        }
+
+        let now = tokio::time::Instant::now();
+        match self.last_respawn {
+            // the server may be killing players as they spawn, so don't respawn them in a loop
+            Some(last) if now - last < MIN_RESPAWN_INTERVAL => {
+                let respawn_at = last + MIN_RESPAWN_INTERVAL;
+                tracing::warn!(
+                    "Died again right after respawning, so respawning in {:.1}s",
+                    (respawn_at - now).as_secs_f32()
+                );
+                self.respawn_state = RespawnState::Scheduled(respawn_at);
+            }
+            _ => {
+                self.respawn().await?;
+                self.last_respawn = Some(now);
+                self.respawn_state = RespawnState::Respawned;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Respawns the player if a respawn was scheduled by [`auto_respawn`](Self::auto_respawn)
+    /// and is now due.
+    async fn respawn_when_scheduled(&mut self) -> Result<(), ConnectionError> {
+        let now = tokio::time::Instant::now();
+        if matches!(self.respawn_state, RespawnState::Scheduled(respawn_at) if now >= respawn_at) {
+            self.respawn().await?;
+            self.last_respawn = Some(now);
+            self.respawn_state = RespawnState::Respawned;
+        }
+
+        Ok(())
+    }
+
+    /// Records the player's new `health`, returning [`Event::LowHealth`] if it has just dropped
+    /// below the threshold.
+    async fn update_health(&mut self, health: SetHealth) -> Result<Option<Event>, ConnectionError> {
+        let previous = self.world.health.replace(health);
+        if health.health <= 0.0 {
+            self.auto_respawn().await?;
+            return Ok(None);
+        }
+        self.respawn_state = RespawnState::Alive;
+
+        let threshold = self.low_health_threshold;
+        let was_low = previous.is_some_and(|previous| previous.health < threshold);
+        Ok((health.health < threshold && !was_low).then_some(Event::LowHealth(health.health)))
+    }
+
+    /// Records the index of a player's chat message, acknowledging the signed messages received
+    /// once there are more than [`MAX_UNACKNOWLEDGED_MESSAGES`] of them, as the vanilla client
+    /// does.
+    async fn track_chat(&mut self, chat: &PlayerChat) -> Result<(), ConnectionError> {
+        let last = self.chat_indices.insert(chat.sender, chat.index);
+        if let Some(last) = last.filter(|last| chat.index != last.wrapping_add(1)) {
+            tracing::warn!(
+                "Chat message {} of player {:032x} did not follow message {last}",
+                chat.index,
+                chat.sender
+            );
+        }
+
+        if chat.signature.is_some() {
+            self.unacknowledged_messages += 1;
+        }
+        if self.unacknowledged_messages > MAX_UNACKNOWLEDGED_MESSAGES {
+            let message_count = std::mem::take(&mut self.unacknowledged_messages);
+            self.write_packet(MessageAcknowledgment { message_count })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Records a block change of the given `kind`, returning the event it is surfaced as.
+    fn block_event(&mut self, kind: Clientbound, data: &[u8]) -> std::io::Result<Event> {
+        if kind == Clientbound::AcknowledgeBlockChange {
+            let sequence = AcknowledgeBlockChange::decode(data)?.sequence;
+            self.world.acknowledged_sequence = Some(sequence);
+            return Ok(Event::BlockChangeAcknowledged(sequence));
+        }
+
+        let update = BlockUpdate::decode(data)?;
+        self.world
+            .blocks
+            .insert(update.location, update.block_state);
+        Ok(Event::BlockChanged(update))
+    }
+
+    /// Records the change of a game `event`, returning the event it is surfaced as.
+    ///
+    /// A change to a game mode which does not exist is logged and skipped, rather than ending the
+    /// connection over a packet nothing else depends on.
+    fn game_event(&mut self, event: GameEvent) -> Option<Event> {
+        Some(match event.event {
+            BEGIN_RAINING => {
+                self.world.raining = true;
+                Event::RainStarted
+            }
+            END_RAINING => {
+                self.world.raining = false;
+                Event::RainStopped
+            }
+            CHANGE_GAME_MODE => {
+                let game_mode = match event.game_mode() {
+                    Ok(game_mode) => game_mode,
+                    Err(e) => {
+                        tracing::warn!("Skipping Game Event: {e}");
+                        return None;
+                    }
+                };
+                self.world.game_mode = Some(game_mode);
+                Event::GameModeChanged(game_mode)
+            }
+            _ => Event::GameEvent(event),
+        })
+    }
+
+    /// Turns the player slightly, back and forth, if the anti-AFK interval has passed since they
+    /// were last turned or teleported.
+    ///
+    /// The server rejects movement until a teleport is confirmed, so the player is not turned
+    /// while `packet` (the packet about to be handled) is a teleport, and any teleport left
+    /// unconfirmed is confirmed first.
+    async fn anti_afk(&mut self, packet: &Packet) -> Result<(), ConnectionError> {
+        if self.kind_of(packet) == Some(Clientbound::SynchronizePlayerPosition) {
+            return Ok(());
+        }
+        self.confirm_teleport().await?;
+
+        // the vehicle moves a mounted player, so they are not turned until they get off
+        if self.world.is_mounted() {
+            return Ok(());
+        }
+
+        let (Some(interval), Some(next), Some(position)) = (
+            self.anti_afk_interval,
+            self.next_anti_afk,
+            self.world.position.as_mut(),
+        ) else {
+            return Ok(());
+        };
+
+        let now = tokio::time::Instant::now();
+        if now < next {
+            return Ok(());
+        }
+
+        self.anti_afk_turn = if self.anti_afk_turn > 0.0 {
+            -ANTI_AFK_TURN
+        } else {
+            ANTI_AFK_TURN
+        };
+        position.yaw += self.anti_afk_turn;
+        let rotation = SetPlayerRotation {
+            yaw: position.yaw,
+            pitch: position.pitch,
+            on_ground: true,
+            protocol_version: self.protocol_version(),
+        };
+        self.next_anti_afk = Some(now + interval);
+
+        self.write_packet(rotation).await
+    }
+
+    /// Teleports the player as the server asked in `sync`, confirming the teleport.
+    async fn teleport(&mut self, sync: &SynchronizePlayerPosition) -> Result<(), ConnectionError> {
+        self.world.teleport(sync);
+        self.unconfirmed_teleport = Some(sync.teleport_id);
+
+        // the next turn is put off after every teleport, so as not to undo it
+        self.anti_afk_turn = 0.0;
+        self.next_anti_afk = self
+            .anti_afk_interval
+            .map(|interval| tokio::time::Instant::now() + interval);
+
+        self.confirm_teleport().await
+    }
+
+    /// Confirms the latest teleport, unless it already has been (sending the confirmation may
+    /// have been cancelled part-way, e.g., by a timeout around [`next_event`](Self::next_event)).
+    async fn confirm_teleport(&mut self) -> Result<(), ConnectionError> {
+        let Some(teleport_id) = self.unconfirmed_teleport else {
+            return Ok(());
+        };
+
+        self.write_packet(ConfirmTeleportation { teleport_id })
+            .await?;
+        self.unconfirmed_teleport = None;
+
+        Ok(())
+    }
+
+    /// Declines the resource pack pushed in `data`, as resource packs are unsupported.
+    ///
+    /// Servers which require a resource pack keep sending it, so this gives up rather than
+    /// decline forever once the limit of forced packs has been declined. Declining an optional
+    /// pack costs nothing, so those are not counted.
+    async fn decline_resource_pack(&mut self, data: &[u8]) -> Result<(), ConnectionError> {
+        let resource_pack = ResourcePack::decode(data, self.protocol_version())?;
+        if resource_pack.forced && self.forced_packs_declined >= self.resource_pack_limit {
+            return Err(ConnectionError::ResourcePackRequired(resource_pack.url));
+        }
+
+        let response = ResourcePackResponse::declined(&resource_pack, self.protocol_version());
+        self.write_packet(response).await?;
+        if resource_pack.forced {
+            self.forced_packs_declined += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Updates what is known about the world from a packet of the given `kind`, for the packets
+    /// which need nothing more than that.
+    fn update_world(&mut self, kind: Clientbound, data: &[u8]) -> std::io::Result<()> {
+        match kind {
+            Clientbound::UpdateEntityPosition => {
+                let update = UpdateEntityPosition::decode(data)?;
+                let offset = play::delta_to_blocks(update.delta);
+                self.world
+                    .move_entity(update.entity_id, offset, None, update.on_ground);
+            }
+            Clientbound::UpdateEntityPositionAndRotation => {
+                let update = UpdateEntityPositionAndRotation::decode(data)?;
+                let offset = play::delta_to_blocks(update.delta);
+                let rotation = Some((update.yaw, update.pitch));
+                self.world
+                    .move_entity(update.entity_id, offset, rotation, update.on_ground);
+            }
+            Clientbound::UpdateEntityRotation => {
+                let update = UpdateEntityRotation::decode(data)?;
+                let rotation = Some((update.yaw, update.pitch));
+                self.world
+                    .move_entity(update.entity_id, [0.0; 3], rotation, update.on_ground);
+            }
+            Clientbound::Commands => {
+                self.world.commands = Commands::decode(data, self.protocol_version())?;
+            }
+            Clientbound::SetCenterChunk => {
+                let center = SetCenterChunk::decode(data)?;
+                self.world
+                    .set_center_chunk((center.chunk_x, center.chunk_z));
+            }
+            Clientbound::PlayerInfoUpdate => {
+                let update = PlayerInfoUpdate::decode(data, self.protocol_version())?;
+                for player in update.players {
+                    if let Some(name) = player.name {
+                        self.world.players.insert(player.uuid, name);
+                    }
+                }
+            }
+            Clientbound::PlayerInfoRemove => {
+                let remove = PlayerInfoRemove::decode(data)?;
+                for uuid in remove.uuids {
+                    self.world.players.remove(&uuid);
+                }
+            }
+            Clientbound::ServerData => {
+                let server_data = ServerData::decode(data, self.protocol_version())?;
+                self.world.enforces_secure_chat = server_data.enforces_secure_chat;
+                self.world.server_data = Some(server_data);
+            }
+            Clientbound::SetExperience => {
+                self.world.experience = Some(SetExperience::decode(data)?);
+            }
+            Clientbound::SetDefaultSpawnPosition => {
+                self.world.spawn = Some(SetDefaultSpawnPosition::decode(data)?.location);
+            }
+            Clientbound::Login => {
+                let login = Login::decode(data)?;
+                self.world.player_entity_id = Some(login.entity_id);
+                self.world.view_distance = Some(login.view_distance);
+                self.world.dimension = self.dimension_type(&login.dimension_type);
+                self.world.dimension_name = Some(login.dimension_name);
+            }
+            Clientbound::UpdateTags => self.update_tags(UpdateTags::decode(data)?),
+            Clientbound::Respawn => {
+                let respawn = Respawn::decode(data)?;
+                let dimension = self.dimension_type(&respawn.dimension_type);
+                self.world.respawn(&respawn, dimension);
+            }
+            Clientbound::SetPassengers => {
+                self.world.set_passengers(&SetPassengers::decode(data)?);
+            }
+            Clientbound::RemoveEntities => {
+                self.world
+                    .remove_entities(&RemoveEntities::decode(data)?.entity_ids);
+            }
+            Clientbound::ChunkData => {
+                let chunk = ChunkData::decode(data)?;
+                self.world.chunks.insert((chunk.chunk_x, chunk.chunk_z));
+            }
+            Clientbound::UnloadChunk => {
+                let unload = UnloadChunk::decode(data)?;
+                self.world.unload_chunk((unload.chunk_x, unload.chunk_z));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Finds the dimension type named `name` among those the server sent during configuration.
+    fn dimension_type(&self, name: &str) -> Option<DimensionType> {
+        self.dimension_types
+            .iter()
+            .find(|(dimension_type, _)| dimension_type == name)
+            .map(|(_, dimension)| *dimension)
+    }
+
+    /// Reads the next packet during play, disconnecting if nothing but keep alives has been
+    /// received within the idle timeout, or giving up on the connection if nothing at all has
+    /// been received within the dead connection timeout.
+    async fn read_play_packet(&mut self) -> Result<Packet, ConnectionError> {
+        let now = tokio::time::Instant::now();
+        let idle = self.idle_timeout.map(|timeout| {
+            let last_activity = *self.last_activity.get_or_insert(now);
+            (
+                last_activity + timeout,
+                ConnectionError::IdleTimeout(timeout),
+            )
+        });
+        let dead = self.dead_connection_timeout.map(|timeout| {
+            let last_received = *self.last_received.get_or_insert(now);
+            (
+                last_received + timeout,
+                ConnectionError::ConnectionDead(timeout),
+            )
+        });
+        let Some((deadline, error)) = idle
+            .into_iter()
+            .chain(dead)
+            .min_by_key(|(deadline, _)| *deadline)
+        else {
+            return self.read_packet().await;
+        };
+
+        // the connection is given up on timing out, so a packet cut off part-way is not a concern
+        let Ok(packet) = tokio::time::timeout_at(deadline, self.read_packet()).await else {
+            // only an idle connection is closed cleanly, as a dead server would not notice
+            if matches!(error, ConnectionError::IdleTimeout(_)) {
+                self.stream.shutdown().await?;
+            }
+            return Err(error);
+        };
+
+        let packet = packet?;
+        let now = tokio::time::Instant::now();
+        self.last_received = Some(now);
+        if self.kind_of(&packet) != Some(Clientbound::KeepAlive) {
+            self.last_activity = Some(now);
+        }
+
+        Ok(packet)
+    }
+
+    /// Reads the next packet during play, taking those held back while awaiting a response first.
+    async fn read_next_packet(&mut self) -> Result<Packet, ConnectionError> {
+        if let Some(packet) = self.held_back.pop_front() {
+            return Ok(packet);
+        }
+
+        loop {
+            let packet = self.read_play_packet().await?;
+            if !self.is_late_response(&packet) {
+                return Ok(packet);
+            }
+        }
+    }
+
+    /// Finds if `packet` is the response to a request which timed out, which is then no longer
+    /// looked out for.
+    fn is_late_response(&mut self, packet: &Packet) -> bool {
+        let Some(index) = self
+            .late_responses
+            .iter_mut()
+            .position(|matches| matches(packet))
+        else {
+            return false;
+        };
+
+        self.late_responses.remove(index);
+        tracing::debug!("Dropped a response which arrived after its request timed out");
+        true
+    }
+
+    /// Sends `request` during play, then reads packets until one `matches` (e.g., the pong
+    /// answering a ping), returning it, or fails with [`ConnectionError::ResponseTimeout`] if none
+    /// did within `timeout`.
+    ///
+    /// Keep alives which arrive in between are answered straight away, and a Disconnect is
+    /// returned as [`ConnectionError::Disconnected`] straight away. Every other packet (e.g., a
+    /// chat message) is held back, to be handled by [`next_event`](Self::next_event) in the order
+    /// it arrived, up to [`MAX_HELD_BACK`] of them. A response which arrives after its request
+    /// timed out is dropped.
+    ///
+    /// Timing out may leave a packet part-way read, so the connection should not be used after a
+    /// [`ConnectionError::ResponseTimeout`].
+    pub async fn send_and_await<P, F>(
+        &mut self,
+        request: P,
+        timeout: Duration,
+        mut matches: F,
+    ) -> Result<Packet, ConnectionError>
+    where
+        P: Into<Packet>,
+        F: FnMut(&Packet) -> bool + Send + 'static,
+    {
+        self.write_packet(request).await?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let Ok(packet) = tokio::time::timeout_at(deadline, self.read_play_packet()).await
+            else {
+                if self.late_responses.len() == MAX_LATE_RESPONSES {
+                    self.late_responses.pop_front();
+                }
+                self.late_responses.push_back(Box::new(matches));
+                return Err(ConnectionError::ResponseTimeout(timeout));
+            };
+            let packet = packet?;
+
+            if self.is_late_response(&packet) {
+                continue;
+            }
+            if matches(&packet) {
+                return Ok(packet);
+            }
+            match self.kind_of(&packet) {
+                Some(Clientbound::KeepAlive) => {
+                    let keep_alive =
+                        play::KeepAlive::decode(packet.data(), self.protocol_version())?;
+                    self.respond(keep_alive).await?;
+                }
+                Some(Clientbound::Disconnect) => return Err(self.disconnect_error(packet.data())),
+                _ => {
+                    if self.held_back.len() == MAX_HELD_BACK {
+                        self.held_back.pop_front();
+                        tracing::warn!("Dropped a packet held back while awaiting a response");
+                    }
+                    self.held_back.push_back(packet);
+                }
+            }
+        }
+    }
+
+    /// Retrieves the next packet to handle during play.
+    ///
+    /// The packets of a bundle are all read before the first of them is returned, so none is
+    /// handled until the whole bundle has arrived. A bundle whose closing delimiter has not
+    /// arrived after [`MAX_BUNDLE_SIZE`] packets is taken to have ended there.
+    ///
+    /// The packets read so far are kept aside until the bundle closes, so a read cancelled
+    /// part-way through a bundle carries on with it when the next one starts.
+    async fn next_play_packet(&mut self) -> Result<Packet, ConnectionError> {
+        loop {
+            if let Some(packet) = self.bundle.pop_front() {
+                return Ok(packet);
+            }
+
+            if self.open_bundle.is_none() {
+                let packet = self.read_next_packet().await?;
+                if self.kind_of(&packet) != Some(Clientbound::BundleDelimiter) {
+                    return Ok(packet);
+                }
+                self.open_bundle = Some(Vec::new());
+            }
+
+            loop {
+                if self.open_bundle.as_ref().map_or(0, Vec::len) >= MAX_BUNDLE_SIZE {
+                    tracing::warn!("Bundle was not closed after {MAX_BUNDLE_SIZE} packets");
+                    break;
+                }
+
+                let packet = self.read_next_packet().await?;
+                if self.kind_of(&packet) == Some(Clientbound::BundleDelimiter) {
+                    break;
+                }
+                self.open_bundle.get_or_insert_with(Vec::new).push(packet);
+            }
+            self.bundle
+                .extend(self.open_bundle.take().unwrap_or_default());
+        }
+    }
+
+    /// Finds which of the packets we handle `packet` is, on the connection's protocol version.
+    fn kind_of(&self, packet: &Packet) -> Option<Clientbound> {
+        Clientbound::from_id(packet.id(), self.protocol_version())
+    }
+
+    /// Decodes the reason of a Disconnect packet into the error ending the connection, keeping the
+    /// JSON as it was sent before protocol 765.
+    fn disconnect_error(&self, mut data: &[u8]) -> ConnectionError {
+        let error = if self.protocol_version() >= NBT_COMPONENT_VERSION {
+            ChatComponent::decode(&mut data, self.protocol_version())
+                .map(|reason| ConnectionError::disconnected(&reason))
+        } else {
+            String::decode(&mut data).map(|reason| ConnectionError::disconnected_json(&reason))
+        };
+
+        error.unwrap_or_else(ConnectionError::from)
+    }
+
+    /// Reads and handles packets during play until one produces an [`Event`] for the caller.
+    ///
+    /// Packets which only need an automatic response (e.g., keep alives) are answered here and
+    /// never surface as events.
+    pub async fn next_event(&mut self) -> Result<Event, ConnectionError> {
+        loop {
+            let packet = self.next_play_packet().await?;
+            self.anti_afk(&packet).await?;
+            self.respawn_when_scheduled().await?;
+
+            let Some(kind) = self.kind_of(&packet) else {
+                return Ok(Event::Unhandled(packet));
+            };
+
+            match self.handle_packet(kind, &packet).await {
+                Ok(Some(event)) => return Ok(event),
+                Ok(None) => {}
+                Err(ConnectionError::Io(e))
+                    if self.lenient_decoding && !kind.is_critical() && is_decode_error(&e) =>
+                {
+                    warn_undecodable(kind, &e, packet.data());
+                }
+                Err(e) => return Err(e),
+            }
+
+            if !self.world.loaded && self.world.is_area_loaded() {
+                self.world.loaded = true;
+                return Ok(Event::WorldLoaded);
+            }
+        }
+    }
+
+    /// Handles a packet of the given `kind` received during play, returning the [`Event`] it
+    /// produced, if any.
+    async fn handle_packet(
+        &mut self,
+        kind: Clientbound,
+        packet: &Packet,
+    ) -> Result<Option<Event>, ConnectionError> {
+        match kind {
+            // bundles are taken apart by next_play_packet
+            Clientbound::BundleDelimiter => {}
+            Clientbound::SpawnEntity => {
+                let spawn = SpawnEntity::decode(packet.data())?;
+                self.world
+                    .entities
+                    .insert(spawn.entity_id, Entity::from(&spawn));
+
+                return Ok(Some(Event::EntitySpawned(spawn.entity_id)));
+            }
+            Clientbound::OpenScreen => {
+                let screen = OpenScreen::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::ScreenOpened(screen)));
+            }
+            Clientbound::SetContainerSlot => {
+                let set_slot = SetContainerSlot::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::SlotChanged(set_slot)));
+            }
+            Clientbound::CommandSuggestions => {
+                let suggestions =
+                    CommandSuggestions::decode(packet.data(), self.protocol_version())?;
+                // answers to earlier requests are out of date
+                if suggestions.transaction_id == self.suggestions_transaction {
+                    return Ok(Some(Event::Suggestions(suggestions)));
+                }
+            }
+            Clientbound::BlockUpdate | Clientbound::AcknowledgeBlockChange => {
+                return Ok(Some(self.block_event(kind, packet.data())?));
+            }
+            Clientbound::GameEvent => {
+                let event = GameEvent::decode(packet.data())?;
+                return Ok(self.game_event(event));
+            }
+            Clientbound::Statistics => {
+                return Ok(Some(Event::Statistics(Statistics::decode(packet.data())?)));
+            }
+            Clientbound::CombatDeath => {
+                let death = CombatDeath::decode(packet.data(), self.protocol_version())?;
+                self.auto_respawn().await?;
+                return Ok(Some(Event::Died(death.message)));
+            }
+            Clientbound::PlayerChat => {
+                let chat = PlayerChat::decode(packet.data())?;
+                self.track_chat(&chat).await?;
+                return Ok(Some(Event::PlayerChat(chat)));
+            }
+            Clientbound::SystemChat => {
+                let chat = SystemChat::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::from(chat)));
+            }
+            Clientbound::SetTitleText => {
+                let title = SetTitleText::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::Title(title.text)));
+            }
+            Clientbound::SetSubtitleText => {
+                let subtitle = SetSubtitleText::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::Subtitle(subtitle.text)));
+            }
+            Clientbound::SetActionBarText => {
+                let action_bar = SetActionBarText::decode(packet.data(), self.protocol_version())?;
+                return Ok(Some(Event::ActionBar(action_bar.text)));
+            }
+            Clientbound::Disconnect => return Err(self.disconnect_error(packet.data())),
+            Clientbound::KeepAlive => {
+                let keep_alive = play::KeepAlive::decode(packet.data(), self.protocol_version())?;
+                self.respond(keep_alive).await?;
+            }
+            Clientbound::Ping => {
+                let ping = play::Ping::decode(packet.data(), self.protocol_version())?;
+                self.respond_to_ping(ping).await?;
+            }
+            Clientbound::SynchronizePlayerPosition => {
+                let sync = SynchronizePlayerPosition::decode(packet.data())?;
+                self.teleport(&sync).await?;
+            }
+            Clientbound::ResourcePack => self.decline_resource_pack(packet.data()).await?,
+            Clientbound::UpdateEntityPosition
+            | Clientbound::UpdateEntityPositionAndRotation
+            | Clientbound::UpdateEntityRotation
+            | Clientbound::Commands
+            | Clientbound::SetCenterChunk
+            | Clientbound::PlayerInfoUpdate
+            | Clientbound::PlayerInfoRemove
+            | Clientbound::ServerData
+            | Clientbound::SetExperience
+            | Clientbound::SetDefaultSpawnPosition
+            | Clientbound::Login
+            | Clientbound::Respawn
+            | Clientbound::UpdateTags
+            | Clientbound::SetPassengers
+            | Clientbound::RemoveEntities
+            | Clientbound::ChunkData
+            | Clientbound::UnloadChunk => self.update_world(kind, packet.data())?,
+            Clientbound::SetHealth => {
+                let health = SetHealth::decode(packet.data())?;
+                return self.update_health(health).await;
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Warns that a packet of the given `kind` holding `data` is skipped, as decoding it failed with
+/// `e`, with a dump of (at most [`MAX_WARNED_DUMP_LENGTH`] bytes of) the packet.
+fn warn_undecodable(kind: Clientbound, e: &std::io::Error, data: &[u8]) {
+    let shown = &data[..data.len().min(MAX_WARNED_DUMP_LENGTH)];
+    let hidden = data.len() - shown.len();
+    if hidden == 0 {
+        tracing::warn!(
+            "Skipping a {kind:?} packet which could not be decoded: {e}\n{}",
+            util::hex_dump(shown)
+        );
+        return;
+    }
+
+    tracing::warn!(
+        "Skipping a {kind:?} packet which could not be decoded: {e}\n{}... and {hidden} more bytes",
+        util::hex_dump(shown)
+    );
+    tracing::trace!(
+        "The whole {kind:?} packet which could not be decoded:\n{}",
+        util::hex_dump(data)
+    );
+}
+
+/// Whether `e` is an error decoding a packet body, which only fails when its layout is not what
+/// was expected, rather than one of the stream (the body has already been read whole).
+fn is_decode_error(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{MAX_BUNDLE_SIZE, MAX_HELD_BACK, MIN_RESPAWN_INTERVAL};
+
+    use crate::logging;
+    use crate::protocol::{
+        capture::test::SharedBuffer,
+        chat::ChatComponent,
+        encoding::{Position, VarInt},
+        nbt::test::{compound, list, network, string},
+        packets::configuration::DimensionType,
+        packets::play::{
+            block::test::block_update_data,
+            chat::test::{player_chat_data, system_chat_data},
+            chunk::test::{chunk_data, unload_chunk_data},
+            commands::test::commands_data,
+            death::test::{combat_death_data, set_health_data},
+            entity::test::{
+                position_delta_data, remove_entities_data, set_passengers_data, spawn_entity_data,
+            },
+            game_event::{test::game_event_data, BEGIN_RAINING, CHANGE_GAME_MODE},
+            inventory::test::set_slot_data,
+            login::test::{login_data, respawn_data},
+            player_info::test::add_players_data,
+            position::test::{spawn_position_data, synchronize_position_data},
+            resource_pack::test::resource_pack_data,
+            server_data::test::server_data_data,
+            suggestions::test::suggestions_data,
+            title::test::text_data,
+            Clientbound, GameEvent, GameMode,
+        },
+        test_util, ConnectionError, Event, Packet, ResponseFaults,
+    };
+
+    #[tokio::test]
+    async fn tracks_spawned_entities() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::SpawnEntity.id(764),
+                spawn_entity_data(12, 1.5, -60.0, 3.25),
+            ))
+            .await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::EntitySpawned(12)));
+
+        let entity = &client.world().entities[&12];
+        assert_eq!(entity.uuid, 42);
+        assert!((entity.y + 60.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn removes_tracked_entities() {
+        let (mut client, mut server) = test_util::play();
+        for entity_id in [12, 13] {
+            server
+                .send(Packet::new(
+                    Clientbound::SpawnEntity.id(764),
+                    spawn_entity_data(entity_id, 0.0, 64.0, 0.0),
+                ))
+                .await;
+        }
+        server
+            .send(Packet::new(
+                Clientbound::RemoveEntities.id(764),
+                remove_entities_data(&[12]),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        client.next_event().await.unwrap();
+        client.next_event().await.unwrap();
+        assert_eq!(client.world().entities.len(), 2);
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        assert!(!client.world().entities.contains_key(&12));
+        assert!(client.world().entities.contains_key(&13));
+    }
+
+    #[tokio::test]
+    async fn moves_tracked_entities() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::SpawnEntity.id(764),
+                spawn_entity_data(12, 1.5, -60.0, 3.25),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::UpdateEntityPosition.id(764),
+                position_delta_data(12, [2048, 4096, -8192]),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        client.next_event().await.unwrap();
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+
+        let entity = &client.world().entities[&12];
+        assert!((entity.x - 2.0).abs() < f64::EPSILON);
+        assert!((entity.y + 59.0).abs() < f64::EPSILON);
+        assert!((entity.z - 1.25).abs() < f64::EPSILON);
+        assert!(entity.on_ground);
+    }
+
+    #[tokio::test]
+    async fn answers_keep_alives() {
+        for (protocol_version, response_id) in [(764, 0x14), (765, 0x15)] {
+            let (mut client, mut server) = test_util::play_with_version(protocol_version);
+            server
+                .send(Packet::new(
+                    Clientbound::KeepAlive.id(protocol_version),
+                    99i64.to_be_bytes().to_vec(),
+                ))
+                .await;
+            server.send(Packet::new(0x7f, vec![])).await;
+
+            // the keep alive is answered, and the next packet surfaces
+            let event = client.next_event().await.unwrap();
+            assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+
+            let response = server.expect(response_id).await;
+            assert_eq!(response.data(), 99i64.to_be_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_world_loaded() {
+        let (mut client, mut server) = test_util::play();
+        let center = [VarInt::from(10).as_slice(), VarInt::from(-4).as_slice()].concat();
+        server
+            .send(Packet::new(Clientbound::SetCenterChunk.id(764), center))
+            .await;
+
+        // a chunk outside the area, then the whole area but one chunk
+        let mut chunks = vec![(0, 0)];
+        for x in 8..=12 {
+            for z in -6..=-2 {
+                chunks.push((x, z));
+            }
+        }
+        let last = chunks.pop().unwrap();
+        for (x, z) in chunks {
+            server
+                .send(Packet::new(
+                    Clientbound::ChunkData.id(764),
+                    chunk_data(x, z),
+                ))
+                .await;
+        }
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        // the area is not yet loaded, so only the unhandled packet surfaces
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        assert!(!client.world().loaded);
+
+        server
+            .send(Packet::new(
+                Clientbound::ChunkData.id(764),
+                chunk_data(last.0, last.1),
+            ))
+            .await;
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::WorldLoaded));
+        assert_eq!(client.world().chunks.len(), 26);
+    }
+
+    #[tokio::test]
+    async fn tracks_tab_list() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::PlayerInfoUpdate.id(764),
+                add_players_data(&[(1, "Steve"), (2, "Alex")]),
+            ))
+            .await;
+        let remove = [[1].as_slice(), &1u128.to_be_bytes()].concat();
+        server
+            .send(Packet::new(Clientbound::PlayerInfoRemove.id(764), remove))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+
+        let players = &client.world().players;
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[&2], "Alex");
+    }
+
+    #[tokio::test]
+    async fn tracks_commands() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(Clientbound::Commands.id(764), commands_data()))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        assert_eq!(client.world().commands.commands(), ["help", "give"]);
+    }
+
+    #[tokio::test]
+    async fn reports_slot_changes() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::SetContainerSlot.id(764),
+                set_slot_data(2, 13, 0x2a, 1),
+            ))
+            .await;
+
+        let event = client.next_event().await.unwrap();
+        let Event::SlotChanged(set_slot) = event else {
+            panic!("expected a slot change, got {event:?}");
+        };
+        assert_eq!(set_slot.slot, 13);
+        assert_eq!(set_slot.item.unwrap().item_id, 0x2a);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_required_resource_packs() {
+        let (mut client, mut server) = test_util::play();
+        client.set_resource_pack_limit(2);
+        for _ in 0..3 {
+            server
+                .send(Packet::new(
+                    Clientbound::ResourcePack.id(764),
+                    resource_pack_data("https://example.com/pack.zip", true),
+                ))
+                .await;
+        }
+
+        let error = client.next_event().await.unwrap_err();
+        assert!(matches!(
+            error,
+            ConnectionError::ResourcePackRequired(url) if url == "https://example.com/pack.zip"
+        ));
+
+        // the packs within the limit were declined
+        for _ in 0..2 {
+            assert_eq!(server.expect(0x27).await.data(), [1]);
+        }
+    }
+
+    #[tokio::test]
+    async fn declines_optional_resource_packs_without_limit() {
+        let (mut client, mut server) = test_util::play();
+        client.set_resource_pack_limit(1);
+        for forced in [false, false, true, false] {
+            server
+                .send(Packet::new(
+                    Clientbound::ResourcePack.id(764),
+                    resource_pack_data("https://example.com/pack.zip", forced),
+                ))
+                .await;
+        }
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(_)), "got {event:?}");
+        for _ in 0..4 {
+            assert_eq!(server.expect(0x27).await.data(), [1]);
+        }
+    }
+
+    #[tokio::test]
+    async fn respawns_automatically() {
+        let (mut client, mut server) = test_util::play();
+        client.set_auto_respawn(true);
+        for packet in [
+            Packet::new(
+                Clientbound::CombatDeath.id(764),
+                combat_death_data(1, "Steve fell"),
+            ),
+            Packet::new(Clientbound::SetHealth.id(764), set_health_data(20.0)),
+            Packet::new(
+                Clientbound::CombatDeath.id(764),
+                combat_death_data(1, "Steve fell"),
+            ),
+        ] {
+            server.send(packet).await;
+        }
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Died(message) if message.to_plain() == "Steve fell"));
+        assert_eq!(server.expect(0x08).await.data(), [0]);
+
+        // dying again right away is not respawned, in case the server kills players on spawn
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Died(_)));
+
+        // so the next packet sent is not a respawn
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn respawns_later_after_dying_again_right_away() {
+        let (mut client, mut server) = test_util::play();
+        client.set_auto_respawn(true);
+        for packet in [
+            Packet::new(
+                Clientbound::CombatDeath.id(764),
+                combat_death_data(1, "Steve fell"),
+            ),
+            Packet::new(Clientbound::SetHealth.id(764), set_health_data(20.0)),
+            Packet::new(Clientbound::SetHealth.id(764), set_health_data(0.0)),
+            Packet::new(
+                Clientbound::CombatDeath.id(764),
+                combat_death_data(1, "Steve fell"),
+            ),
+        ] {
+            server.send(packet).await;
+        }
+
+        client.next_event().await.unwrap();
+        assert_eq!(server.expect(0x08).await.data(), [0]);
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Died(_)));
+
+        // the respawn waits for the interval, and is only sent once for both signs of the death
+        tokio::time::advance(MIN_RESPAWN_INTERVAL).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert_eq!(server.expect(0x08).await.data(), [0]);
+
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+    }
+
+    #[tokio::test]
+    async fn reports_low_health() {
+        let (mut client, mut server) = test_util::play();
+        client.set_low_health_threshold(6.0);
+        for health in [20.0, 8.0, 4.0, 3.0] {
+            server
+                .send(Packet::new(
+                    Clientbound::SetHealth.id(764),
+                    set_health_data(health),
+                ))
+                .await;
+        }
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        // only dropping below the threshold is reported, not dropping further
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::LowHealth(health) if (health - 4.0).abs() < f32::EPSILON));
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        assert_eq!(client.world().health.unwrap().food, 20);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn turns_idle_player() {
+        let (mut client, mut server) = test_util::play();
+        client.set_anti_afk_interval(Some(Duration::from_secs(30)));
+        server
+            .send(Packet::new(
+                Clientbound::SynchronizePlayerPosition.id(764),
+                synchronize_position_data(1.5, 64.0, -3.0, 7),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert_eq!(server.expect(0x00).await.data(), [7]);
+
+        // just before the interval, the player is left alone
+        tokio::time::advance(Duration::from_secs(29)).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        let rotation = server.expect(0x18).await;
+        assert_eq!(rotation.data()[..4], 91.0f32.to_be_bytes());
+        assert!((client.world().position.unwrap().yaw - 91.0).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn sends_chat_for_protocol() {
+        for (protocol_version, message_id) in [(764, 0x05), (766, 0x06)] {
+            let (mut client, mut server) = test_util::play_with_version(protocol_version);
+
+            client.send_chat("hello").await.unwrap();
+            server.expect(message_id).await;
+
+            client.send_chat("/help").await.unwrap();
+            let command = server.expect(0x04).await;
+            assert_eq!(command.data()[..5], *b"\x04help");
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_unsigned_chat_to_secure_servers() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::ServerData.id(764),
+                server_data_data(true),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+
+        let server_data = client.world().server_data.as_ref().unwrap();
+        assert_eq!(server_data.motd.to_plain(), "A Minecraft Server");
+        assert_eq!(
+            server_data.icon.as_deref(),
+            Some([0x89, b'P', b'N'].as_slice())
+        );
+
+        let error = client.send_chat("hello").await.unwrap_err();
+        assert!(matches!(error, ConnectionError::SecureChatEnforced));
+        client.send_chat("/help").await.unwrap();
+        server.expect(0x04).await;
+
+        client.set_insecure_chat(true);
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+    }
+
+    #[tokio::test]
+    async fn matches_suggestions_to_latest_request() {
+        let (mut client, mut server) = test_util::play();
+        assert_eq!(client.request_suggestions("/g").await.unwrap(), 1);
+        assert_eq!(client.request_suggestions("/ga").await.unwrap(), 2);
+        assert_eq!(server.expect(0x0a).await.data(), b"\x01\x02/g");
+
+        for transaction_id in [1, 2] {
+            server
+                .send(Packet::new(
+                    Clientbound::CommandSuggestions.id(764),
+                    suggestions_data(transaction_id),
+                ))
+                .await;
+        }
+
+        // the answer to the first request is skipped
+        let event = client.next_event().await.unwrap();
+        let Event::Suggestions(suggestions) = event else {
+            panic!("expected suggestions, got {event:?}");
+        };
+        assert_eq!(suggestions.transaction_id, 2);
+        assert_eq!(suggestions.suggestions.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn disconnects_when_idle() {
+        let (mut client, mut server) = test_util::play();
+        client.set_idle_timeout(Some(Duration::from_secs(45)));
+        for id in 0..3i64 {
+            server
+                .send(Packet::new(
+                    Clientbound::KeepAlive.id(764),
+                    id.to_be_bytes().to_vec(),
+                ))
+                .await;
+        }
+
+        // the keep alives are answered, but do not keep the connection from being idle
+        let error = client.next_event().await.unwrap_err();
+        assert!(matches!(error, ConnectionError::IdleTimeout(timeout) if timeout.as_secs() == 45));
+        for id in 0..3i64 {
+            assert_eq!(server.expect(0x14).await.data(), id.to_be_bytes());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn faults_keep_alive_responses() {
+        let (mut client, mut server) = test_util::play();
+        client.set_response_faults(Some(ResponseFaults {
+            delay: Duration::from_secs(2),
+            drop_probability: 0.0,
+            seed: Some(1),
+        }));
+        server
+            .send(Packet::new(
+                Clientbound::KeepAlive.id(764),
+                5i64.to_be_bytes().to_vec(),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let start = tokio::time::Instant::now();
+        client.next_event().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        assert_eq!(server.expect(0x14).await.data(), 5i64.to_be_bytes());
+
+        // every response is dropped
+        client.set_response_faults(Some(ResponseFaults {
+            delay: Duration::ZERO,
+            drop_probability: 1.0,
+            seed: Some(1),
+        }));
+        server
+            .send(Packet::new(
+                Clientbound::KeepAlive.id(764),
+                6i64.to_be_bytes().to_vec(),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        client.send_chat("/list").await.unwrap();
+        assert_eq!(server.receive().await.id(), 0x04);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn awaits_response_between_other_packets() {
+        let (mut client, mut server) = test_util::play();
+        let script = async {
+            assert_eq!(server.expect(0x1d).await.data(), 9i64.to_be_bytes());
+            server
+                .send(Packet::new(
+                    Clientbound::KeepAlive.id(764),
+                    1i64.to_be_bytes().to_vec(),
+                ))
+                .await;
+            server
+                .send(Packet::new(
+                    Clientbound::SystemChat.id(764),
+                    system_chat_data(r#"{"text":"Welcome!"}"#),
+                ))
+                .await;
+            // the pong response
+            server
+                .send(Packet::new(0x34, 9i64.to_be_bytes().to_vec()))
+                .await;
+            assert_eq!(server.expect(0x14).await.data(), 1i64.to_be_bytes());
+        };
+        let request = client.send_and_await(
+            Packet::new(0x1d, 9i64.to_be_bytes().to_vec()),
+            Duration::from_secs(5),
+            |packet| packet.id() == 0x34,
+        );
+
+        let (pong, ()) = tokio::join!(request, script);
+        assert_eq!(pong.unwrap().data(), 9i64.to_be_bytes());
+
+        // the chat message which arrived in between is still handled
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::SystemChat { .. }), "got {event:?}");
+
+        let error = client
+            .send_and_await(Packet::new(0x1d, vec![]), Duration::from_secs(5), |_| false)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, ConnectionError::ResponseTimeout(timeout) if timeout.as_secs() == 5)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_late_responses() {
+        let (mut client, mut server) = test_util::play();
+        let error = client
+            .send_and_await(
+                Packet::new(0x1d, vec![]),
+                Duration::from_secs(5),
+                |packet| packet.id() == 0x34,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ConnectionError::ResponseTimeout(_)));
+
+        // the pong arrives after the request gave up on it
+        server.send(Packet::new(0x34, vec![])).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        let event = client.next_event().await.unwrap();
+        assert!(
+            matches!(&event, Event::Unhandled(packet) if packet.id() == 0x7f),
+            "got {event:?}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn returns_disconnects_while_awaiting_response() {
+        let (mut client, mut server) = test_util::play_with_version(765);
+        let script = async {
+            server.expect(0x1d).await;
+            let reason = network(compound(vec![("text", string("Kicked"))]));
+            server
+                .send(Packet::new(Clientbound::Disconnect.id(765), reason))
+                .await;
+            // the server closes the connection after kicking
+            drop(server);
+        };
+        let request =
+            client.send_and_await(Packet::new(0x1d, vec![]), Duration::from_secs(5), |_| false);
+
+        let (error, ()) = tokio::join!(request, script);
+        match error {
+            Err(ConnectionError::Disconnected { reason_plain, .. }) => {
+                assert_eq!(reason_plain, "Kicked");
+            }
+            other => panic!("expected a disconnect, got {other:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bounds_packets_held_back() {
+        let (mut client, mut server) = test_util::play();
+        let script = async {
+            server.expect(0x1d).await;
+            for i in 0..=MAX_HELD_BACK {
+                server
+                    .send(Packet::new(
+                        0x7f,
+                        u32::try_from(i).unwrap().to_be_bytes().to_vec(),
+                    ))
+                    .await;
+            }
+            server.send(Packet::new(0x34, vec![])).await;
+        };
+        let request = client.send_and_await(
+            Packet::new(0x1d, vec![]),
+            Duration::from_secs(5),
+            |packet| packet.id() == 0x34,
+        );
+        let (pong, ()) = tokio::join!(request, script);
+        pong.unwrap();
+
+        // the oldest packet was dropped
+        let event = client.next_event().await.unwrap();
+        assert!(
+            matches!(&event, Event::Unhandled(packet) if packet.data() == 1u32.to_be_bytes()),
+            "got {event:?}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn faults_ping_responses() {
+        let (mut client, mut server) = test_util::play();
+        client.set_response_faults(Some(ResponseFaults {
+            delay: Duration::from_secs(2),
+            drop_probability: 0.0,
+            seed: Some(1),
+        }));
+        server
+            .send(Packet::new(
+                Clientbound::Ping.id(764),
+                5i32.to_be_bytes().to_vec(),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let start = tokio::time::Instant::now();
+        client.next_event().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        assert_eq!(server.expect(0x23).await.data(), 5i32.to_be_bytes());
+
+        // every response is dropped
+        client.set_response_faults(Some(ResponseFaults {
+            delay: Duration::ZERO,
+            drop_probability: 1.0,
+            seed: Some(1),
+        }));
+        server
+            .send(Packet::new(
+                Clientbound::Ping.id(764),
+                6i32.to_be_bytes().to_vec(),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        client.send_chat("/list").await.unwrap();
+        assert_eq!(server.receive().await.id(), 0x04);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_on_silent_server() {
+        let (mut client, mut server) = test_util::play();
+        client.set_idle_timeout(Some(Duration::from_secs(90)));
+        client.set_dead_connection_timeout(Some(Duration::from_secs(30)));
+        server
+            .send(Packet::new(
+                Clientbound::KeepAlive.id(764),
+                1i64.to_be_bytes().to_vec(),
+            ))
+            .await;
+
+        // the keep alive shows the server is alive, after which it goes silent
+        let start = tokio::time::Instant::now();
+        let error = client.next_event().await.unwrap_err();
+        assert!(
+            matches!(error, ConnectionError::ConnectionDead(timeout) if timeout.as_secs() == 30)
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(30));
+        assert_eq!(server.expect(0x14).await.data(), 1i64.to_be_bytes());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn answers_keep_alives_without_pacing() {
+        let (mut client, mut server) = test_util::play();
+        client.set_send_rate(Some(1));
+        client.send_chat("hi").await.unwrap();
+
+        server
+            .send(Packet::new(
+                Clientbound::KeepAlive.id(764),
+                99i64.to_be_bytes().to_vec(),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let start = tokio::time::Instant::now();
+        client.next_event().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // chat is still paced
+        client.send_chat("again").await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        server.expect(0x05).await;
+        server.expect(0x14).await;
+        server.expect(0x05).await;
+    }
+
+    #[tokio::test]
+    async fn tracks_game_events() {
+        let (mut client, mut server) = test_util::play();
+        for (event, value) in [(CHANGE_GAME_MODE, 1.0), (BEGIN_RAINING, 0.0), (4, 0.0)] {
+            server
+                .send(Packet::new(
+                    Clientbound::GameEvent.id(764),
+                    game_event_data(event, value),
+                ))
+                .await;
+        }
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::GameModeChanged(GameMode::Creative)));
+        assert_eq!(client.world().game_mode, Some(GameMode::Creative));
+
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            Event::RainStarted
+        ));
+        assert!(client.world().raining);
+
+        // the win screen has no event of its own
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(
+            event,
+            Event::GameEvent(GameEvent { event: 4, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn skips_invalid_game_modes() {
+        let (mut client, mut server) = test_util::play();
+        for (event, value) in [
+            (CHANGE_GAME_MODE, 1.0),
+            (CHANGE_GAME_MODE, 9.0),
+            (BEGIN_RAINING, 0.0),
+        ] {
+            server
+                .send(Packet::new(
+                    Clientbound::GameEvent.id(764),
+                    game_event_data(event, value),
+                ))
+                .await;
+        }
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::GameModeChanged(GameMode::Creative)));
+
+        // the connection carries on past the game mode which does not exist
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            Event::RainStarted
+        ));
+        assert_eq!(client.world().game_mode, Some(GameMode::Creative));
+    }
+
+    #[tokio::test]
+    async fn tracks_spawn_position() {
+        let (mut client, mut server) = test_util::play();
+        let spawn = Position {
+            x: -120,
+            y: -60,
+            z: 3_000,
+        };
+        server
+            .send(Packet::new(
+                Clientbound::SetDefaultSpawnPosition.id(764),
+                spawn_position_data(spawn),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        client.next_event().await.unwrap();
+        assert_eq!(client.world().spawn, Some(spawn));
+    }
+
+    #[tokio::test]
+    async fn handles_bundles_together() {
+        let (mut client, mut server) = test_util::play();
+        let delimiter = || Packet::new(Clientbound::BundleDelimiter.id(764), vec![]);
+        server.send(delimiter()).await;
+        for entity_id in [1, 2] {
+            server
+                .send(Packet::new(
+                    Clientbound::SpawnEntity.id(764),
+                    spawn_entity_data(entity_id, 0.0, 64.0, 0.0),
+                ))
+                .await;
+        }
+
+        // nothing in the bundle is handled until it is closed
+        let pending = tokio::time::timeout(Duration::from_millis(50), client.next_event()).await;
+        assert!(pending.is_err());
+        assert!(client.world().entities.is_empty());
+
+        server.send(delimiter()).await;
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            Event::EntitySpawned(1)
+        ));
+        assert_eq!(client.world().entities.len(), 1);
+        assert!(matches!(
+            client.next_event().await.unwrap(),
+            Event::EntitySpawned(2)
+        ));
+    }
+
+    #[tokio::test]
+    async fn carries_on_with_bundles_after_cancelled_reads() {
+        let (mut client, mut server) = test_util::play();
+        let delimiter = || Packet::new(Clientbound::BundleDelimiter.id(764), vec![]);
+        let spawn = |entity_id| {
+            Packet::new(
+                Clientbound::SpawnEntity.id(764),
+                spawn_entity_data(entity_id, 0.0, 64.0, 0.0),
+            )
+        };
+        server.send(delimiter()).await;
+        server.send(spawn(1)).await;
+
+        let pending = tokio::time::timeout(Duration::from_millis(50), client.next_event()).await;
+        assert!(pending.is_err());
+        // the half of the bundle read so far is not handed out as packets of their own
+        assert!(client.bundle.is_empty());
+
+        server.send(spawn(2)).await;
+        server.send(delimiter()).await;
+        server.send(spawn(3)).await;
+        for entity_id in [1, 2, 3] {
+            let event = tokio::time::timeout(Duration::from_secs(1), client.next_event()).await;
+            assert!(
+                matches!(event, Ok(Ok(Event::EntitySpawned(id))) if id == entity_id),
+                "{event:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn ends_unclosed_bundles() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(Clientbound::BundleDelimiter.id(764), vec![]))
+            .await;
+        for _ in 0..=MAX_BUNDLE_SIZE {
+            server.send(Packet::new(0x7f, vec![])).await;
+        }
+
+        for _ in 0..=MAX_BUNDLE_SIZE {
+            assert!(matches!(
+                client.next_event().await.unwrap(),
+                Event::Unhandled(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn tracks_block_changes() {
+        let (mut client, mut server) = test_util::play();
+        let location = Position { x: -5, y: 70, z: 9 };
+        server
+            .send(Packet::new(
+                Clientbound::BlockUpdate.id(764),
+                block_update_data(location, 0),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::AcknowledgeBlockChange.id(764),
+                VarInt::from(3).as_slice().to_vec(),
+            ))
+            .await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::BlockChanged(update) if update.location == location));
+        assert_eq!(client.world().blocks[&location], 0);
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::BlockChangeAcknowledged(3)));
+        assert_eq!(client.world().acknowledged_sequence, Some(3));
+    }
+
+    #[tokio::test]
+    async fn forgets_chunks_out_of_range() {
+        let (mut client, mut server) = test_util::play();
+        // a view distance of 10, so chunks are kept 13 out from the centre
+        server
+            .send(Packet::new(
+                Clientbound::Login.id(764),
+                login_data("minecraft:overworld"),
+            ))
+            .await;
+        for (x, z) in [(0, 0), (20, 0)] {
+            server
+                .send(Packet::new(
+                    Clientbound::ChunkData.id(764),
+                    chunk_data(x, z),
+                ))
+                .await;
+        }
+        let location = Position {
+            x: 321,
+            y: 70,
+            z: 5,
+        };
+        server
+            .send(Packet::new(
+                Clientbound::BlockUpdate.id(764),
+                block_update_data(location, 1),
+            ))
+            .await;
+        client.next_event().await.unwrap();
+        assert_eq!(client.world().chunks.len(), 2);
+
+        let center = [VarInt::from(1).as_slice(), VarInt::from(0).as_slice()].concat();
+        server
+            .send(Packet::new(Clientbound::SetCenterChunk.id(764), center))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert_eq!(client.world().chunks, [(0, 0)].into());
+        assert!(client.world().blocks.is_empty());
+
+        server
+            .send(Packet::new(
+                Clientbound::UnloadChunk.id(764),
+                unload_chunk_data(0, 0),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert!(client.world().chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tracks_dimension_height() {
+        let (mut client, mut server) = test_util::play_configured().await;
+        server
+            .send(Packet::new(
+                Clientbound::Login.id(764),
+                login_data("minecraft:overworld"),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::GameEvent.id(764),
+                game_event_data(BEGIN_RAINING, 0.0),
+            ))
+            .await;
+
+        client.next_event().await.unwrap();
+        assert_eq!(
+            client.world().dimension,
+            Some(DimensionType {
+                min_y: -64,
+                height: 384,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn resets_world_on_changing_dimension() {
+        let (mut client, mut server) = test_util::play_configured().await;
+        let nether = DimensionType {
+            min_y: 0,
+            height: 256,
+        };
+        server
+            .send(Packet::new(
+                Clientbound::Login.id(764),
+                login_data("minecraft:overworld"),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::SpawnEntity.id(764),
+                spawn_entity_data(12, 0.0, 64.0, 0.0),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::Respawn.id(764),
+                respawn_data("minecraft:the_nether", "minecraft:the_nether", 0),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        client.next_event().await.unwrap();
+        client.world.chunks.insert((0, 0));
+        client.world.vehicle = Some(12);
+        assert_eq!(
+            client.world().dimension_name.as_deref(),
+            Some("minecraft:overworld")
+        );
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        let world = client.world();
+        assert_eq!(world.dimension, Some(nether));
+        assert_eq!(
+            world.dimension_name.as_deref(),
+            Some("minecraft:the_nether")
+        );
+        assert_eq!(world.game_mode, Some(GameMode::Creative));
+        assert!(world.entities.is_empty());
+        assert!(world.chunks.is_empty());
+        assert_eq!(world.vehicle, None);
+    }
+
+    #[tokio::test]
+    async fn classifies_death_messages() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(
+                Clientbound::SystemChat.id(764),
+                system_chat_data(r#"{"translate":"death.attack.player","with":["Steve","Alex"]}"#),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::SystemChat.id(764),
+                system_chat_data(r#"{"text":"Welcome!"}"#),
+            ))
+            .await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(
+            matches!(event, Event::DeathMessage(ref text) if text == "death.attack.player [Steve, Alex]"),
+            "expected a death message, got {event:?}"
+        );
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::SystemChat(chat) if chat.content.to_plain() == "Welcome!"));
+    }
+
+    #[tokio::test]
+    async fn acknowledges_signed_chat() {
+        let (mut client, mut server) = test_util::play();
+        // unsigned messages are not acknowledged
+        server
+            .send(Packet::new(
+                Clientbound::PlayerChat.id(764),
+                player_chat_data(1, 0, false, "hi"),
+            ))
+            .await;
+        for index in 1..=65 {
+            server
+                .send(Packet::new(
+                    Clientbound::PlayerChat.id(764),
+                    player_chat_data(1, index, true, "hello"),
+                ))
+                .await;
+        }
+
+        for _ in 0..66 {
+            let event = client.next_event().await.unwrap();
+            assert!(matches!(event, Event::PlayerChat(_)), "got {event:?}");
+        }
+        assert_eq!(client.last_chat_index(1), Some(65));
+        assert_eq!(client.last_chat_index(2), None);
+
+        let acknowledgment = server.expect(0x03).await;
+        assert_eq!(acknowledgment.data(), [65]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pauses_anti_afk_while_mounted() {
+        let (mut client, mut server) = test_util::play();
+        client.set_anti_afk_interval(Some(Duration::from_secs(30)));
+        // the player's entity id is 7
+        server
+            .send(Packet::new(
+                Clientbound::Login.id(764),
+                login_data("minecraft:overworld"),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::SynchronizePlayerPosition.id(764),
+                synchronize_position_data(1.5, 64.0, -3.0, 1),
+            ))
+            .await;
+        server
+            .send(Packet::new(
+                Clientbound::SetPassengers.id(764),
+                set_passengers_data(20, &[3, 7]),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        server.expect(0x00).await;
+        assert_eq!(client.world().vehicle, Some(20));
+
+        // the player is not turned while riding
+        tokio::time::advance(Duration::from_secs(30)).await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+
+        // getting off the vehicle turns them again
+        server
+            .send(Packet::new(
+                Clientbound::SetPassengers.id(764),
+                set_passengers_data(20, &[3]),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert!(!client.world().is_mounted());
+        server.expect(0x18).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn confirms_teleport_before_turning() {
+        let (mut client, mut server) = test_util::play();
+        client.set_anti_afk_interval(Some(Duration::from_secs(30)));
+        server
+            .send(Packet::new(
+                Clientbound::SynchronizePlayerPosition.id(764),
+                synchronize_position_data(1.5, 64.0, -3.0, 1),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert_eq!(server.expect(0x00).await.data(), [1]);
+
+        // the player is due a turn as the next teleport arrives, which is confirmed first
+        tokio::time::advance(Duration::from_secs(30)).await;
+        server
+            .send(Packet::new(
+                Clientbound::SynchronizePlayerPosition.id(764),
+                synchronize_position_data(10.0, 70.0, 4.0, 2),
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        assert_eq!(server.expect(0x00).await.data(), [2]);
+
+        // the teleport put the next turn off, so nothing else was sent
+        client.send_chat("hello").await.unwrap();
+        server.expect(0x05).await;
+    }
+
+    #[tokio::test]
+    async fn skips_undecodable_packets_when_lenient() {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let _subscriber = tracing::subscriber::set_default(logging::subscriber(
+            logging::level(false, false),
+            move || writer.clone(),
+        ));
+
+        let (mut client, mut server) = test_util::play();
+        client.set_lenient_decoding(true);
+        // the statistics are cut off part-way through their count
+        server
+            .send(Packet::new(Clientbound::Statistics.id(764), vec![0xff]))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::Unhandled(packet) if packet.id() == 0x7f));
+        let logged = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(logged.contains("Skipping a Statistics packet which could not be decoded"));
+        assert!(logged.contains("00000000  ff"));
+
+        // only the start of a long packet is dumped
+        server
+            .send(Packet::new(
+                Clientbound::Statistics.id(764),
+                vec![0xff; 1000],
+            ))
+            .await;
+        server.send(Packet::new(0x7f, vec![])).await;
+        client.next_event().await.unwrap();
+        let logged = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(logged.contains("000000f0  ff"));
+        assert!(logged.contains("... and 744 more bytes"));
+        assert!(!logged.contains("00000100  ff"));
+
+        // a disconnect which can not be decoded still ends the connection
+        server
+            .send(Packet::new(
+                Clientbound::Disconnect.id(764),
+                vec![0x05, b'b'],
+            ))
+            .await;
+        assert!(matches!(
+            client.next_event().await,
+            Err(ConnectionError::Io(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn surfaces_titles() {
+        let (mut client, mut server) = test_util::play();
+        for (kind, text) in [
+            (Clientbound::SetTitleText, "Welcome"),
+            (Clientbound::SetSubtitleText, "to the server"),
+            (Clientbound::SetActionBarText, "Mana: 30"),
+        ] {
+            server
+                .send(Packet::new(kind.id(764), text_data(text)))
+                .await;
+        }
+
+        let title = ChatComponent::text("Welcome");
+        assert!(matches!(client.next_event().await, Ok(Event::Title(text)) if text == title));
+        let subtitle = ChatComponent::text("to the server");
+        assert!(matches!(client.next_event().await, Ok(Event::Subtitle(text)) if text == subtitle));
+        let action_bar = ChatComponent::text("Mana: 30");
+        assert!(
+            matches!(client.next_event().await, Ok(Event::ActionBar(text)) if text == action_bar)
+        );
+    }
+
+    #[tokio::test]
+    async fn surfaces_nbt_titles() {
+        let (mut client, mut server) = test_util::play_with_version(765);
+        // the title moved from 0x61, which is now the subtitle
+        for (id, text) in [(0x63, "Welcome"), (0x61, "to the server")] {
+            let text = network(compound(vec![("text", string(text))]));
+            server.send(Packet::new(id, text)).await;
+        }
+
+        let title = ChatComponent::text("Welcome");
+        assert!(matches!(client.next_event().await, Ok(Event::Title(text)) if text == title));
+        let subtitle = ChatComponent::text("to the server");
+        assert!(matches!(client.next_event().await, Ok(Event::Subtitle(text)) if text == subtitle));
+    }
+
+    #[tokio::test]
+    async fn decodes_nbt_disconnect_reason() {
+        let (mut client, mut server) = test_util::play_with_version(765);
+        let reason = network(compound(vec![
+            ("text", string("Kicked")),
+            ("color", string("red")),
+            ("extra", list(vec![string(": too fast")])),
+        ]));
+        server
+            .send(Packet::new(Clientbound::Disconnect.id(765), reason))
+            .await;
+
+        match client.next_event().await {
+            Err(ConnectionError::Disconnected {
+                reason_json,
+                reason_plain,
+            }) => {
+                assert_eq!(reason_plain, "Kicked: too fast");
+                assert_eq!(reason_json["color"], "red");
+            }
+            other => panic!("expected a kick, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn decodes_nbt_system_chat() {
+        let (mut client, mut server) = test_util::play_with_version(765);
+        let content = network(compound(vec![("text", string("Welcome!"))]));
+        // System Chat moved from 0x67, which is now Start Configuration
+        server
+            .send(Packet::new(0x69, [content.as_slice(), &[0]].concat()))
+            .await;
+
+        let event = client.next_event().await.unwrap();
+        assert!(matches!(event, Event::SystemChat(chat) if chat.content.to_plain() == "Welcome!"));
+    }
+
+    #[tokio::test]
+    async fn fails_on_undecodable_packets() {
+        let (mut client, mut server) = test_util::play();
+        server
+            .send(Packet::new(Clientbound::Statistics.id(764), vec![0xff]))
+            .await;
+
+        assert!(matches!(
+            client.next_event().await,
+            Err(ConnectionError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// A token bucket pacing packets to a steady rate, which lets a second's worth of packets through
+/// at once before pacing the rest.
+pub(super) struct RateLimiter {
+    /// How long it takes for a single packet to be allowed again.
+    interval: Duration,
+    /// How far ahead of the steady rate packets may be sent, which is the size of the burst.
+    burst: Duration,
+    /// When the bucket will be full again, if no more packets are sent.
+    full_at: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing at most `packets_per_second` packets every second, which must
+    /// not be 0.
+    pub(super) fn new(packets_per_second: u32) -> Self {
+        let interval = Duration::from_secs(1) / packets_per_second;
+
+        Self {
+            interval,
+            burst: interval * (packets_per_second - 1),
+            full_at: Instant::now(),
+        }
+    }
+
+    /// Waits until another packet may be sent, then takes it from the bucket.
+    pub(super) async fn acquire(&mut self) {
+        let now = Instant::now();
+        let full_at = self.full_at.max(now);
+        if let Some(wait_until) = full_at.checked_sub(self.burst) {
+            tokio::time::sleep_until(wait_until).await;
+        }
+
+        self.full_at = full_at + self.interval;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::RateLimiter;
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_over_time() {
+        let mut limiter = RateLimiter::new(4);
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+        // the burst goes through at once
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(250));
+
+        // a full second later the whole burst is available again
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Faults put into the client's responses to the server's keep alives and pings on purpose, so
+/// server admins can check how their server handles a slow or unreliable client.
+///
+/// The default answers straight away, every time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseFaults {
+    /// How long each response is held back before it is sent.
+    pub delay: Duration,
+    /// The chance, from 0 to 1, that a response is dropped rather than sent.
+    pub drop_probability: f64,
+    /// What the drops are decided from, so a run can be repeated, or `None` for a random seed.
+    pub seed: Option<u64>,
+}
+
+/// Decides the fate of each response, as a [`ResponseFaults`] says.
+pub(super) struct FaultInjector {
+    faults: ResponseFaults,
+    rng: StdRng,
+}
+
+impl FaultInjector {
+    /// Creates an injector putting in `faults`, whose drop probability must be from 0 to 1.
+    pub(super) fn new(faults: ResponseFaults) -> Self {
+        let rng = match faults.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Self { faults, rng }
+    }
+
+    /// Waits out the delay of a response, then decides if it is sent rather than dropped.
+    pub(super) async fn should_send(&mut self) -> bool {
+        if !self.faults.delay.is_zero() {
+            tokio::time::sleep(self.faults.delay).await;
+        }
+
+        !self.rng.gen_bool(self.faults.drop_probability)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::{FaultInjector, ResponseFaults};
+
+    #[tokio::test(start_paused = true)]
+    async fn delays_responses() {
+        let mut injector = FaultInjector::new(ResponseFaults {
+            delay: Duration::from_millis(1500),
+            ..ResponseFaults::default()
+        });
+
+        let start = Instant::now();
+        assert!(injector.should_send().await);
+        assert_eq!(start.elapsed(), Duration::from_millis(1500));
+    }
+
+    #[tokio::test]
+    async fn drops_responses_at_the_configured_probability() {
+        let faults = ResponseFaults {
+            drop_probability: 0.25,
+            seed: Some(7),
+            ..ResponseFaults::default()
+        };
+
+        let mut drops = Vec::new();
+        for _ in 0..2 {
+            let mut injector = FaultInjector::new(faults.clone());
+            let mut dropped = 0;
+            for _ in 0..1000 {
+                if !injector.should_send().await {
+                    dropped += 1;
+                }
+            }
+            drops.push(dropped);
+        }
+
+        // the same seed drops the same responses
+        assert_eq!(drops[0], drops[1]);
+        assert!((200..300).contains(&drops[0]), "dropped {}", drops[0]);
+
+        let mut reliable = FaultInjector::new(ResponseFaults::default());
+        for _ in 0..100 {
+            assert!(reliable.should_send().await);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+use super::{encode_frame, read_frame, Connection, PacketHandler, RateLimiter};
+use crate::protocol::{
+    capture::{CaptureWriter, Direction},
+    ConnectionError, Packet, State,
+};
+
+/// The capture of a split connection, which both halves record their packets to.
+type SharedCapture = Arc<Mutex<CaptureWriter>>;
+
+/// The half of a [`Connection`] which reads packets, from
+/// [`Connection::into_split`].
+pub struct ReadHalf<S> {
+    stream: tokio::io::ReadHalf<S>,
+    state: State,
+    compression_threshold: Option<usize>,
+    handlers: HashMap<(State, i32), PacketHandler>,
+    capture: Option<SharedCapture>,
+}
+
+/// The half of a [`Connection`] which writes packets, from
+/// [`Connection::into_split`].
+pub struct WriteHalf<S> {
+    stream: tokio::io::WriteHalf<S>,
+    state: State,
+    compression_threshold: Option<usize>,
+    rate_limiter: Option<RateLimiter>,
+    write_buffer: Vec<u8>,
+    capture: Option<SharedCapture>,
+}
+
+/// Records `packet` to `capture`, if the connection is captured.
+fn record(
+    capture: Option<&SharedCapture>,
+    direction: Direction,
+    state: State,
+    packet: &Packet,
+) -> std::io::Result<()> {
+    match capture {
+        // a record cut short by a panic in the other half is no reason to stop capturing
+        Some(capture) => capture
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .write(direction, state, packet),
+        None => Ok(()),
+    }
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Splits the connection into a half which reads packets and a half which writes them, so
+    /// each can be moved to its own task (e.g., reading chat in one task while sending it from
+    /// another), as [`tokio::net::TcpStream::into_split`] does for the stream.
+    ///
+    /// Each half keeps the compression threshold, and the read half the registered
+    /// [handlers](Self::on_packet), while the write half keeps the
+    /// [send rate](Self::set_send_rate). The state of the session (e.g., the world) is not
+    /// tracked by either half, so [`next_event`](Self::next_event) has no equivalent: the read
+    /// half returns every packet, and keep alives must be answered through the write half.
+    /// A capture carries on, with both halves recording to it.
+    ///
+    /// Encryption is not supported yet, so there is no cipher state to split; once it is, each
+    /// half will need its own cipher stream (the read half decrypting, the write half
+    /// encrypting).
+    pub fn into_split(self) -> (ReadHalf<S>, WriteHalf<S>) {
+        let (reader, writer) = tokio::io::split(self.stream);
+        let capture = self.capture.map(|capture| Arc::new(Mutex::new(capture)));
+
+        (
+            ReadHalf {
+                stream: reader,
+                state: self.state,
+                compression_threshold: self.compression_threshold,
+                handlers: self.handlers,
+                capture: capture.clone(),
+            },
+            WriteHalf {
+                stream: writer,
+                state: self.state,
+                compression_threshold: self.compression_threshold,
+                rate_limiter: self.rate_limiter,
+                write_buffer: self.write_buffer,
+                capture,
+            },
+        )
+    }
+}
+
+impl<S> ReadHalf<S>
+where
+    S: AsyncRead + Unpin,
+{
+    /// Reads a single packet, as [`Connection::read_packet`] does.
+    pub async fn read_packet(&mut self) -> Result<Packet, ConnectionError> {
+        loop {
+            let packet = read_frame(&mut self.stream, self.compression_threshold).await?;
+            record(
+                self.capture.as_ref(),
+                Direction::Clientbound,
+                self.state,
+                &packet,
+            )?;
+
+            match self.handlers.get_mut(&(self.state, packet.id())) {
+                Some(handler) => handler(packet.data()),
+                None => return Ok(packet),
+            }
+        }
+    }
+}
+
+impl<S> WriteHalf<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    /// Writes a single packet, as [`Connection::write_packet`] does.
+    pub async fn write_packet<P>(&mut self, packet: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        self.write_packet_buffered(packet)?;
+        self.flush().await
+    }
+
+    /// Buffers a single packet, as [`Connection::write_packet_buffered`] does.
+    pub fn write_packet_buffered<P>(&mut self, packet: P) -> Result<(), ConnectionError>
+    where
+        P: Into<Packet>,
+    {
+        let packet = packet.into();
+        record(
+            self.capture.as_ref(),
+            Direction::Serverbound,
+            self.state,
+            &packet,
+        )?;
+
+        let bytes = encode_frame(packet, self.compression_threshold)?;
+        self.write_buffer.extend_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    /// Writes every buffered packet to the stream, as [`Connection::flush`] does.
+    pub async fn flush(&mut self) -> Result<(), ConnectionError> {
+        let mut buffer = std::mem::take(&mut self.write_buffer);
+        let written = self.stream.write_all(&buffer).await;
+        // keep the allocation for the packets buffered next
+        buffer.clear();
+        self.write_buffer = buffer;
+
+        written?;
+        self.stream.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::protocol::{
+        capture::{test::SharedBuffer, CaptureReader, CaptureWriter, Direction},
+        test_util, Connection, Packet,
+    };
+
+    #[tokio::test]
+    async fn reads_and_writes_from_separate_tasks() {
+        let (client, mut server) = test_util::play();
+        let (mut reader, mut writer) = client.into_split();
+
+        let reading = tokio::spawn(async move {
+            let mut ids = Vec::new();
+            for _ in 0..3 {
+                ids.push(reader.read_packet().await.unwrap().id());
+            }
+            ids
+        });
+        let writing = tokio::spawn(async move {
+            for id in 0..3 {
+                writer
+                    .write_packet(Packet::new(id, id.to_be_bytes().to_vec()))
+                    .await
+                    .unwrap();
+            }
+        });
+
+        // the server answers each packet the write half sent to the read half
+        for id in 0..3 {
+            let packet = server.expect(id).await;
+            server
+                .send(Packet::new(0x70 + id, packet.data().to_vec()))
+                .await;
+        }
+
+        writing.await.unwrap();
+        assert_eq!(reading.await.unwrap(), [0x70, 0x71, 0x72]);
+    }
+
+    #[tokio::test]
+    async fn captures_both_halves() {
+        let buffer = SharedBuffer::default();
+        let (mut client, mut server) = test_util::play();
+        client.set_capture(CaptureWriter::new(buffer.clone(), 764, true).unwrap());
+        let (mut reader, mut writer) = client.into_split();
+
+        server.send(Packet::new(0x70, vec![1])).await;
+        reader.read_packet().await.unwrap();
+        writer
+            .write_packet(Packet::new(0x05, vec![2]))
+            .await
+            .unwrap();
+        server.expect(0x05).await;
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let mut capture = CaptureReader::new(bytes.as_slice()).unwrap();
+        let received = capture.read().unwrap().unwrap();
+        assert_eq!(received.direction, Direction::Clientbound);
+        assert_eq!(received.packet.id(), 0x70);
+        let sent = capture.read().unwrap().unwrap();
+        assert_eq!(sent.direction, Direction::Serverbound);
+        assert_eq!(sent.packet.id(), 0x05);
+        assert!(capture.read().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn empties_write_buffer_when_flush_fails() {
+        let (client, server) = tokio::io::duplex(16);
+        let (_reader, mut writer) = Connection::new(client, 760).into_split();
+        writer
+            .write_packet_buffered(Packet::new(0x05, vec![0; 64]))
+            .unwrap();
+
+        // the server leaves once part of the packet has been written
+        let leave = async move {
+            tokio::task::yield_now().await;
+            drop(server);
+        };
+        let (flushed, ()) = tokio::join!(writer.flush(), leave);
+
+        assert!(flushed.is_err());
+        assert!(writer.write_buffer.is_empty());
+    }
+}
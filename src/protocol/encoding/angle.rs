@@ -0,0 +1,30 @@
+/// An angle, sent as a single byte of steps of 1/256 of a full turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// Retrieves the angle in degrees, from 0 (inclusive) to 360 (exclusive).
+    pub fn degrees(self) -> f32 {
+        f32::from(self.0) * 360.0 / 256.0
+    }
+}
+
+impl super::Decode for Angle {
+    fn decode<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Self(u8::decode(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Angle;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn converts_to_degrees() {
+        assert_eq!(Angle(0).degrees(), 0.0);
+        assert_eq!(Angle(64).degrees(), 90.0);
+        assert_eq!(Angle(128).degrees(), 180.0);
+        assert_eq!(Angle(255).degrees(), 358.593_75);
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::{self, Read};
+
+use super::VarInt;
+
+/// Types which can be read from the body of a received packet.
+pub trait Decode: Sized {
+    /// Reads a value from the start of `reader`, advancing it past the bytes consumed.
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+impl Decode for VarInt {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        VarInt::read(reader)
+    }
+}
+
+impl Decode for bool {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid boolean byte {other}"),
+            )),
+        }
+    }
+}
+
+/// Implements [`Decode`] for numeric types sent as big-endian bytes.
+macro_rules! impl_decode_be {
+    ($($t:ty),*) => {
+        $(
+            impl Decode for $t {
+                fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+                    let mut bytes = [0; std::mem::size_of::<$t>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$t>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+// `u128` is how UUIDs are sent
+impl_decode_be!(u8, i8, u16, i16, u32, i32, u64, i64, u128, f32, f64);
+
+impl Decode for String {
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let length = read_length(reader)?;
+
+        let mut bytes = vec![0; length];
+        reader.read_exact(&mut bytes)?;
+
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Reads a var-int length prefix, rejecting negative lengths.
+pub fn read_length<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let length = i32::from(VarInt::read(reader)?);
+
+    usize::try_from(length).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid negative length {length}"),
+        )
+    })
+}
+
+/// Reads a var-int length-prefixed array, decoding each element with `read_element`.
+pub fn read_array<R, T, F>(reader: &mut R, mut read_element: F) -> io::Result<Vec<T>>
+where
+    R: Read,
+    F: FnMut(&mut R) -> io::Result<T>,
+{
+    let length = read_length(reader)?;
+
+    // the length is untrusted, so don't let it pre-allocate an arbitrary amount
+    let mut elements = Vec::with_capacity(length.min(256));
+    for _ in 0..length {
+        elements.push(read_element(reader)?);
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_array, Decode};
+
+    #[test]
+    fn decodes_primitives() {
+        let mut reader: &[u8] = &[1, 0x12, 0x34, 0xff, 0xff, 0xff, 0xfe];
+
+        assert!(bool::decode(&mut reader).unwrap());
+        assert_eq!(u16::decode(&mut reader).unwrap(), 0x1234);
+        assert_eq!(i32::decode(&mut reader).unwrap(), -2);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_bool() {
+        let mut reader: &[u8] = &[2];
+        assert!(bool::decode(&mut reader).is_err());
+    }
+
+    #[test]
+    fn decodes_string() {
+        let mut reader: &[u8] = &[5, b'h', b'e', b'l', b'l', b'o', 0];
+
+        assert_eq!(String::decode(&mut reader).unwrap(), "hello");
+        assert_eq!(reader, [0]);
+    }
+
+    #[test]
+    fn rejects_truncated_string() {
+        let mut reader: &[u8] = &[5, b'h', b'i'];
+        assert!(String::decode(&mut reader).is_err());
+    }
+
+    #[test]
+    fn decodes_array() {
+        let mut reader: &[u8] = &[3, 1, 2, 3];
+
+        assert_eq!(read_array(&mut reader, u8::decode).unwrap(), [1, 2, 3]);
+    }
+}
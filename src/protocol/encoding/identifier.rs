@@ -0,0 +1,46 @@
+/// Checks that `identifier` is a valid namespaced identifier (e.g., `minecraft:brand`), whose
+/// namespace may be left out to mean `minecraft`.
+///
+/// Namespaces may only hold lowercase letters, digits, `.`, `-` and `_`, and paths may also hold
+/// `/`.
+pub fn is_valid_identifier(identifier: &str) -> bool {
+    let (namespace, path) = identifier
+        .split_once(':')
+        .unwrap_or(("minecraft", identifier));
+    let is_namespace_char =
+        |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_');
+
+    !namespace.is_empty()
+        && !path.is_empty()
+        && namespace.chars().all(is_namespace_char)
+        && path.chars().all(|c| is_namespace_char(c) || c == '/')
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_valid_identifier;
+
+    #[test]
+    fn validates_identifiers() {
+        for identifier in ["minecraft:brand", "brand", "my_mod:sync/v2.1"] {
+            assert!(
+                is_valid_identifier(identifier),
+                "{identifier} should be valid"
+            );
+        }
+
+        for identifier in [
+            "",
+            "minecraft:",
+            ":brand",
+            "Minecraft:brand",
+            "a:b:c",
+            "a b",
+        ] {
+            assert!(
+                !is_valid_identifier(identifier),
+                "{identifier} should be invalid"
+            );
+        }
+    }
+}
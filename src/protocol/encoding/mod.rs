@@ -3,3 +3,17 @@ pub use var_int::VarInt;
 
 mod string;
 pub use string::EncodedString;
+
+mod angle;
+pub use angle::Angle;
+
+mod position;
+pub use position::Position;
+
+mod identifier;
+pub use identifier::is_valid_identifier;
+
+mod decode;
+pub use decode::{read_array, read_length, Decode};
+
+pub mod prelude;
@@ -0,0 +1,78 @@
+/// The position of a block, sent packed into 64 bits: 26 for x, then 26 for z, then 12 for y.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Position {
+    /// Packs the position into the 64 bits it is sent as.
+    pub fn pack(self) -> i64 {
+        ((i64::from(self.x) & 0x3ff_ffff) << 38)
+            | ((i64::from(self.z) & 0x3ff_ffff) << 12)
+            | (i64::from(self.y) & 0xfff)
+    }
+}
+
+impl super::Decode for Position {
+    fn decode<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let packed = i64::decode(reader)?;
+
+        // shifting right is arithmetic, which sign-extends each field; every field is at most 26
+        // bits, so fits in an i32
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(Self {
+            x: (packed >> 38) as i32,
+            y: (packed << 52 >> 52) as i32,
+            z: (packed << 26 >> 38) as i32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Position;
+    use crate::protocol::encoding::Decode;
+
+    #[test]
+    fn round_trips_positions() {
+        for position in [
+            Position { x: 0, y: 0, z: 0 },
+            Position {
+                x: 18_357_644,
+                y: 831,
+                z: -20_882_616,
+            },
+            Position {
+                x: -1,
+                y: -64,
+                z: -1,
+            },
+            Position {
+                x: 33_554_431,
+                y: 2047,
+                z: -33_554_432,
+            },
+        ] {
+            let packed = position.pack().to_be_bytes();
+            assert_eq!(Position::decode(&mut &packed[..]).unwrap(), position);
+        }
+    }
+
+    #[test]
+    fn decodes_known_position() {
+        // the example from the protocol documentation, grouped by field
+        #[allow(clippy::unusual_byte_groupings)]
+        let packed = 0b01000110000001110110001100_10110000010101101101001000_001100111111u64;
+
+        assert_eq!(
+            Position::decode(&mut &packed.to_be_bytes()[..]).unwrap(),
+            Position {
+                x: 18_357_644,
+                y: 831,
+                z: -20_882_616
+            }
+        );
+    }
+}
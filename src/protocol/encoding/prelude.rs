@@ -0,0 +1,29 @@
+//! Re-exports the encoding primitives, for packets to `use prelude::*` rather than importing
+//! each individually.
+
+pub use super::{
+    is_valid_identifier, read_array, read_length, Angle, Decode, EncodedString, VarInt,
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::protocol::Packet;
+
+    #[test]
+    fn builds_packet() {
+        let message = EncodedString::try_from(String::from("hello")).unwrap();
+        let data = [
+            VarInt::from(300).as_slice(),
+            message.as_slice().as_slice(),
+            &[Angle(64).0],
+        ]
+        .concat();
+        let packet = Packet::new(0x01, data);
+
+        let mut body = packet.data();
+        assert_eq!(i32::from(VarInt::decode(&mut body).unwrap()), 300);
+        assert_eq!(String::decode(&mut body).unwrap(), "hello");
+        assert_eq!(Angle::decode(&mut body).unwrap(), Angle(64));
+    }
+}
@@ -8,6 +8,7 @@
 /// the remaining 7 bits represent the value held at that byte.
 ///
 /// See [`https://wiki.vg/VarInt_And_VarLong`] for more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct VarInt {
     inner: [u8; 5],
 }
@@ -29,6 +30,59 @@ impl VarInt {
 
         &self.inner[0..=max_index]
     }
+
+    /// Reads a var-int from an async `reader`, one byte at a time.
+    ///
+    /// Fails with [`std::io::ErrorKind::InvalidData`] if the var-int is longer than 5 bytes.
+    pub async fn read_async<R>(reader: &mut R) -> std::io::Result<Self>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let mut var_int = Self { inner: [0; 5] };
+
+        for i in 0..5 {
+            let byte = reader.read_u8().await?;
+            var_int.inner[i] = byte;
+
+            // no continuation bit, so this was the last byte
+            if byte & 0b1000_0000 == 0 {
+                return Ok(var_int);
+            }
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "var-int is longer than 5 bytes",
+        ))
+    }
+
+    /// Reads a var-int from the start of `reader`, advancing it past the bytes consumed.
+    ///
+    /// Fails with [`std::io::ErrorKind::InvalidData`] if the var-int is longer than 5 bytes.
+    pub fn read<R>(reader: &mut R) -> std::io::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let mut var_int = Self { inner: [0; 5] };
+
+        for i in 0..5 {
+            let mut byte = [0];
+            reader.read_exact(&mut byte)?;
+            var_int.inner[i] = byte[0];
+
+            // no continuation bit, so this was the last byte
+            if byte[0] & 0b1000_0000 == 0 {
+                return Ok(var_int);
+            }
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "var-int is longer than 5 bytes",
+        ))
+    }
 }
 
 impl From<i32> for VarInt {
@@ -239,4 +293,30 @@ mod test {
             assert_eq!(VarInt::from(value.value).as_slice(), value.encoded);
         }
     }
+
+    #[test]
+    fn can_read() {
+        for test in get_test_suite() {
+            let mut reader = test.encoded.as_slice();
+            assert_eq!(i32::from(VarInt::read(&mut reader).unwrap()), test.value);
+            assert!(reader.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn can_read_async() {
+        for test in get_test_suite() {
+            let mut reader = test.encoded.as_slice();
+            assert_eq!(
+                i32::from(VarInt::read_async(&mut reader).await.unwrap()),
+                test.value
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_overlong() {
+        let mut reader: &[u8] = &[0b1000_0000; 6];
+        assert!(VarInt::read(&mut reader).is_err());
+    }
 }
@@ -0,0 +1,323 @@
+use std::num::TryFromIntError;
+
+use super::{chat::ChatComponent, State};
+
+/// The errors that can occur while talking to a server over a
+/// [`Connection`](super::Connection).
+#[derive(Debug)]
+pub enum ConnectionError {
+    /// The underlying stream failed, or a packet body could not be decoded.
+    Io(std::io::Error),
+    /// A received packet declared a length outside of what the protocol allows.
+    InvalidPacketLength(i32),
+    /// A packet was too large to be encoded.
+    PacketTooLarge(TryFromIntError),
+    /// The server closed the connection, giving a reason.
+    Disconnected {
+        /// The reason, rendered as plain text.
+        reason_plain: String,
+        /// The reason as the chat component JSON the server sent, for callers rendering it
+        /// themselves.
+        reason_json: serde_json::Value,
+    },
+    /// The server requested encryption, which is required by online-mode servers and not yet
+    /// supported.
+    EncryptionUnsupported,
+    /// The server sent a packet which is not valid in the current state.
+    UnexpectedPacket { state: State, id: i32 },
+    /// The server kept sending the resource pack at the contained URL after it was declined,
+    /// which it does when the pack is required.
+    ResourcePackRequired(String),
+    /// An unsigned chat message was not sent, as the server enforces secure chat and would kick
+    /// the player for it.
+    SecureChatEnforced,
+    /// The client disconnected, as nothing but keep alives was received for the contained
+    /// timeout.
+    IdleTimeout(std::time::Duration),
+    /// Nothing at all, not even a keep alive, was received for the contained timeout, so the
+    /// server is taken to have gone away without closing the connection.
+    ConnectionDead(std::time::Duration),
+    /// The response to a request did not arrive within the contained timeout.
+    ResponseTimeout(std::time::Duration),
+}
+
+/// Text found in the disconnect reasons servers (and proxies) give when they fail to verify the
+/// player's session with Mojang.
+const AUTH_FAILURE_REASONS: [&str; 4] = [
+    "multiplayer.disconnect.unverified_username",
+    "disconnect.loginFailedInfo.invalidSession",
+    "Failed to verify username",
+    "Invalid session",
+];
+
+/// Text found in the disconnect reasons vanilla servers give when the player is not on the
+/// whitelist, as translation keys or (from older or modded servers) as messages.
+const WHITELIST_REASONS: [&str; 3] = [
+    "multiplayer.disconnect.not_whitelisted",
+    "You are not white-listed on this server",
+    "You are not whitelisted on this server",
+];
+
+/// Text found in the disconnect reasons given when the player (or their IP address) is banned,
+/// whether or not the ban has a reason or an expiry.
+const BAN_REASONS: [&str; 3] = [
+    "multiplayer.disconnect.banned",
+    "You are banned from this server",
+    "Your IP address is banned from this server",
+];
+
+/// Text found in the disconnect reasons given when the server has no room for the player.
+const SERVER_FULL_REASONS: [&str; 2] = ["multiplayer.disconnect.server_full", "The server is full"];
+
+/// Why the server disconnected the player, for the common reasons callers may want to react to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// The player is not on the server's whitelist.
+    Whitelisted,
+    /// The player or their IP address is banned from the server.
+    Banned,
+    /// The server has as many players as it allows.
+    ServerFull,
+    /// Any other reason, as the server gave it.
+    Other(String),
+}
+
+impl DisconnectReason {
+    /// Classifies a disconnect `reason`, such as the plain text of a
+    /// [`ConnectionError::Disconnected`] reason.
+    pub fn classify(reason: &str) -> Self {
+        let contains_any = |texts: &[&str]| texts.iter().any(|text| reason.contains(text));
+
+        if contains_any(&WHITELIST_REASONS) {
+            Self::Whitelisted
+        } else if contains_any(&BAN_REASONS) {
+            Self::Banned
+        } else if contains_any(&SERVER_FULL_REASONS) {
+            Self::ServerFull
+        } else {
+            Self::Other(reason.to_string())
+        }
+    }
+}
+
+impl ConnectionError {
+    /// Creates a [`ConnectionError::Disconnected`] for the server giving `reason`.
+    pub fn disconnected(reason: &ChatComponent) -> Self {
+        let reason_plain = reason.to_plain();
+
+        Self::Disconnected {
+            reason_json: serde_json::to_value(reason)
+                .unwrap_or_else(|_| serde_json::Value::String(reason_plain.clone())),
+            reason_plain,
+        }
+    }
+
+    /// Creates a [`ConnectionError::Disconnected`] for the server giving `reason` as chat
+    /// component JSON, keeping the JSON as it was sent. A reason which is not JSON is taken as
+    /// plain text.
+    pub fn disconnected_json(reason: &str) -> Self {
+        let Ok(reason_json) = serde_json::from_str::<serde_json::Value>(reason) else {
+            return Self::disconnected(&ChatComponent::text(reason));
+        };
+
+        Self::Disconnected {
+            reason_plain: serde_json::from_value::<ChatComponent>(reason_json.clone())
+                .map_or_else(|_| reason.to_string(), |reason| reason.to_plain()),
+            reason_json,
+        }
+    }
+
+    /// Classifies why the server disconnected us, if it did.
+    pub fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        match self {
+            Self::Disconnected { reason_plain, .. } => {
+                Some(DisconnectReason::classify(reason_plain))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if the server disconnected us because it could not verify our session, which a new
+    /// token may fix.
+    pub fn is_auth_failure(&self) -> bool {
+        match self {
+            Self::Disconnected { reason_plain, .. } => AUTH_FAILURE_REASONS
+                .iter()
+                .any(|auth_reason| reason_plain.contains(auth_reason)),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "connection error: {e}"),
+            Self::InvalidPacketLength(length) => write!(f, "invalid packet length {length}"),
+            Self::PacketTooLarge(e) => write!(f, "packet too large to encode: {e}"),
+            Self::Disconnected { reason_plain, .. } => {
+                write!(f, "disconnected by server: {reason_plain}")
+            }
+            Self::EncryptionUnsupported => {
+                write!(f, "server requested encryption, which is not supported")
+            }
+            Self::UnexpectedPacket { state, id } => {
+                write!(f, "unexpected packet 0x{id:02x} in the {state} state")
+            }
+            Self::ResourcePackRequired(url) => {
+                write!(
+                    f,
+                    "server requires the resource pack at {url}, which is not supported"
+                )
+            }
+            Self::IdleTimeout(timeout) => write!(
+                f,
+                "disconnected after {}s without activity",
+                timeout.as_secs()
+            ),
+            Self::ConnectionDead(timeout) => write!(
+                f,
+                "nothing received for {}s, the connection appears dead",
+                timeout.as_secs()
+            ),
+            Self::ResponseTimeout(timeout) => {
+                write!(f, "no response arrived within {timeout:?}")
+            }
+            Self::SecureChatEnforced => write!(
+                f,
+                "server enforces secure chat, so unsigned chat messages are not sent"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::PacketTooLarge(e) => Some(e),
+            Self::InvalidPacketLength(_)
+            | Self::Disconnected { .. }
+            | Self::EncryptionUnsupported
+            | Self::UnexpectedPacket { .. }
+            | Self::ResourcePackRequired(_)
+            | Self::SecureChatEnforced
+            | Self::IdleTimeout(_)
+            | Self::ConnectionDead(_)
+            | Self::ResponseTimeout(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConnectionError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<TryFromIntError> for ConnectionError {
+    fn from(e: TryFromIntError) -> Self {
+        Self::PacketTooLarge(e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConnectionError, DisconnectReason};
+
+    #[test]
+    fn classifies_disconnect_reasons() {
+        for (reason, expected) in [
+            (
+                r#"{"translate":"multiplayer.disconnect.not_whitelisted"}"#,
+                DisconnectReason::Whitelisted,
+            ),
+            (
+                r#"{"text":"You are not white-listed on this server!"}"#,
+                DisconnectReason::Whitelisted,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.banned.reason","with":["Griefing"]}"#,
+                DisconnectReason::Banned,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.banned_ip.reason","with":["Spam"]}"#,
+                DisconnectReason::Banned,
+            ),
+            (
+                r#"{"translate":"multiplayer.disconnect.server_full"}"#,
+                DisconnectReason::ServerFull,
+            ),
+            ("The server is full!", DisconnectReason::ServerFull),
+            (
+                r#"{"text":"Server closed"}"#,
+                DisconnectReason::Other(r#"{"text":"Server closed"}"#.to_string()),
+            ),
+        ] {
+            assert_eq!(DisconnectReason::classify(reason), expected, "{reason}");
+        }
+    }
+
+    #[test]
+    fn classifies_reasons_by_their_text() {
+        // the text is split across parts of the component, so only its plain text holds it whole
+        let error = ConnectionError::disconnected_json(
+            r#"{"text":"You are banned ","extra":[{"text":"from this server","color":"red"}]}"#,
+        );
+        assert_eq!(error.disconnect_reason(), Some(DisconnectReason::Banned));
+
+        let error = ConnectionError::disconnected_json(
+            r#"{"text":"Failed to verify ","extra":["username!"]}"#,
+        );
+        assert!(error.is_auth_failure());
+
+        let error = ConnectionError::disconnected_json(
+            r#"{"translate":"multiplayer.disconnect.unverified_username"}"#,
+        );
+        assert!(error.is_auth_failure());
+    }
+
+    #[test]
+    fn classifies_only_disconnects() {
+        let error = ConnectionError::disconnected_json("The server is full!");
+        assert_eq!(
+            error.disconnect_reason(),
+            Some(DisconnectReason::ServerFull)
+        );
+        assert_eq!(
+            ConnectionError::SecureChatEnforced.disconnect_reason(),
+            None
+        );
+    }
+
+    #[test]
+    fn keeps_plain_and_json_reasons() {
+        let reason = r#"{"text":"Kicked: ","color":"red","extra":[{"text":"AFK","bold":true}," for too long"]}"#;
+
+        match ConnectionError::disconnected_json(reason) {
+            ConnectionError::Disconnected {
+                reason_plain,
+                reason_json,
+            } => {
+                assert_eq!(reason_plain, "Kicked: AFK for too long");
+                assert_eq!(
+                    reason_json,
+                    serde_json::json!({
+                        "text": "Kicked: ",
+                        "color": "red",
+                        "extra": [{"text": "AFK", "bold": true}, " for too long"],
+                    })
+                );
+            }
+            other => panic!("expected a disconnect, got {other:?}"),
+        }
+
+        // reasons which are not JSON are plain text
+        let error = ConnectionError::disconnected_json("Server closed");
+        assert_eq!(error.to_string(), "disconnected by server: Server closed");
+        assert!(matches!(
+            error,
+            ConnectionError::Disconnected { reason_json, .. } if reason_json == "Server closed"
+        ));
+    }
+}
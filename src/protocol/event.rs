@@ -0,0 +1,82 @@
+use super::{
+    chat::ChatComponent,
+    packets::play::{
+        BlockUpdate, CommandSuggestions, GameEvent, GameMode, OpenScreen, PlayerChat,
+        SetContainerSlot, Statistics, SystemChat,
+    },
+    Packet,
+};
+
+/// Something which happened during play, returned from
+/// [`Connection::next_event`](super::Connection::next_event).
+#[derive(Debug)]
+pub enum Event {
+    /// An entity was added to the world, with the given entity id.
+    EntitySpawned(i32),
+    /// The chunks around the player have been received, so the world has finished loading.
+    ///
+    /// This only happens once per connection.
+    WorldLoaded,
+    /// The server opened a window (e.g., a menu).
+    ScreenOpened(OpenScreen),
+    /// The item in a slot of a window changed.
+    SlotChanged(SetContainerSlot),
+    /// The player died, with the given death message.
+    ///
+    /// The player is respawned automatically if enabled with
+    /// [`Connection::set_auto_respawn`](super::Connection::set_auto_respawn), or otherwise with
+    /// [`Connection::respawn`](super::Connection::respawn).
+    Died(ChatComponent),
+    /// The server announced that a player (not necessarily this one) died, with the death
+    /// message rendered as [`ChatComponent::to_plain`] does.
+    DeathMessage(String),
+    /// A player sent a chat message.
+    PlayerChat(PlayerChat),
+    /// The server sent a chat message of its own, which is not a death message.
+    SystemChat(SystemChat),
+    /// The server showed a title in the middle of the screen.
+    Title(ChatComponent),
+    /// The server showed a subtitle below the title.
+    Subtitle(ChatComponent),
+    /// The server showed text above the hotbar.
+    ActionBar(ChatComponent),
+    /// The player's health dropped below the threshold set with
+    /// [`Connection::set_low_health_threshold`](super::Connection::set_low_health_threshold), to
+    /// the given health.
+    ///
+    /// This happens again only once the health has risen back to the threshold.
+    LowHealth(f32),
+    /// The server answered the latest
+    /// [`Connection::request_suggestions`](super::Connection::request_suggestions).
+    Suggestions(CommandSuggestions),
+    /// The server sent the player's statistics, usually in answer to
+    /// [`Connection::request_statistics`](super::Connection::request_statistics).
+    Statistics(Statistics),
+    /// A block changed (e.g., because a player broke it).
+    BlockChanged(BlockUpdate),
+    /// The server handled every block change the player made up to the given sequence number,
+    /// which is also recorded in [`World::acknowledged_sequence`](super::World::acknowledged_sequence).
+    BlockChangeAcknowledged(i32),
+    /// It started raining.
+    RainStarted,
+    /// It stopped raining.
+    RainStopped,
+    /// The player's game mode changed to the given game mode.
+    GameModeChanged(GameMode),
+    /// The server sent a game event which has no event of its own (e.g., the win screen).
+    GameEvent(GameEvent),
+    /// A packet was received which is not handled by the client.
+    Unhandled(Packet),
+}
+
+/// Surfaces a system chat message as [`Event::DeathMessage`] if it is one, or otherwise as
+/// [`Event::SystemChat`].
+impl From<SystemChat> for Event {
+    fn from(chat: SystemChat) -> Self {
+        if chat.is_death_message() {
+            Self::DeathMessage(chat.content.to_plain())
+        } else {
+            Self::SystemChat(chat)
+        }
+    }
+}
@@ -1,5 +1,44 @@
 pub mod encoding;
 pub mod packets;
 
+/// The protocol version (1.20.2) the client speaks by default, and whose play packets it
+/// understands.
+pub const PROTOCOL_VERSION: i32 = 764;
+
+/// The protocol versions (1.20.2 to 1.20.4) whose play packets the client understands, so which it
+/// can join servers on.
+pub const PLAY_VERSIONS: std::ops::RangeInclusive<i32> = PROTOCOL_VERSION..=765;
+
+/// The largest length a packet may declare, which is the largest value a 3-byte var-int can hold.
+pub(crate) const MAX_PACKET_LENGTH: i32 = 2_097_151;
+
+pub mod capture;
+pub mod chat;
+pub mod nbt;
+pub mod proxy;
+
+mod compression;
+
+mod connection;
+pub use connection::{
+    Connection, PacketHandler, ReadHalf, ResponseFaults, WriteHalf, DEFAULT_LOW_HEALTH_THRESHOLD,
+    DEFAULT_RESOURCE_PACK_LIMIT, MIN_RESPAWN_INTERVAL,
+};
+
+mod error;
+pub use error::{ConnectionError, DisconnectReason};
+
+mod event;
+pub use event::Event;
+
 mod packet;
 pub use packet::Packet;
+
+mod state;
+pub use state::State;
+
+mod world;
+pub use world::{Entity, PlayerPosition, World};
+
+#[cfg(test)]
+pub(crate) mod test_util;
@@ -0,0 +1,511 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
+
+use super::encoding::Decode;
+
+/// The ids of each NBT tag type.
+const END_TAG: u8 = 0;
+const BYTE_TAG: u8 = 1;
+const SHORT_TAG: u8 = 2;
+const INT_TAG: u8 = 3;
+const LONG_TAG: u8 = 4;
+const FLOAT_TAG: u8 = 5;
+const DOUBLE_TAG: u8 = 6;
+const BYTE_ARRAY_TAG: u8 = 7;
+const STRING_TAG: u8 = 8;
+const LIST_TAG: u8 = 9;
+const COMPOUND_TAG: u8 = 10;
+const INT_ARRAY_TAG: u8 = 11;
+const LONG_ARRAY_TAG: u8 = 12;
+
+/// The deepest lists and compounds may be nested, as in the vanilla server.
+const MAX_DEPTH: usize = 512;
+
+/// The first protocol version (1.20.2) which leaves the name of the root tag out of NBT sent over
+/// the network.
+const UNNAMED_ROOT_VERSION: i32 = 764;
+
+/// How the root tag of some NBT is framed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NbtFormat {
+    /// The root tag has a name (usually empty), as in files and in packets before protocol 764.
+    Named,
+    /// The root tag has no name, as in packets from protocol 764 (1.20.2) onwards.
+    Network,
+}
+
+impl NbtFormat {
+    /// Retrieves the format NBT is sent in on `protocol_version`.
+    pub fn for_protocol(protocol_version: i32) -> Self {
+        if protocol_version >= UNNAMED_ROOT_VERSION {
+            Self::Network
+        } else {
+            Self::Named
+        }
+    }
+}
+
+/// A tag of NBT, the binary format the server sends registries and item data in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<NbtTag>),
+    Compound(HashMap<String, NbtTag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl NbtTag {
+    /// Retrieves the tag called `key`, if this is a compound holding one.
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Compound(tags) => tags.get(key),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the value of an int tag.
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the value of a string tag.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the elements of a list tag.
+    pub fn as_list(&self) -> Option<&[Self]> {
+        match self {
+            Self::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the id of the tag's type.
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Byte(_) => BYTE_TAG,
+            Self::Short(_) => SHORT_TAG,
+            Self::Int(_) => INT_TAG,
+            Self::Long(_) => LONG_TAG,
+            Self::Float(_) => FLOAT_TAG,
+            Self::Double(_) => DOUBLE_TAG,
+            Self::ByteArray(_) => BYTE_ARRAY_TAG,
+            Self::String(_) => STRING_TAG,
+            Self::List(_) => LIST_TAG,
+            Self::Compound(_) => COMPOUND_TAG,
+            Self::IntArray(_) => INT_ARRAY_TAG,
+            Self::LongArray(_) => LONG_ARRAY_TAG,
+        }
+    }
+
+    /// Writes the payload of the tag to the end of `buffer`.
+    fn write_payload(&self, buffer: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::Byte(value) => buffer.extend(value.to_be_bytes()),
+            Self::Short(value) => buffer.extend(value.to_be_bytes()),
+            Self::Int(value) => buffer.extend(value.to_be_bytes()),
+            Self::Long(value) => buffer.extend(value.to_be_bytes()),
+            Self::Float(value) => buffer.extend(value.to_be_bytes()),
+            Self::Double(value) => buffer.extend(value.to_be_bytes()),
+            Self::ByteArray(values) => {
+                write_length(buffer, values.len())?;
+                buffer.extend(values);
+            }
+            Self::String(value) => write_string(buffer, value)?,
+            Self::List(elements) => {
+                let kind = elements.first().map_or(END_TAG, Self::kind);
+                if elements.iter().any(|element| element.kind() != kind) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "NBT list elements must all be of the same type",
+                    ));
+                }
+
+                buffer.push(kind);
+                write_length(buffer, elements.len())?;
+                for element in elements {
+                    element.write_payload(buffer)?;
+                }
+            }
+            Self::Compound(tags) => {
+                for (name, tag) in tags {
+                    buffer.push(tag.kind());
+                    write_string(buffer, name)?;
+                    tag.write_payload(buffer)?;
+                }
+                buffer.push(END_TAG);
+            }
+            Self::IntArray(values) => {
+                write_length(buffer, values.len())?;
+                buffer.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+            }
+            Self::LongArray(values) => {
+                write_length(buffer, values.len())?;
+                buffer.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the payload of a tag of type `kind`, nested `depth` lists and compounds deep.
+    fn read_payload<R: Read>(reader: &mut R, kind: u8, depth: usize) -> io::Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("NBT is nested more than {MAX_DEPTH} tags deep"),
+            ));
+        }
+
+        Ok(match kind {
+            BYTE_TAG => Self::Byte(i8::decode(reader)?),
+            SHORT_TAG => Self::Short(i16::decode(reader)?),
+            INT_TAG => Self::Int(i32::decode(reader)?),
+            LONG_TAG => Self::Long(i64::decode(reader)?),
+            FLOAT_TAG => Self::Float(f32::decode(reader)?),
+            DOUBLE_TAG => Self::Double(f64::decode(reader)?),
+            BYTE_ARRAY_TAG => Self::ByteArray(read_elements(reader, u8::decode)?),
+            STRING_TAG => Self::String(read_string(reader)?),
+            LIST_TAG => {
+                let kind = u8::decode(reader)?;
+                Self::List(read_elements(reader, |reader| {
+                    Self::read_payload(reader, kind, depth + 1)
+                })?)
+            }
+            COMPOUND_TAG => {
+                let mut tags = HashMap::new();
+                loop {
+                    let kind = u8::decode(reader)?;
+                    if kind == END_TAG {
+                        break;
+                    }
+
+                    let name = read_string(reader)?;
+                    tags.insert(name, Self::read_payload(reader, kind, depth + 1)?);
+                }
+
+                Self::Compound(tags)
+            }
+            INT_ARRAY_TAG => Self::IntArray(read_elements(reader, i32::decode)?),
+            LONG_ARRAY_TAG => Self::LongArray(read_elements(reader, i64::decode)?),
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown NBT tag type {other}"),
+                ))
+            }
+        })
+    }
+}
+
+/// Reads a root tag in the given `format` from the start of `reader`, discarding its name if it
+/// has one.
+///
+/// Every tag type is supported, and a tag of an unknown type fails to decode.
+pub fn read_nbt<R: Read>(reader: &mut R, format: NbtFormat) -> io::Result<NbtTag> {
+    let kind = u8::decode(reader)?;
+    if format == NbtFormat::Named {
+        read_string(reader)?;
+    }
+
+    NbtTag::read_payload(reader, kind, 0)
+}
+
+/// Reads a root tag in the given `format` which may be left out, as the NBT of an item is,
+/// where a lone end tag stands in its place.
+pub fn read_optional_nbt<R: Read>(reader: &mut R, format: NbtFormat) -> io::Result<Option<NbtTag>> {
+    let kind = u8::decode(reader)?;
+    if kind == END_TAG {
+        return Ok(None);
+    }
+    if format == NbtFormat::Named {
+        read_string(reader)?;
+    }
+
+    NbtTag::read_payload(reader, kind, 0).map(Some)
+}
+
+/// Encodes `tag` as a root tag in the given `format` (with an empty name, if it has one), so that
+/// [`read_nbt`] reads it back unchanged.
+///
+/// Fails if a string is longer than 65535 bytes, or a list holds tags of different types.
+pub fn write_nbt(tag: &NbtTag, format: NbtFormat) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![tag.kind()];
+    if format == NbtFormat::Named {
+        write_string(&mut buffer, "")?;
+    }
+    tag.write_payload(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+/// Writes an NBT string, prefixed with its length as an unsigned short.
+fn write_string(buffer: &mut Vec<u8>, value: &str) -> io::Result<()> {
+    let length = u16::try_from(value.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("NBT strings must be at most {} bytes", u16::MAX),
+        )
+    })?;
+    buffer.extend(length.to_be_bytes());
+    buffer.extend(value.as_bytes());
+
+    Ok(())
+}
+
+/// Writes the length of an array or list as an int.
+fn write_length(buffer: &mut Vec<u8>, length: usize) -> io::Result<()> {
+    let length =
+        i32::try_from(length).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    buffer.extend(length.to_be_bytes());
+
+    Ok(())
+}
+
+/// Reads an NBT string, which is prefixed with its length as an unsigned short.
+fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let mut bytes = vec![0; usize::from(u16::decode(reader)?)];
+    reader.read_exact(&mut bytes)?;
+
+    // strings are in Java's modified UTF-8, which only differs for characters we never look up
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Reads an array or list, prefixed with its length as an int, decoding each element with
+/// `read_element`.
+fn read_elements<R, T, F>(reader: &mut R, mut read_element: F) -> io::Result<Vec<T>>
+where
+    R: Read,
+    F: FnMut(&mut R) -> io::Result<T>,
+{
+    let length = i32::decode(reader)?;
+    // empty lists may say they have a negative length
+    let length = usize::try_from(length).unwrap_or(0);
+
+    // the length is untrusted, so don't let it pre-allocate an arbitrary amount
+    let mut elements = Vec::with_capacity(length.min(256));
+    for _ in 0..length {
+        elements.push(read_element(reader)?);
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::collections::HashMap;
+
+    use super::{
+        read_nbt, write_nbt, NbtFormat, NbtTag, COMPOUND_TAG, END_TAG, INT_TAG, LIST_TAG,
+        STRING_TAG,
+    };
+
+    /// The type and payload of an encoded tag.
+    pub type Tag = (u8, Vec<u8>);
+
+    /// Encodes an int tag.
+    pub fn int(value: i32) -> Tag {
+        (INT_TAG, value.to_be_bytes().to_vec())
+    }
+
+    /// Encodes a string tag.
+    pub fn string(value: &str) -> Tag {
+        let length = u16::try_from(value.len()).unwrap();
+        (
+            STRING_TAG,
+            [length.to_be_bytes().as_slice(), value.as_bytes()].concat(),
+        )
+    }
+
+    /// Encodes a list tag of `elements`, which must all be of the same type.
+    pub fn list(elements: Vec<Tag>) -> Tag {
+        let kind = elements.first().map_or(END_TAG, |(kind, _)| *kind);
+        let length = i32::try_from(elements.len()).unwrap();
+        let payloads = elements.into_iter().flat_map(|(_, payload)| payload);
+
+        (
+            LIST_TAG,
+            [kind]
+                .into_iter()
+                .chain(length.to_be_bytes())
+                .chain(payloads)
+                .collect(),
+        )
+    }
+
+    /// Encodes a compound tag of the named `tags`.
+    pub fn compound(tags: Vec<(&str, Tag)>) -> Tag {
+        let mut payload = Vec::new();
+        for (name, (kind, tag)) in tags {
+            payload.push(kind);
+            payload.extend(string(name).1);
+            payload.extend(tag);
+        }
+        payload.push(END_TAG);
+
+        (COMPOUND_TAG, payload)
+    }
+
+    /// Encodes `tag` as a nameless root tag, as it is sent over the network.
+    pub fn network((kind, payload): Tag) -> Vec<u8> {
+        [[kind].as_slice(), &payload].concat()
+    }
+
+    #[test]
+    fn decodes_nested_tags() {
+        let data = network(compound(vec![
+            ("name", string("minecraft:overworld")),
+            ("values", list(vec![int(1), int(-2)])),
+            ("element", compound(vec![("height", int(384))])),
+        ]));
+        let nbt = read_nbt(&mut data.as_slice(), NbtFormat::Network).unwrap();
+
+        assert_eq!(
+            nbt.get("name").and_then(NbtTag::as_str),
+            Some("minecraft:overworld")
+        );
+        assert_eq!(
+            nbt.get("values").and_then(NbtTag::as_list),
+            Some([NbtTag::Int(1), NbtTag::Int(-2)].as_slice())
+        );
+        assert_eq!(
+            nbt.get("element")
+                .and_then(|element| element.get("height"))
+                .and_then(NbtTag::as_int),
+            Some(384)
+        );
+        assert_eq!(nbt.get("missing"), None);
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        // lists of lists, each holding one more list
+        let mut data = vec![LIST_TAG];
+        for _ in 0..600 {
+            data.extend([LIST_TAG, 0, 0, 0, 1]);
+        }
+
+        assert!(read_nbt(&mut data.as_slice(), NbtFormat::Network).is_err());
+    }
+
+    #[test]
+    fn decodes_lists_of_compounds() {
+        let data = network(compound(vec![(
+            "players",
+            list(vec![
+                compound(vec![("name", string("Steve"))]),
+                compound(vec![("name", string("Alex"))]),
+            ]),
+        )]));
+        let nbt = read_nbt(&mut data.as_slice(), NbtFormat::Network).unwrap();
+
+        let names: Vec<_> = nbt
+            .get("players")
+            .and_then(NbtTag::as_list)
+            .unwrap()
+            .iter()
+            .map(|player| player.get("name").and_then(NbtTag::as_str))
+            .collect();
+        assert_eq!(names, [Some("Steve"), Some("Alex")]);
+    }
+
+    #[test]
+    fn rejects_unknown_tags() {
+        // a compound holding a tag of type 13
+        let data = [COMPOUND_TAG, 13, 0, 1, b'a', 0];
+        assert!(read_nbt(&mut data.as_slice(), NbtFormat::Network).is_err());
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let tag = NbtTag::Compound(HashMap::from([
+            ("title".to_string(), NbtTag::String("Notes".to_string())),
+            (
+                "pages".to_string(),
+                NbtTag::List(vec![
+                    NbtTag::String("Dig down".to_string()),
+                    NbtTag::String("Find diamonds".to_string()),
+                ]),
+            ),
+            ("generation".to_string(), NbtTag::Byte(-1)),
+            ("resolved".to_string(), NbtTag::List(vec![])),
+            (
+                "display".to_string(),
+                NbtTag::Compound(HashMap::from([
+                    ("color".to_string(), NbtTag::Int(0x00ff_00ff)),
+                    ("seed".to_string(), NbtTag::Long(-5)),
+                    ("scale".to_string(), NbtTag::Double(0.5)),
+                    ("boxes".to_string(), NbtTag::IntArray(vec![1, 2, 3])),
+                    ("states".to_string(), NbtTag::LongArray(vec![i64::MIN])),
+                    ("icon".to_string(), NbtTag::ByteArray(vec![0xca, 0xfe])),
+                ])),
+            ),
+        ]));
+
+        for format in [NbtFormat::Network, NbtFormat::Named] {
+            let data = write_nbt(&tag, format).unwrap();
+            assert_eq!(data[0], COMPOUND_TAG);
+            assert_eq!(read_nbt(&mut data.as_slice(), format).unwrap(), tag);
+        }
+    }
+
+    #[test]
+    fn refuses_to_write_mixed_lists() {
+        let tag = NbtTag::List(vec![NbtTag::Int(1), NbtTag::String("2".to_string())]);
+        assert!(write_nbt(&tag, NbtFormat::Network).is_err());
+    }
+
+    #[test]
+    fn frames_root_by_format() {
+        let tag = NbtTag::Compound(HashMap::from([("x".to_string(), NbtTag::Int(1))]));
+
+        let network_data = write_nbt(&tag, NbtFormat::Network).unwrap();
+        assert_eq!(
+            network_data,
+            [
+                [COMPOUND_TAG].as_slice(),
+                &[INT_TAG, 0, 1, b'x', 0, 0, 0, 1, END_TAG]
+            ]
+            .concat()
+        );
+
+        // the named form has an empty name between the type and the payload
+        let named_data = write_nbt(&tag, NbtFormat::Named).unwrap();
+        assert_eq!(named_data[..3], [COMPOUND_TAG, 0, 0]);
+        assert_eq!(named_data[3..], network_data[1..]);
+
+        // a named root is read with its name, even if it is not empty
+        let data = [
+            [COMPOUND_TAG, 0, 5].as_slice(),
+            b"level",
+            &network_data[1..],
+        ]
+        .concat();
+        assert_eq!(
+            read_nbt(&mut data.as_slice(), NbtFormat::Named).unwrap(),
+            tag
+        );
+        // misreading the network form as named fails, rather than returning the wrong tag
+        assert!(read_nbt(&mut network_data.as_slice(), NbtFormat::Named).is_err());
+
+        assert_eq!(NbtFormat::for_protocol(763), NbtFormat::Named);
+        assert_eq!(NbtFormat::for_protocol(764), NbtFormat::Network);
+    }
+}
@@ -2,6 +2,7 @@ use std::num::TryFromIntError;
 
 use super::encoding::VarInt;
 
+#[derive(Debug)]
 pub struct Packet {
     id: VarInt,
     data: Vec<u8>,
@@ -15,6 +16,22 @@ impl Packet {
             data,
         }
     }
+
+    /// Creates a packet from the `id` and `body` of one which has been received, whose framing
+    /// has already been removed.
+    pub fn from_parts(id: i32, body: Vec<u8>) -> Self {
+        Self::new(id, body)
+    }
+
+    /// Retrieves the id of the packet.
+    pub fn id(&self) -> i32 {
+        i32::from(self.id)
+    }
+
+    /// Retrieves a reference to the data (body) of the packet, not including the id.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
 }
 
 impl TryFrom<Packet> for Vec<u8> {
@@ -24,6 +41,19 @@ impl TryFrom<Packet> for Vec<u8> {
         let full_data = [p.id.as_slice(), p.data.as_slice()].concat();
         let data_len = i32::try_from(full_data.len())?;
 
-        Ok(vec![VarInt::from(data_len).as_slice(), full_data.as_slice()].concat())
+        Ok([VarInt::from(data_len).as_slice(), full_data.as_slice()].concat())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Packet;
+
+    #[test]
+    fn builds_from_parts() {
+        let packet = Packet::from_parts(0x24, vec![1, 2, 3]);
+
+        assert_eq!(packet.id(), 0x24);
+        assert_eq!(packet.data(), [1, 2, 3]);
     }
 }
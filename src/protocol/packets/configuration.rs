@@ -0,0 +1,712 @@
+use std::{collections::HashMap, io};
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{read_array, Decode, EncodedString, VarInt},
+    nbt::{read_nbt, NbtFormat, NbtTag},
+    Packet,
+};
+
+/// The first protocol version (1.20.5) which renumbered the configuration packets, adding cookies.
+const COOKIES_VERSION: i32 = 766;
+
+/// The first protocol version (1.20.3) which lets servers push several resource packs, adding a
+/// packet which renumbered the configuration packets after it.
+pub(crate) const RESOURCE_PACK_STACK_VERSION: i32 = 765;
+
+/// The registry of dimension types, which among other things sets how tall each dimension is.
+const DIMENSION_TYPE_REGISTRY: &str = "minecraft:dimension_type";
+
+/// The clientbound packets of the Configuration state which we handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clientbound {
+    Disconnect,
+    FinishConfiguration,
+    KeepAlive,
+    Ping,
+    RegistryData,
+    FeatureFlags,
+    UpdateTags,
+    /// Only sent from protocol 766 (1.20.5) onwards.
+    SelectKnownPacks,
+}
+
+impl Clientbound {
+    /// The ids of each packet, for protocol 764, for 765, and from 766 onwards respectively (if
+    /// it exists there).
+    const IDS: [(Self, [Option<i32>; 3]); 8] = [
+        (Self::Disconnect, [Some(0x01), Some(0x01), Some(0x02)]),
+        (
+            Self::FinishConfiguration,
+            [Some(0x02), Some(0x02), Some(0x03)],
+        ),
+        (Self::KeepAlive, [Some(0x03), Some(0x03), Some(0x04)]),
+        (Self::Ping, [Some(0x04), Some(0x04), Some(0x05)]),
+        (Self::RegistryData, [Some(0x05), Some(0x05), Some(0x07)]),
+        (Self::FeatureFlags, [Some(0x07), Some(0x08), Some(0x0c)]),
+        (Self::UpdateTags, [Some(0x08), Some(0x09), Some(0x0d)]),
+        (Self::SelectKnownPacks, [None, None, Some(0x0e)]),
+    ];
+
+    /// Picks which of the ids in [`IDS`](Self::IDS) are used on `protocol_version`.
+    fn column(protocol_version: i32) -> usize {
+        match protocol_version {
+            ..RESOURCE_PACK_STACK_VERSION => 0,
+            RESOURCE_PACK_STACK_VERSION..COOKIES_VERSION => 1,
+            _ => 2,
+        }
+    }
+
+    /// Finds the packet with the given `id` on `protocol_version`, if it is one we handle.
+    pub fn from_id(id: i32, protocol_version: i32) -> Option<Self> {
+        let column = Self::column(protocol_version);
+
+        Self::IDS
+            .iter()
+            .find(|(_, ids)| ids[column] == Some(id))
+            .map(|(packet, _)| *packet)
+    }
+
+    /// Retrieves the id of the packet on `protocol_version`, or [`None`] if it is not sent on
+    /// that version.
+    pub fn id(self, protocol_version: i32) -> Option<i32> {
+        Self::IDS
+            .iter()
+            .find(|(packet, _)| *packet == self)
+            .and_then(|(_, ids)| ids[Self::column(protocol_version)])
+    }
+}
+
+/// Finds the id of a serverbound packet on `protocol_version`, given its id for protocols 764 to
+/// 765 and from 766 onwards respectively.
+fn serverbound_id(protocol_version: i32, old_id: i32, new_id: i32) -> i32 {
+    if protocol_version >= COOKIES_VERSION {
+        new_id
+    } else {
+        old_id
+    }
+}
+
+/// The packet the server sends when it kicks the client during configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disconnect {
+    pub reason: ChatComponent,
+}
+
+impl Disconnect {
+    /// Decodes a Disconnect packet body, whose reason is NBT from protocol 765.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            reason: ChatComponent::decode(&mut data, protocol_version)?,
+        })
+    }
+}
+
+/// The packet listing the experimental features the server enabled, which the client must know
+/// about to understand the content they add.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    /// The identifiers of the features (e.g., `minecraft:update_1_21`).
+    pub flags: Vec<String>,
+}
+
+impl FeatureFlags {
+    /// Decodes a Feature Flags packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            flags: read_array(&mut data, String::decode)?,
+        })
+    }
+}
+
+/// The packet grouping the entries of registries into tags (e.g., the blocks of
+/// `minecraft:mineable/pickaxe`), sent during configuration and again during play whenever the
+/// server reloads them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateTags {
+    /// The ids of the entries in each tag, by the tag's identifier, by the identifier of the
+    /// registry they are in (e.g., `minecraft:block`).
+    pub registries: HashMap<String, HashMap<String, Vec<i32>>>,
+}
+
+impl UpdateTags {
+    /// Decodes an Update Tags packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let registries = read_array(&mut data, |data| {
+            let registry = String::decode(data)?;
+            let tags = read_array(data, |data| {
+                let tag = String::decode(data)?;
+                let entries = read_array(data, |data| VarInt::decode(data).map(i32::from))?;
+                Ok((tag, entries))
+            })?;
+
+            Ok((registry, tags.into_iter().collect()))
+        })?;
+
+        Ok(Self {
+            registries: registries.into_iter().collect(),
+        })
+    }
+}
+
+/// The height of the dimensions of a type, from the `minecraft:dimension_type` registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionType {
+    /// The lowest y coordinate blocks may be at.
+    pub min_y: i32,
+    /// How many blocks tall the dimension is.
+    pub height: i32,
+}
+
+impl DimensionType {
+    /// Retrieves the highest y coordinate blocks may be at.
+    pub fn max_y(self) -> i32 {
+        self.min_y + self.height - 1
+    }
+
+    /// Checks whether blocks may be at the y coordinate `y`.
+    pub fn contains_y(self, y: i32) -> bool {
+        (self.min_y..=self.max_y()).contains(&y)
+    }
+
+    /// Reads the height from the `element` describing a dimension type, skipping everything
+    /// else about it.
+    fn from_element(element: &NbtTag) -> io::Result<Self> {
+        let field = |name| {
+            element.get(name).and_then(NbtTag::as_int).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("dimension type has no {name}"),
+                )
+            })
+        };
+
+        Ok(Self {
+            min_y: field("min_y")?,
+            height: field("height")?,
+        })
+    }
+}
+
+/// The registry data the server sends during configuration, of which only the dimension types
+/// are decoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryData {
+    /// The dimension types in the packet, by name, in the order of their ids (or empty if the
+    /// packet holds another registry).
+    pub dimension_types: Vec<(String, DimensionType)>,
+}
+
+impl RegistryData {
+    /// Decodes a Registry Data packet body.
+    ///
+    /// Until protocol 766 every registry is sent in a single compound (the registry codec), and
+    /// from then on each registry is sent in its own packet, with an entry of NBT per element.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        if protocol_version < COOKIES_VERSION {
+            let codec = read_nbt(&mut data, NbtFormat::for_protocol(protocol_version))?;
+            let Some(registry) = codec.get(DIMENSION_TYPE_REGISTRY) else {
+                return Ok(Self::default());
+            };
+
+            let elements = registry
+                .get("value")
+                .and_then(NbtTag::as_list)
+                .unwrap_or_default();
+            let dimension_types = elements
+                .iter()
+                .map(|entry| {
+                    let name = entry.get("name").and_then(NbtTag::as_str).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "dimension type has no name")
+                    })?;
+                    let element = entry.get("element").ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "dimension type has no element")
+                    })?;
+
+                    Ok((name.to_string(), DimensionType::from_element(element)?))
+                })
+                .collect::<io::Result<_>>()?;
+
+            return Ok(Self { dimension_types });
+        }
+
+        if String::decode(&mut data)? != DIMENSION_TYPE_REGISTRY {
+            return Ok(Self::default());
+        }
+
+        let entries = read_array(&mut data, |data| {
+            let name = String::decode(data)?;
+            // entries without data come from a known pack, and we know none
+            let element = if bool::decode(data)? {
+                Some(DimensionType::from_element(&read_nbt(
+                    data,
+                    NbtFormat::for_protocol(protocol_version),
+                )?)?)
+            } else {
+                None
+            };
+
+            Ok((name, element))
+        })?;
+
+        Ok(Self {
+            dimension_types: entries
+                .into_iter()
+                .filter_map(|(name, element)| Some((name, element?)))
+                .collect(),
+        })
+    }
+}
+
+/// The serverbound packet describing the client's settings, sent at the start of configuration.
+pub struct ClientInformation {
+    /// The language of the client (e.g., `en_US`).
+    pub locale: String,
+    /// How many chunks around the player the server should send.
+    pub view_distance: i8,
+    /// If the client wants chat messages (0), only command feedback (1), or nothing (2).
+    pub chat_mode: i32,
+    /// If chat messages should keep their colors.
+    pub chat_colors: bool,
+    /// A bit mask of the skin layers (cape, jacket, sleeves, ...) to display.
+    pub displayed_skin_parts: u8,
+    /// If the player is left handed (0) or right handed (1).
+    pub main_hand: i32,
+    /// If the server should filter chat messages sent to the client.
+    pub enable_text_filtering: bool,
+    /// If the player may appear in the server status' player sample.
+    pub allow_server_listings: bool,
+}
+
+impl Default for ClientInformation {
+    /// The settings of a vanilla client, as it is when first installed.
+    fn default() -> Self {
+        Self {
+            locale: String::from("en_US"),
+            view_distance: 10,
+            chat_mode: 0,
+            chat_colors: true,
+            displayed_skin_parts: 0x7f,
+            main_hand: 1,
+            enable_text_filtering: false,
+            allow_server_listings: true,
+        }
+    }
+}
+
+/// Implement conversion from `ClientInformation` -> `Packet`
+impl TryFrom<ClientInformation> for Packet {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(p: ClientInformation) -> Result<Self, Self::Error> {
+        let locale = EncodedString::try_from(p.locale)?;
+
+        Ok(Self::new(
+            0x00,
+            [
+                locale.as_slice().as_slice(),
+                &p.view_distance.to_be_bytes(),
+                VarInt::from(p.chat_mode).as_slice(),
+                &[u8::from(p.chat_colors), p.displayed_skin_parts],
+                VarInt::from(p.main_hand).as_slice(),
+                &[
+                    u8::from(p.enable_text_filtering),
+                    u8::from(p.allow_server_listings),
+                ],
+            ]
+            .concat(),
+        ))
+    }
+}
+
+/// The serverbound packet acknowledging Finish Configuration, moving the connection into the
+/// Play state.
+pub struct AcknowledgeFinishConfiguration {
+    pub protocol_version: i32,
+}
+
+/// Implement conversion from `AcknowledgeFinishConfiguration` -> `Packet`
+impl From<AcknowledgeFinishConfiguration> for Packet {
+    fn from(p: AcknowledgeFinishConfiguration) -> Self {
+        Self::new(serverbound_id(p.protocol_version, 0x02, 0x03), vec![])
+    }
+}
+
+/// The keep alive the server sends during configuration, which must be echoed back.
+pub struct KeepAlive {
+    pub protocol_version: i32,
+    pub id: i64,
+}
+
+impl KeepAlive {
+    /// Decodes a clientbound Keep Alive packet body.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            protocol_version,
+            id: i64::decode(&mut data)?,
+        })
+    }
+}
+
+/// Implement conversion from `KeepAlive` -> `Packet`, as the serverbound response
+impl From<KeepAlive> for Packet {
+    fn from(p: KeepAlive) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x03, 0x04),
+            p.id.to_be_bytes().to_vec(),
+        )
+    }
+}
+
+/// The ping the server sends during configuration, which must be answered with a pong.
+pub struct Ping {
+    pub protocol_version: i32,
+    pub id: i32,
+}
+
+impl Ping {
+    /// Decodes a clientbound Ping packet body.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            protocol_version,
+            id: i32::decode(&mut data)?,
+        })
+    }
+}
+
+/// Implement conversion from `Ping` -> `Packet`, as the serverbound Pong response
+impl From<Ping> for Packet {
+    fn from(p: Ping) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x04, 0x05),
+            p.id.to_be_bytes().to_vec(),
+        )
+    }
+}
+
+/// The plugin channel the client names its brand on.
+const BRAND_CHANNEL: &str = "minecraft:brand";
+
+/// The serverbound Plugin Message naming the client's brand (e.g., `vanilla`), which the client
+/// sends at the start of configuration.
+pub struct ClientBrand {
+    brand: EncodedString,
+    pub protocol_version: i32,
+}
+
+impl ClientBrand {
+    /// Creates a new Plugin Message packet naming the client `brand`, on `protocol_version`.
+    pub fn new(brand: String, protocol_version: i32) -> Result<Self, std::num::TryFromIntError> {
+        Ok(Self {
+            brand: brand.try_into()?,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `ClientBrand` -> `Packet`
+impl From<ClientBrand> for Packet {
+    fn from(p: ClientBrand) -> Self {
+        let channel =
+            EncodedString::try_from(BRAND_CHANNEL.to_string()).expect("the brand channel is short");
+
+        Self::new(
+            serverbound_id(p.protocol_version, 0x01, 0x02),
+            [channel.as_slice(), p.brand.as_slice()].concat(),
+        )
+    }
+}
+
+/// The serverbound response to Select Known Packs (protocol 766 onwards). We know no packs, so
+/// the server sends every registry in full.
+#[derive(Default)]
+pub struct KnownPacks {}
+
+/// Implement conversion from `KnownPacks` -> `Packet`
+impl From<KnownPacks> for Packet {
+    fn from(_: KnownPacks) -> Self {
+        Self::new(0x07, VarInt::from(0).as_slice().to_vec())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        AcknowledgeFinishConfiguration, ClientBrand, ClientInformation, Clientbound, DimensionType,
+        Disconnect, FeatureFlags, KeepAlive, RegistryData, UpdateTags,
+    };
+    use crate::protocol::{
+        chat::ChatComponent,
+        encoding::EncodedString,
+        nbt::test::{compound, int, list, network, string, Tag},
+        Packet,
+    };
+
+    /// Encodes the element of a dimension type as the vanilla server does, with its height and
+    /// the rest of its settings.
+    fn dimension_type_element(min_y: i32, height: i32) -> Tag {
+        compound(vec![
+            ("piglin_safe", (0x01, vec![0])),
+            ("natural", (0x01, vec![1])),
+            ("ambient_light", (0x05, 0.0f32.to_be_bytes().to_vec())),
+            ("infiniburn", string("#minecraft:infiniburn_overworld")),
+            ("effects", string("minecraft:overworld")),
+            (
+                "monster_spawn_light_level",
+                compound(vec![
+                    ("type", string("minecraft:uniform")),
+                    (
+                        "value",
+                        compound(vec![("min_inclusive", int(0)), ("max_inclusive", int(7))]),
+                    ),
+                ]),
+            ),
+            ("min_y", int(min_y)),
+            ("height", int(height)),
+            ("logical_height", int(height)),
+            ("coordinate_scale", (0x06, 1.0f64.to_be_bytes().to_vec())),
+        ])
+    }
+
+    /// Builds the body of a protocol 764 Registry Data packet, holding the registry codec with
+    /// the overworld's and the nether's dimension types.
+    pub fn registry_codec_data() -> Vec<u8> {
+        let entry = |name, id, min_y, height| {
+            compound(vec![
+                ("name", string(name)),
+                ("id", int(id)),
+                ("element", dimension_type_element(min_y, height)),
+            ])
+        };
+
+        network(compound(vec![
+            (
+                "minecraft:chat_type",
+                compound(vec![
+                    ("type", string("minecraft:chat_type")),
+                    ("value", list(vec![])),
+                ]),
+            ),
+            (
+                "minecraft:dimension_type",
+                compound(vec![
+                    ("type", string("minecraft:dimension_type")),
+                    (
+                        "value",
+                        list(vec![
+                            entry("minecraft:overworld", 0, -64, 384),
+                            entry("minecraft:the_nether", 1, 0, 256),
+                        ]),
+                    ),
+                ]),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn encodes_default_client_information() {
+        let packet = Packet::try_from(ClientInformation::default()).unwrap();
+
+        assert_eq!(packet.id(), 0x00);
+        assert_eq!(
+            packet.data(),
+            [b"\x05en_US".as_slice(), &[10, 0, 1, 0x7f, 1, 0, 1]].concat()
+        );
+    }
+
+    #[test]
+    fn encodes_client_brand() {
+        let packet = Packet::from(ClientBrand::new("vanilla".to_string(), 764).unwrap());
+
+        assert_eq!(packet.id(), 0x01);
+        assert_eq!(packet.data(), b"\x0fminecraft:brand\x07vanilla");
+
+        let packet = Packet::from(ClientBrand::new("vanilla".to_string(), 766).unwrap());
+        assert_eq!(packet.id(), 0x02);
+    }
+
+    #[test]
+    fn finds_clientbound_ids() {
+        assert_eq!(
+            Clientbound::from_id(0x02, 764),
+            Some(Clientbound::FinishConfiguration)
+        );
+        assert_eq!(
+            Clientbound::from_id(0x03, 766),
+            Some(Clientbound::FinishConfiguration)
+        );
+        assert_eq!(Clientbound::from_id(0x0e, 765), None);
+
+        assert_eq!(Clientbound::RegistryData.id(764), Some(0x05));
+        assert_eq!(Clientbound::RegistryData.id(766), Some(0x07));
+        assert_eq!(Clientbound::SelectKnownPacks.id(765), None);
+
+        // 1.20.3 added a packet before the feature flags
+        assert_eq!(Clientbound::FeatureFlags.id(764), Some(0x07));
+        assert_eq!(Clientbound::FeatureFlags.id(765), Some(0x08));
+        assert_eq!(
+            Clientbound::from_id(0x0c, 766),
+            Some(Clientbound::FeatureFlags)
+        );
+    }
+
+    /// Builds the body of a Feature Flags packet enabling `flags`.
+    pub fn feature_flags_data(flags: &[&str]) -> Vec<u8> {
+        let count = u8::try_from(flags.len()).unwrap();
+        let flags: Vec<u8> = flags
+            .iter()
+            .flat_map(|flag| {
+                EncodedString::try_from(flag.to_string())
+                    .unwrap()
+                    .as_slice()
+            })
+            .collect();
+
+        [[count].as_slice(), &flags].concat()
+    }
+
+    /// Builds the body of an Update Tags packet with only `pickaxe_blocks` in the
+    /// `minecraft:mineable/pickaxe` block tag, and no item tags.
+    pub fn update_tags_data(pickaxe_blocks: &[u8]) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+        let count = u8::try_from(pickaxe_blocks.len()).unwrap();
+
+        [
+            [2].as_slice(),
+            &string("minecraft:block"),
+            &[1],
+            &string("minecraft:mineable/pickaxe"),
+            &[count],
+            pickaxe_blocks,
+            &string("minecraft:item"),
+            &[0],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_update_tags() {
+        let tags = UpdateTags::decode(&update_tags_data(&[1, 2, 3])).unwrap();
+
+        assert_eq!(tags.registries.len(), 2);
+        assert_eq!(
+            tags.registries["minecraft:block"]["minecraft:mineable/pickaxe"],
+            [1, 2, 3]
+        );
+        assert!(tags.registries["minecraft:item"].is_empty());
+    }
+
+    #[test]
+    fn decodes_feature_flags() {
+        let data = feature_flags_data(&["minecraft:vanilla", "minecraft:update_1_21"]);
+
+        assert_eq!(
+            FeatureFlags::decode(&data).unwrap(),
+            FeatureFlags {
+                flags: vec![
+                    "minecraft:vanilla".to_string(),
+                    "minecraft:update_1_21".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn numbers_serverbound_packets() {
+        let old: Packet = AcknowledgeFinishConfiguration {
+            protocol_version: 764,
+        }
+        .into();
+        let new: Packet = AcknowledgeFinishConfiguration {
+            protocol_version: 766,
+        }
+        .into();
+
+        assert_eq!(old.id(), 0x02);
+        assert_eq!(new.id(), 0x03);
+    }
+
+    #[test]
+    fn echoes_keep_alive() {
+        let keep_alive = KeepAlive::decode(&[0, 0, 0, 0, 0, 0, 0x01, 0x02], 764).unwrap();
+        let packet: Packet = keep_alive.into();
+
+        assert_eq!(packet.id(), 0x03);
+        assert_eq!(packet.data(), [0, 0, 0, 0, 0, 0, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn decodes_disconnect() {
+        let json = EncodedString::try_from(r#"{"text":"Server closed"}"#.to_string()).unwrap();
+        let disconnect = Disconnect::decode(&json.as_slice(), 764).unwrap();
+        assert_eq!(disconnect.reason, ChatComponent::text("Server closed"));
+
+        // a string tag, from protocol 765
+        let nbt = [[0x08, 0, 13].as_slice(), b"Server closed"].concat();
+        let disconnect = Disconnect::decode(&nbt, 765).unwrap();
+        assert_eq!(disconnect.reason, ChatComponent::text("Server closed"));
+    }
+
+    #[test]
+    fn decodes_dimension_types_from_registry_codec() {
+        let registry = RegistryData::decode(&registry_codec_data(), 764).unwrap();
+
+        assert_eq!(
+            registry.dimension_types,
+            [
+                (
+                    "minecraft:overworld".to_string(),
+                    DimensionType {
+                        min_y: -64,
+                        height: 384,
+                    }
+                ),
+                (
+                    "minecraft:the_nether".to_string(),
+                    DimensionType {
+                        min_y: 0,
+                        height: 256,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(registry.dimension_types[0].1.max_y(), 319);
+        assert!(registry.dimension_types[0].1.contains_y(-64));
+        assert!(!registry.dimension_types[0].1.contains_y(320));
+    }
+
+    #[test]
+    fn decodes_dimension_types_from_registry_entries() {
+        let data = [
+            EncodedString::try_from("minecraft:dimension_type".to_string())
+                .unwrap()
+                .as_slice()
+                .as_slice(),
+            &[1],
+            &EncodedString::try_from("minecraft:overworld".to_string())
+                .unwrap()
+                .as_slice(),
+            &[1],
+            &network(dimension_type_element(-64, 384)),
+        ]
+        .concat();
+        let registry = RegistryData::decode(&data, 766).unwrap();
+        assert_eq!(
+            registry.dimension_types,
+            [(
+                "minecraft:overworld".to_string(),
+                DimensionType {
+                    min_y: -64,
+                    height: 384,
+                }
+            )]
+        );
+
+        // other registries hold no dimension types
+        let data = EncodedString::try_from("minecraft:chat_type".to_string())
+            .unwrap()
+            .as_slice();
+        let registry = RegistryData::decode(&data, 766).unwrap();
+        assert!(registry.dimension_types.is_empty());
+    }
+}
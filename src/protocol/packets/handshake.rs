@@ -1,10 +1,14 @@
-use std::num::TryFromIntError;
+use std::{io, num::TryFromIntError};
 
 use crate::protocol::{
     encoding::{EncodedString, VarInt},
     Packet,
 };
 
+/// The longest server address vanilla servers accept in the handshake. Longer addresses (e.g.,
+/// with Forge markers or forwarding data appended) get the connection dropped without a reason.
+pub const MAX_SERVER_ADDRESS_LENGTH: usize = 255;
+
 pub struct Handshake {
     /// The version of the client protocol.
     protocol_version: VarInt,
@@ -20,19 +24,37 @@ impl Handshake {
     /// Creates a new Handshake packet, given the `protocol_version` of the client, the
     /// `server_address` to connect to, the `server_port` of the server, and if the next request is
     /// a status request.
+    ///
+    /// Fails if the `server_address` is longer than [`MAX_SERVER_ADDRESS_LENGTH`] characters.
     pub fn new(
         protocol_version: i32,
         server_address: String,
         server_port: u16,
         is_status: bool,
-    ) -> Result<Self, TryFromIntError> {
+    ) -> Result<Self, io::Error> {
+        let length = server_address.chars().count();
+        if length > MAX_SERVER_ADDRESS_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("server address is {length} characters long, but servers only accept up to {MAX_SERVER_ADDRESS_LENGTH}"),
+            ));
+        }
+
         Ok(Self {
             protocol_version: VarInt::from(protocol_version),
-            server_address: server_address.try_into()?,
+            server_address: server_address
+                .try_into()
+                .map_err(|e: TryFromIntError| io::Error::new(io::ErrorKind::InvalidInput, e))?,
             server_port: server_port.to_be_bytes(),
             next_state: VarInt::from(if is_status { 1 } else { 2 }),
         })
     }
+
+    /// Serializes the handshake into the full packet as it is sent, including its length
+    /// prefix (uncompressed, as the handshake always is).
+    pub fn into_bytes(self) -> Result<Vec<u8>, TryFromIntError> {
+        Vec::try_from(Packet::from(self))
+    }
 }
 
 /// Implement conversion from Handshake -> Packet
@@ -40,7 +62,7 @@ impl From<Handshake> for Packet {
     fn from(p: Handshake) -> Self {
         Self::new(
             0x00,
-            vec![
+            [
                 p.protocol_version.as_slice(),
                 &p.server_address.as_slice(),
                 &p.server_port,
@@ -50,3 +72,41 @@ impl From<Handshake> for Packet {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Handshake, MAX_SERVER_ADDRESS_LENGTH};
+    use crate::protocol::Packet;
+
+    #[test]
+    fn accepts_longest_address() {
+        let address = "a".repeat(MAX_SERVER_ADDRESS_LENGTH);
+        assert!(Handshake::new(764, address, 25565, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_long_address() {
+        // e.g., a host with forwarding data appended
+        let address = format!("localhost\0{}", "a".repeat(MAX_SERVER_ADDRESS_LENGTH));
+
+        let error = Handshake::new(764, address, 25565, false)
+            .err()
+            .expect("address should be rejected");
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("265 characters"));
+    }
+
+    #[test]
+    fn serializes_into_bytes() {
+        let handshake = || Handshake::new(764, "localhost".to_string(), 25565, true).unwrap();
+
+        assert_eq!(
+            handshake().into_bytes().unwrap(),
+            Vec::try_from(Packet::from(handshake())).unwrap()
+        );
+        assert_eq!(
+            handshake().into_bytes().unwrap(),
+            b"\x10\x00\xfc\x05\x09localhost\x63\xdd\x01"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::{io, num::TryFromIntError};
+
+use crate::protocol::{
+    encoding::{read_array, Decode, EncodedString, VarInt},
+    packets::Handshake,
+    Packet,
+};
+
+/// The first protocol version (1.16) which sends the Login Success UUID as a `u128`, rather than
+/// as a hyphenated string.
+pub(crate) const BINARY_UUID_VERSION: i32 = 735;
+/// The first protocol version (1.19) which sends profile properties in Login Success.
+pub(crate) const PROPERTIES_VERSION: i32 = 759;
+/// The protocol versions (1.20.5 and 1.20.6) which send a "strict error handling" flag in Login
+/// Success.
+pub(crate) const STRICT_ERROR_HANDLING_VERSIONS: std::ops::RangeInclusive<i32> = 766..=767;
+/// The first protocol version (1.20.2) which requires Login Acknowledged and has a Configuration
+/// state.
+pub const CONFIGURATION_VERSION: i32 = 764;
+
+/// The id of the clientbound Disconnect packet, during login.
+pub const DISCONNECT_ID: i32 = 0x00;
+/// The id of the clientbound Encryption Request packet.
+pub const ENCRYPTION_REQUEST_ID: i32 = 0x01;
+/// The id of the clientbound Login Success packet.
+pub const LOGIN_SUCCESS_ID: i32 = 0x02;
+/// The id of the clientbound Set Compression packet.
+pub const SET_COMPRESSION_ID: i32 = 0x03;
+/// The id of the clientbound Login Plugin Request packet.
+pub const LOGIN_PLUGIN_REQUEST_ID: i32 = 0x04;
+
+/// The longest username the server accepts in Login Start.
+const MAX_USERNAME_LENGTH: usize = 16;
+
+/// The serverbound packet which begins logging in.
+pub struct LoginStart {
+    /// The protocol version being logged in with, which decides the layout of the packet.
+    protocol_version: i32,
+    /// The name of the player.
+    username: EncodedString,
+    /// The UUID of the player, if known.
+    uuid: Option<u128>,
+}
+
+impl LoginStart {
+    /// Creates a new Login Start packet for the player `username`, laid out for the given
+    /// `protocol_version`.
+    ///
+    /// The `uuid` is only sent on versions which carry it. Offline-mode servers assign their own
+    /// UUID, so `None` is sent as the nil UUID where the field is required.
+    pub fn new(
+        protocol_version: i32,
+        username: String,
+        uuid: Option<u128>,
+    ) -> Result<Self, io::Error> {
+        if username.is_empty() || username.chars().count() > MAX_USERNAME_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "username '{username}' must be between 1 and {MAX_USERNAME_LENGTH} characters"
+                ),
+            ));
+        }
+
+        Ok(Self {
+            protocol_version,
+            username: username
+                .try_into()
+                .map_err(|e: TryFromIntError| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            uuid,
+        })
+    }
+}
+
+/// Implement conversion from `LoginStart` -> `Packet`
+impl From<LoginStart> for Packet {
+    fn from(p: LoginStart) -> Self {
+        let username = p.username.as_slice();
+        let uuid = p.uuid.unwrap_or_default().to_be_bytes();
+        let has_uuid = [u8::from(p.uuid.is_some())];
+
+        let data = match p.protocol_version {
+            // 1.19: no signature data
+            759 => [username.as_slice(), &[0]].concat(),
+            // 1.19.1 and 1.19.2: no signature data, then an optional UUID
+            760 => match p.uuid {
+                Some(_) => [username.as_slice(), &[0], &has_uuid, &uuid].concat(),
+                None => [username.as_slice(), &[0], &has_uuid].concat(),
+            },
+            // 1.19.3 to 1.20.1: an optional UUID
+            761..=763 => match p.uuid {
+                Some(_) => [username.as_slice(), &has_uuid, &uuid].concat(),
+                None => [username.as_slice(), &has_uuid].concat(),
+            },
+            // 1.20.2 onwards: a required UUID
+            v if v >= CONFIGURATION_VERSION => [username.as_slice(), &uuid].concat(),
+            _ => username,
+        };
+
+        Self::new(0x00, data)
+    }
+}
+
+/// Builds the packets which begin logging in as `username` to an offline-mode (e.g., LAN) server
+/// at `server_address` and `server_port`, speaking `protocol_version`: the handshake, then the
+/// login start.
+///
+/// Offline-mode servers neither encrypt the connection nor check the player's session, so the
+/// packets are ready to write without authenticating first.
+pub fn offline_login_packets(
+    server_address: String,
+    server_port: u16,
+    username: String,
+    protocol_version: i32,
+) -> Result<Vec<Packet>, io::Error> {
+    Ok(vec![
+        Handshake::new(protocol_version, server_address, server_port, false)?.into(),
+        LoginStart::new(protocol_version, username, None)?.into(),
+    ])
+}
+
+/// A property of a player's profile (e.g., their skin textures).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+impl Decode for ProfileProperty {
+    fn decode<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let name = String::decode(reader)?;
+        let value = String::decode(reader)?;
+        let signature = if bool::decode(reader)? {
+            Some(String::decode(reader)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name,
+            value,
+            signature,
+        })
+    }
+}
+
+/// The clientbound packet which signals that login has completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginSuccess {
+    /// The UUID the server assigned to the player.
+    pub uuid: u128,
+    /// The name of the player.
+    pub username: String,
+    /// The properties of the player's profile.
+    pub properties: Vec<ProfileProperty>,
+}
+
+impl LoginSuccess {
+    /// Decodes a Login Success packet body, laid out for the given `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let uuid = if protocol_version >= BINARY_UUID_VERSION {
+            u128::decode(&mut data)?
+        } else {
+            let uuid = String::decode(&mut data)?;
+            u128::from_str_radix(&uuid.replace('-', ""), 16).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid uuid '{uuid}': {e}"),
+                )
+            })?
+        };
+        let username = String::decode(&mut data)?;
+        let properties = if protocol_version >= PROPERTIES_VERSION {
+            read_array(&mut data, ProfileProperty::decode)?
+        } else {
+            Vec::new()
+        };
+        if STRICT_ERROR_HANDLING_VERSIONS.contains(&protocol_version) {
+            bool::decode(&mut data)?;
+        }
+
+        Ok(Self {
+            uuid,
+            username,
+            properties,
+        })
+    }
+}
+
+/// The serverbound packet acknowledging Login Success, moving the connection into the
+/// Configuration state. Only sent from protocol 764 (1.20.2) onwards.
+#[derive(Default)]
+pub struct LoginAcknowledged {}
+
+/// Implement conversion from `LoginAcknowledged` -> `Packet`
+impl From<LoginAcknowledged> for Packet {
+    fn from(_: LoginAcknowledged) -> Self {
+        Self::new(0x03, vec![])
+    }
+}
+
+/// The clientbound packet which enables compression for all following packets.
+pub struct SetCompression {
+    /// Packets of at least this many bytes are compressed. Negative values disable compression.
+    pub threshold: i32,
+}
+
+impl SetCompression {
+    /// Decodes a Set Compression packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            threshold: i32::from(VarInt::decode(&mut data)?),
+        })
+    }
+}
+
+/// The clientbound packet a server (or proxy) sends to run a custom login step.
+pub struct LoginPluginRequest {
+    /// The id the response must echo back.
+    pub message_id: i32,
+    /// The channel the request was sent on.
+    pub channel: String,
+}
+
+impl LoginPluginRequest {
+    /// Decodes a Login Plugin Request packet body, ignoring the channel-specific data.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            message_id: i32::from(VarInt::decode(&mut data)?),
+            channel: String::decode(&mut data)?,
+        })
+    }
+}
+
+/// The serverbound packet answering a [`LoginPluginRequest`].
+pub struct LoginPluginResponse {
+    /// The id of the request being responded to.
+    pub message_id: i32,
+    /// If the request was understood. We understand no login plugin channels, so this answers
+    /// that the request was not understood when unset.
+    pub data: Option<Vec<u8>>,
+}
+
+/// Implement conversion from `LoginPluginResponse` -> `Packet`
+impl From<LoginPluginResponse> for Packet {
+    fn from(p: LoginPluginResponse) -> Self {
+        let message_id = VarInt::from(p.message_id);
+
+        let data = match p.data {
+            Some(data) => [message_id.as_slice(), &[1], &data].concat(),
+            None => [message_id.as_slice(), &[0]].concat(),
+        };
+
+        Self::new(0x02, data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{offline_login_packets, LoginStart, LoginSuccess};
+    use crate::protocol::Packet;
+
+    /// The uuid `00000000-0000-0000-0000-000000000001` as bytes.
+    const UUID: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+
+    #[test]
+    fn builds_offline_login_packets() {
+        let packets =
+            offline_login_packets("localhost".to_string(), 25565, "Steve".to_string(), 764)
+                .unwrap();
+
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].id(), 0x00);
+        assert_eq!(packets[0].data(), b"\xfc\x05\x09localhost\x63\xdd\x02");
+        assert_eq!(packets[1].id(), 0x00);
+        assert_eq!(
+            packets[1].data(),
+            [b"\x05Steve".as_slice(), &[0; 16]].concat()
+        );
+
+        assert!(offline_login_packets("localhost".to_string(), 25565, String::new(), 764).is_err());
+    }
+
+    #[test]
+    fn login_start_layouts() {
+        let tests: [(i32, Vec<u8>); 4] = [
+            (758, b"\x05Steve".to_vec()),
+            (759, b"\x05Steve\x00".to_vec()),
+            (761, [b"\x05Steve\x01".as_slice(), &UUID].concat()),
+            (764, [b"\x05Steve".as_slice(), &UUID].concat()),
+        ];
+
+        for (protocol_version, data) in tests {
+            let packet: Packet = LoginStart::new(protocol_version, "Steve".to_string(), Some(1))
+                .unwrap()
+                .into();
+
+            assert_eq!(packet.id(), 0x00);
+            assert_eq!(packet.data(), data, "protocol {protocol_version}");
+        }
+    }
+
+    #[test]
+    fn login_start_rejects_long_username() {
+        assert!(LoginStart::new(764, "a".repeat(17), None).is_err());
+        assert!(LoginStart::new(764, String::new(), None).is_err());
+    }
+
+    #[test]
+    fn decodes_login_success() {
+        let data = [UUID.as_slice(), b"\x05Steve\x01\x08textures\x03abc\x00"].concat();
+        let success = LoginSuccess::decode(&data, 764).unwrap();
+
+        assert_eq!(success.uuid, 1);
+        assert_eq!(success.username, "Steve");
+        assert_eq!(success.properties.len(), 1);
+        assert_eq!(success.properties[0].name, "textures");
+        assert_eq!(success.properties[0].signature, None);
+    }
+
+    #[test]
+    fn decodes_legacy_login_success() {
+        let data = b"\x2400000000-0000-0000-0000-000000000001\x05Steve";
+        let success = LoginSuccess::decode(data, 578).unwrap();
+
+        assert_eq!(success.uuid, 1);
+        assert_eq!(success.username, "Steve");
+    }
+}
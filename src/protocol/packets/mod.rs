@@ -1,5 +1,11 @@
+pub mod configuration;
 mod handshake;
+pub mod login;
+pub mod play;
 mod status;
 
-pub use handshake::Handshake;
-pub use status::Status;
+pub use handshake::{Handshake, MAX_SERVER_ADDRESS_LENGTH};
+pub use status::{
+    PingRequest, PlayerSample, ServerPlayers, ServerStatus, ServerVersion, Status,
+    PONG_RESPONSE_ID, STATUS_RESPONSE_ID,
+};
@@ -0,0 +1,258 @@
+use std::io;
+
+use super::serverbound_id;
+use crate::protocol::{
+    encoding::{Decode, Position, VarInt},
+    Packet,
+};
+
+/// The first protocol version (1.19) which acknowledges block changes by sequence number, sending
+/// one with each Player Action.
+const SEQUENCE_VERSION: i32 = 759;
+
+/// The clientbound Block Update packet, sent when a single block in a loaded chunk changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockUpdate {
+    pub location: Position,
+    /// The id of the block's new state in the block state registry (e.g., 0 for air).
+    pub block_state: i32,
+}
+
+impl BlockUpdate {
+    /// Decodes a Block Update packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            location: Position::decode(&mut data)?,
+            block_state: i32::from(VarInt::decode(&mut data)?),
+        })
+    }
+}
+
+/// The clientbound Acknowledge Block Change packet, confirming that the server has handled every
+/// block change the player made up to a sequence number (e.g., by digging).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcknowledgeBlockChange {
+    /// The sequence number of the latest change handled.
+    pub sequence: i32,
+}
+
+impl AcknowledgeBlockChange {
+    /// Decodes an Acknowledge Block Change packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            sequence: i32::from(VarInt::decode(&mut data)?),
+        })
+    }
+}
+
+/// What the player does to the block they are digging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiggingStatus {
+    Started,
+    Cancelled,
+    Finished,
+}
+
+impl DiggingStatus {
+    /// Retrieves the id the status is sent as.
+    fn id(self) -> i32 {
+        match self {
+            Self::Started => 0,
+            Self::Cancelled => 1,
+            Self::Finished => 2,
+        }
+    }
+}
+
+impl TryFrom<i32> for DiggingStatus {
+    type Error = io::Error;
+
+    fn try_from(id: i32) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Started),
+            1 => Ok(Self::Cancelled),
+            2 => Ok(Self::Finished),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "digging status {other} must be 0 (started), 1 (cancelled), or 2 (finished)"
+                ),
+            )),
+        }
+    }
+}
+
+/// A face of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    /// The bottom face, towards negative y.
+    Bottom,
+    Top,
+    /// The face towards negative z.
+    North,
+    South,
+    /// The face towards negative x.
+    West,
+    East,
+}
+
+impl Face {
+    /// Retrieves the id the face is sent as.
+    fn id(self) -> u8 {
+        match self {
+            Self::Bottom => 0,
+            Self::Top => 1,
+            Self::North => 2,
+            Self::South => 3,
+            Self::West => 4,
+            Self::East => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for Face {
+    type Error = io::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Bottom),
+            1 => Ok(Self::Top),
+            2 => Ok(Self::North),
+            3 => Ok(Self::South),
+            4 => Ok(Self::West),
+            5 => Ok(Self::East),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("face {other} must be from 0 to 5"),
+            )),
+        }
+    }
+}
+
+/// The serverbound Player Action packet, which starts, cancels, or finishes digging a block.
+///
+/// The server confirms the change with an [`AcknowledgeBlockChange`] carrying `sequence`, and
+/// sends a [`BlockUpdate`] once the block is broken.
+pub struct PlayerAction {
+    pub status: DiggingStatus,
+    /// The block being dug.
+    pub location: Position,
+    /// The face of the block being dug.
+    pub face: Face,
+    /// The sequence number of the change, which should increase with each one.
+    pub sequence: i32,
+    pub protocol_version: i32,
+}
+
+/// Implement conversion from `PlayerAction` -> `Packet`
+impl From<PlayerAction> for Packet {
+    fn from(p: PlayerAction) -> Self {
+        let sequence = (p.protocol_version >= SEQUENCE_VERSION).then(|| VarInt::from(p.sequence));
+
+        Self::new(
+            serverbound_id(p.protocol_version, 0x20, 0x21),
+            [
+                VarInt::from(p.status.id()).as_slice(),
+                &p.location.pack().to_be_bytes(),
+                &[p.face.id()],
+                sequence.as_ref().map_or(&[], VarInt::as_slice),
+            ]
+            .concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{AcknowledgeBlockChange, BlockUpdate, DiggingStatus, Face, PlayerAction};
+    use crate::protocol::{
+        encoding::{Position, VarInt},
+        Packet,
+    };
+
+    /// Builds the body of a Block Update packet setting the block at `location` to `block_state`.
+    pub fn block_update_data(location: Position, block_state: i32) -> Vec<u8> {
+        [
+            location.pack().to_be_bytes().as_slice(),
+            VarInt::from(block_state).as_slice(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_block_update() {
+        let location = Position {
+            x: -130,
+            y: -59,
+            z: -2,
+        };
+
+        assert_eq!(
+            BlockUpdate::decode(&block_update_data(location, 2_000)).unwrap(),
+            BlockUpdate {
+                location,
+                block_state: 2_000,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_acknowledge_block_change() {
+        assert_eq!(
+            AcknowledgeBlockChange::decode(&[0xac, 0x02]).unwrap(),
+            AcknowledgeBlockChange { sequence: 300 }
+        );
+    }
+
+    #[test]
+    fn encodes_start_digging() {
+        let location = Position { x: 1, y: 64, z: -1 };
+        let packet = Packet::from(PlayerAction {
+            status: DiggingStatus::Started,
+            location,
+            face: Face::Top,
+            sequence: 4,
+            protocol_version: 764,
+        });
+
+        assert_eq!(packet.id(), 0x20);
+        assert_eq!(
+            packet.data(),
+            [
+                [0].as_slice(),
+                // x in the top 26 bits, z in the next 26, then y
+                &[0x00, 0x00, 0x00, 0x7f, 0xff, 0xff, 0xf0, 0x40],
+                &[1, 4],
+            ]
+            .concat()
+        );
+
+        // the sequence was only added in 1.19
+        let packet = Packet::from(PlayerAction {
+            status: DiggingStatus::Finished,
+            location,
+            face: Face::Top,
+            sequence: 4,
+            protocol_version: 758,
+        });
+        assert_eq!(packet.data().len(), 10);
+
+        // Player Action moved in 1.20.3
+        let packet = Packet::from(PlayerAction {
+            status: DiggingStatus::Started,
+            location,
+            face: Face::Top,
+            sequence: 4,
+            protocol_version: 765,
+        });
+        assert_eq!(packet.id(), 0x21);
+    }
+
+    #[test]
+    fn validates_digging_enums() {
+        assert_eq!(DiggingStatus::try_from(2).unwrap(), DiggingStatus::Finished);
+        assert!(DiggingStatus::try_from(3).is_err());
+        assert_eq!(Face::try_from(5).unwrap(), Face::East);
+        assert!(Face::try_from(6).is_err());
+    }
+}
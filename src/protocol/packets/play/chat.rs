@@ -0,0 +1,351 @@
+use std::{
+    io::{self, Read},
+    num::TryFromIntError,
+};
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{Decode, EncodedString, VarInt},
+    Packet,
+};
+
+/// The longest chat message the server accepts.
+const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// The first protocol version (1.20.5) which split signed commands into their own packet, leaving
+/// Chat Command with only the command.
+const UNSIGNED_COMMAND_VERSION: i32 = 766;
+
+/// Converts `text` into an encoded string, failing if it is longer than a chat message may be.
+fn encode_text(text: String, kind: &str) -> Result<EncodedString, io::Error> {
+    if text.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{kind} must be at most {MAX_MESSAGE_LENGTH} characters"),
+        ));
+    }
+
+    text.try_into()
+        .map_err(|e: TryFromIntError| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// The prefix of the translation keys of death messages (e.g., `death.attack.player`).
+const DEATH_MESSAGE_PREFIX: &str = "death.";
+
+/// The clientbound System Chat Message packet, a message from the server rather than a player
+/// (e.g., a player joining, or a death message).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemChat {
+    pub content: ChatComponent,
+    /// If the message is shown above the hotbar, rather than in the chat.
+    pub overlay: bool,
+}
+
+impl SystemChat {
+    /// Decodes a System Chat Message packet body, whose content is NBT from protocol 765.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            content: ChatComponent::decode(&mut data, protocol_version)?,
+            overlay: bool::decode(&mut data)?,
+        })
+    }
+
+    /// Whether the message announces that a player died, judging by its translation key.
+    pub fn is_death_message(&self) -> bool {
+        self.content
+            .translate
+            .as_deref()
+            .is_some_and(|key| key.starts_with(DEATH_MESSAGE_PREFIX))
+    }
+}
+
+/// How many bytes a message signature is.
+const SIGNATURE_LENGTH: usize = 256;
+
+/// The clientbound Player Chat Message packet, a chat message sent by a player.
+///
+/// Only the fields up to the salt are decoded, so the signatures of the previous messages and
+/// how the message is filtered and shown are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerChat {
+    /// The UUID of the player who sent the message.
+    pub sender: u128,
+    /// Where the message comes among those the sender has sent, counting up from 0.
+    pub index: i32,
+    /// The signature of the message, if it was signed.
+    pub signature: Option<Vec<u8>>,
+    pub message: String,
+    /// When the message was sent, in milliseconds since the Unix epoch.
+    pub timestamp: i64,
+    pub salt: i64,
+}
+
+impl PlayerChat {
+    /// Decodes a Player Chat Message packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let sender = u128::decode(&mut data)?;
+        let index = i32::from(VarInt::decode(&mut data)?);
+        let signature = if bool::decode(&mut data)? {
+            let mut signature = vec![0; SIGNATURE_LENGTH];
+            data.read_exact(&mut signature)?;
+            Some(signature)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            sender,
+            index,
+            signature,
+            message: String::decode(&mut data)?,
+            timestamp: i64::decode(&mut data)?,
+            salt: i64::decode(&mut data)?,
+        })
+    }
+}
+
+/// The serverbound packet acknowledging the signed chat messages received since the last
+/// acknowledgment, which servers enforcing secure chat check to keep the chain of messages in
+/// sync.
+pub struct MessageAcknowledgment {
+    /// How many signed messages were received since the last acknowledgment.
+    pub message_count: i32,
+}
+
+/// Implement conversion from `MessageAcknowledgment` -> `Packet`
+impl From<MessageAcknowledgment> for Packet {
+    fn from(p: MessageAcknowledgment) -> Self {
+        Self::new(0x03, VarInt::from(p.message_count).as_slice().to_vec())
+    }
+}
+
+/// The unsigned salt, signature count, and acknowledgements which end chat packets.
+fn unsigned_trailer() -> Vec<u8> {
+    [
+        // the salt only matters for signed messages
+        0i64.to_be_bytes().as_slice(),
+        // no signature, then no acknowledged messages, in a fixed bit set of 20 bits
+        &[0, 0],
+        &[0; 3],
+    ]
+    .concat()
+}
+
+/// The serverbound packet which sends a chat message, unsigned.
+pub struct ChatMessage {
+    message: EncodedString,
+    /// When the message was sent, in milliseconds since the Unix epoch.
+    timestamp: i64,
+    protocol_version: i32,
+}
+
+impl ChatMessage {
+    /// Creates a new Chat Message packet sending `message`, which was sent at `timestamp`
+    /// milliseconds since the Unix epoch, on `protocol_version`.
+    pub fn new(message: String, timestamp: i64, protocol_version: i32) -> Result<Self, io::Error> {
+        Ok(Self {
+            message: encode_text(message, "chat messages")?,
+            timestamp,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `ChatMessage` -> `Packet`
+impl From<ChatMessage> for Packet {
+    fn from(p: ChatMessage) -> Self {
+        let id = if p.protocol_version >= UNSIGNED_COMMAND_VERSION {
+            0x06
+        } else {
+            0x05
+        };
+
+        Self::new(
+            id,
+            [
+                p.message.as_slice().as_slice(),
+                &p.timestamp.to_be_bytes(),
+                &unsigned_trailer(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// The serverbound packet which runs a command, without signing its arguments.
+pub struct ChatCommand {
+    /// The command, without its leading `/`.
+    command: EncodedString,
+    /// When the command was sent, in milliseconds since the Unix epoch.
+    timestamp: i64,
+    protocol_version: i32,
+}
+
+impl ChatCommand {
+    /// Creates a new Chat Command packet running `command` (without its leading `/`), which was
+    /// sent at `timestamp` milliseconds since the Unix epoch, on `protocol_version`.
+    pub fn new(command: String, timestamp: i64, protocol_version: i32) -> Result<Self, io::Error> {
+        Ok(Self {
+            command: encode_text(command, "commands")?,
+            timestamp,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `ChatCommand` -> `Packet`
+impl From<ChatCommand> for Packet {
+    fn from(p: ChatCommand) -> Self {
+        if p.protocol_version >= UNSIGNED_COMMAND_VERSION {
+            return Self::new(0x04, p.command.as_slice());
+        }
+
+        Self::new(
+            0x04,
+            [
+                p.command.as_slice().as_slice(),
+                &p.timestamp.to_be_bytes(),
+                &unsigned_trailer(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{ChatCommand, ChatMessage, MessageAcknowledgment, PlayerChat, SystemChat};
+    use crate::protocol::{
+        encoding::{EncodedString, VarInt},
+        Packet,
+    };
+
+    /// Builds the body of a Player Chat Message packet from `sender`, the `index`th message they
+    /// sent, signed if `signed` is set.
+    pub fn player_chat_data(sender: u128, index: i32, signed: bool, message: &str) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+        let signature = if signed {
+            [[1].as_slice(), &[0xab; 256]].concat()
+        } else {
+            vec![0]
+        };
+
+        [
+            sender.to_be_bytes().as_slice(),
+            VarInt::from(index).as_slice(),
+            &signature,
+            &string(message),
+            &1_700_000_000_000i64.to_be_bytes(),
+            &7i64.to_be_bytes(),
+            // no previous messages, unsigned content, or filtering, then the chat type and the
+            // sender's name
+            &[0, 0, 0, 0],
+            &string(r#"{"text":"Steve"}"#),
+            &[0],
+        ]
+        .concat()
+    }
+
+    /// Builds the body of a System Chat Message packet shown in the chat, holding the JSON
+    /// `content`.
+    pub fn system_chat_data(content: &str) -> Vec<u8> {
+        [
+            EncodedString::try_from(content.to_string())
+                .unwrap()
+                .as_slice()
+                .as_slice(),
+            &[0],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_player_chat() {
+        let chat = PlayerChat::decode(&player_chat_data(5, 300, true, "hello")).unwrap();
+
+        assert_eq!(chat.sender, 5);
+        assert_eq!(chat.index, 300);
+        assert_eq!(chat.signature, Some(vec![0xab; 256]));
+        assert_eq!(chat.message, "hello");
+        assert_eq!(chat.timestamp, 1_700_000_000_000);
+        assert_eq!(chat.salt, 7);
+
+        let chat = PlayerChat::decode(&player_chat_data(5, 0, false, "hi")).unwrap();
+        assert_eq!(chat.signature, None);
+        assert_eq!(chat.message, "hi");
+    }
+
+    #[test]
+    fn builds_message_acknowledgment() {
+        let packet = Packet::from(MessageAcknowledgment { message_count: 65 });
+
+        assert_eq!(packet.id(), 0x03);
+        assert_eq!(packet.data(), [65]);
+    }
+
+    #[test]
+    fn classifies_death_messages() {
+        let death = SystemChat::decode(
+            &system_chat_data(r#"{"translate":"death.attack.player","with":["Steve","Alex"]}"#),
+            764,
+        )
+        .unwrap();
+        assert!(death.is_death_message());
+        assert!(!death.overlay);
+
+        let joined = SystemChat::decode(
+            &system_chat_data(r#"{"translate":"multiplayer.player.joined","with":["Steve"]}"#),
+            764,
+        )
+        .unwrap();
+        assert!(!joined.is_death_message());
+    }
+
+    #[test]
+    fn builds_unsigned_message() {
+        let packet = Packet::from(ChatMessage::new("hi".to_string(), 1, 764).unwrap());
+
+        assert_eq!(packet.id(), 0x05);
+        assert_eq!(
+            packet.data(),
+            [
+                b"\x02hi".as_slice(),
+                &1i64.to_be_bytes(),
+                &[0; 8],
+                &[0, 0],
+                &[0; 3],
+            ]
+            .concat()
+        );
+
+        let packet = Packet::from(ChatMessage::new("hi".to_string(), 1, 766).unwrap());
+        assert_eq!(packet.id(), 0x06);
+    }
+
+    #[test]
+    fn builds_unsigned_command() {
+        let packet = Packet::from(ChatCommand::new("help".to_string(), 1, 764).unwrap());
+        assert_eq!(packet.id(), 0x04);
+        assert_eq!(
+            packet.data(),
+            [
+                b"\x04help".as_slice(),
+                &1i64.to_be_bytes(),
+                &[0; 8],
+                &[0, 0],
+                &[0; 3],
+            ]
+            .concat()
+        );
+
+        let packet = Packet::from(ChatCommand::new("help".to_string(), 1, 766).unwrap());
+        assert_eq!(packet.id(), 0x04);
+        assert_eq!(packet.data(), b"\x04help");
+    }
+
+    #[test]
+    fn rejects_long_messages() {
+        assert!(ChatMessage::new("a".repeat(256), 0, 764).is_ok());
+        assert!(ChatMessage::new("a".repeat(257), 0, 764).is_err());
+    }
+}
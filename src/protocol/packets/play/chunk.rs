@@ -0,0 +1,117 @@
+use std::io;
+
+use crate::protocol::encoding::{Decode, VarInt};
+
+/// The clientbound packet which moves the centre of the area of chunks the client should load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetCenterChunk {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl SetCenterChunk {
+    /// Decodes a Set Center Chunk packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            chunk_x: i32::from(VarInt::decode(&mut data)?),
+            chunk_z: i32::from(VarInt::decode(&mut data)?),
+        })
+    }
+}
+
+/// The position of a received Chunk Data and Update Light packet.
+///
+/// The chunk's blocks and light follow the position, but are not decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkData {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl ChunkData {
+    /// Decodes the position from a Chunk Data and Update Light packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            chunk_x: i32::decode(&mut data)?,
+            chunk_z: i32::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound Unload Chunk packet, telling the client to forget a chunk it has left the
+/// range of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnloadChunk {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl UnloadChunk {
+    /// Decodes an Unload Chunk packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        // the position is read as one long, so the z comes first
+        let chunk_z = i32::decode(&mut data)?;
+
+        Ok(Self {
+            chunk_x: i32::decode(&mut data)?,
+            chunk_z,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{ChunkData, SetCenterChunk, UnloadChunk};
+    use crate::protocol::encoding::VarInt;
+
+    /// Builds the body of an Unload Chunk packet for the chunk at `(x, z)`.
+    pub fn unload_chunk_data(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
+        [chunk_z.to_be_bytes(), chunk_x.to_be_bytes()].concat()
+    }
+
+    /// Builds the start of a Chunk Data and Update Light packet body for the chunk at `(x, z)`,
+    /// followed by some placeholder chunk data.
+    pub fn chunk_data(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
+        [
+            chunk_x.to_be_bytes().as_slice(),
+            &chunk_z.to_be_bytes(),
+            &[0; 32],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_set_center_chunk() {
+        let data = [VarInt::from(-3).as_slice(), VarInt::from(7).as_slice()].concat();
+
+        assert_eq!(
+            SetCenterChunk::decode(&data).unwrap(),
+            SetCenterChunk {
+                chunk_x: -3,
+                chunk_z: 7
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_chunk_position() {
+        assert_eq!(
+            ChunkData::decode(&chunk_data(-3, 7)).unwrap(),
+            ChunkData {
+                chunk_x: -3,
+                chunk_z: 7
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_unload_chunk() {
+        assert_eq!(
+            UnloadChunk::decode(&unload_chunk_data(-3, 7)).unwrap(),
+            UnloadChunk {
+                chunk_x: -3,
+                chunk_z: 7
+            }
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::io;
+
+use crate::protocol::encoding::{read_array, read_length, Decode, VarInt};
+
+/// Node flag bits, see <https://wiki.vg/Command_Data>.
+const NODE_TYPE_MASK: u8 = 0x03;
+const EXECUTABLE: u8 = 0x04;
+const HAS_REDIRECT: u8 = 0x08;
+const HAS_SUGGESTIONS: u8 = 0x10;
+
+/// The first protocol version (1.20.3) with the `minecraft:style` argument parser, which moved
+/// the ids of every parser after it up by one.
+const STYLE_PARSER_VERSION: i32 = 765;
+
+/// The id of the `minecraft:style` parser, from [`STYLE_PARSER_VERSION`] onwards.
+const STYLE_PARSER: i32 = 18;
+
+/// What a [`CommandNode`] matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The root of the tree, whose children are the commands.
+    Root,
+    /// A fixed word, such as a command's name.
+    Literal(String),
+    /// A value the player fills in, parsed by the parser with the given id in the
+    /// `minecraft:command_argument_type` registry.
+    Argument { name: String, parser: i32 },
+}
+
+/// A node of the command tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    /// The indices of the nodes which may follow this one.
+    pub children: Vec<usize>,
+    /// Whether the command is complete if it ends at this node.
+    pub executable: bool,
+    /// The index of the node parsing continues from, for aliases such as `/tp` to `/teleport`.
+    pub redirect: Option<usize>,
+}
+
+/// The clientbound Commands packet, holding the tree of commands the player may send.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Commands {
+    pub nodes: Vec<CommandNode>,
+    /// The index of the root node.
+    pub root: usize,
+}
+
+impl Commands {
+    /// Decodes a Commands packet body, whose parser ids are those of `protocol_version`.
+    ///
+    /// Parser properties are skipped rather than kept, so an argument using a parser with
+    /// properties we do not know the layout of is an error.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let nodes = read_array(&mut data, |data| read_node(data, protocol_version))?;
+        let root = read_index(&mut data)?;
+
+        let in_range = |index: &usize| *index < nodes.len();
+        let valid = in_range(&root)
+            && nodes.iter().all(|node| {
+                node.children.iter().all(in_range) && node.redirect.iter().all(in_range)
+            });
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "command node index out of range",
+            ));
+        }
+
+        Ok(Self { nodes, root })
+    }
+
+    /// Retrieves the names of the commands, which are the literals under the root node.
+    pub fn commands(&self) -> Vec<&str> {
+        let Some(root) = self.nodes.get(self.root) else {
+            return Vec::new();
+        };
+
+        root.children
+            .iter()
+            .filter_map(|&child| match &self.nodes[child].kind {
+                NodeKind::Literal(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Reads a single node of the tree, sent on `protocol_version`.
+fn read_node(data: &mut &[u8], protocol_version: i32) -> io::Result<CommandNode> {
+    let flags = u8::decode(data)?;
+    let children = read_array(data, read_index)?;
+    let redirect = if flags & HAS_REDIRECT == 0 {
+        None
+    } else {
+        Some(read_index(data)?)
+    };
+
+    let kind = match flags & NODE_TYPE_MASK {
+        0 => NodeKind::Root,
+        1 => NodeKind::Literal(String::decode(data)?),
+        2 => {
+            let name = String::decode(data)?;
+            let parser = i32::from(VarInt::decode(data)?);
+            skip_properties(data, parser, protocol_version)?;
+            if flags & HAS_SUGGESTIONS != 0 {
+                String::decode(data)?;
+            }
+
+            NodeKind::Argument { name, parser }
+        }
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid command node type {other}"),
+            ))
+        }
+    };
+
+    Ok(CommandNode {
+        kind,
+        children,
+        executable: flags & EXECUTABLE != 0,
+        redirect,
+    })
+}
+
+/// Reads a var-int node index.
+fn read_index(data: &mut &[u8]) -> io::Result<usize> {
+    read_length(data)
+}
+
+/// Skips past the properties of the argument `parser` (an id on `protocol_version`), whose layout
+/// depends on the parser.
+fn skip_properties(data: &mut &[u8], parser: i32, protocol_version: i32) -> io::Result<()> {
+    /// Skips the flags of a numeric range, then the minimum and maximum it says are present.
+    fn skip_range<T: Decode>(data: &mut &[u8]) -> io::Result<()> {
+        let flags = u8::decode(data)?;
+        if flags & 0x01 != 0 {
+            T::decode(data)?;
+        }
+        if flags & 0x02 != 0 {
+            T::decode(data)?;
+        }
+
+        Ok(())
+    }
+
+    // look parsers up by their protocol 764 ids, which have no minecraft:style
+    let parser = match parser {
+        STYLE_PARSER if protocol_version >= STYLE_PARSER_VERSION => return Ok(()),
+        STYLE_PARSER.. if protocol_version >= STYLE_PARSER_VERSION => parser - 1,
+        _ => parser,
+    };
+
+    match parser {
+        // brigadier:float, brigadier:double, brigadier:integer, brigadier:long
+        1 => skip_range::<f32>(data),
+        2 => skip_range::<f64>(data),
+        3 => skip_range::<i32>(data),
+        4 => skip_range::<i64>(data),
+        // brigadier:string's kind
+        5 => VarInt::decode(data).map(drop),
+        // minecraft:entity and minecraft:score_holder's flags
+        6 | 29 => u8::decode(data).map(drop),
+        // minecraft:time's minimum
+        40 => i32::decode(data).map(drop),
+        // minecraft:resource_or_tag, resource_or_tag_key, resource and resource_key's registry
+        41..=44 => String::decode(data).map(drop),
+        // the remaining parsers have no properties
+        0..=48 => Ok(()),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown command argument parser {other}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{Commands, NodeKind};
+    use crate::protocol::encoding::EncodedString;
+
+    /// Builds the body of a Commands packet for `/help` and `/give <amount>`, where the amount
+    /// is an integer with a minimum of 1.
+    pub fn commands_data() -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        [
+            // four nodes, starting with the root
+            [4, 0x00, 2, 1, 2].as_slice(),
+            // executable `help`, with no children
+            &[0x05, 0],
+            &string("help"),
+            // `give`, followed by the argument
+            &[0x01, 1, 3],
+            &string("give"),
+            // executable integer `amount`, with a minimum
+            &[0x06, 0],
+            &string("amount"),
+            &[3, 0x01],
+            &1i32.to_be_bytes(),
+            // the root's index
+            &[0],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_command_tree() {
+        let commands = Commands::decode(&commands_data(), 764).unwrap();
+
+        assert_eq!(commands.commands(), ["help", "give"]);
+        assert_eq!(commands.nodes.len(), 4);
+        assert_eq!(commands.nodes[2].children, [3]);
+        assert_eq!(
+            commands.nodes[3].kind,
+            NodeKind::Argument {
+                name: "amount".to_string(),
+                parser: 3
+            }
+        );
+        assert!(commands.nodes[3].executable);
+    }
+
+    #[test]
+    fn rejects_out_of_range_children() {
+        let data = [1, 0x00, 1, 5, 0];
+        assert!(Commands::decode(&data, 764).is_err());
+    }
+
+    #[test]
+    fn looks_up_parsers_by_protocol_version() {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+        // `/wait <duration>`, where the duration is a minecraft:time with a minimum of 0, and
+        // the root comes last
+        let data = |parser| {
+            [
+                [3, 0x01, 1, 1].as_slice(),
+                &string("wait"),
+                &[0x06, 0],
+                &string("duration"),
+                &[parser],
+                &0i32.to_be_bytes(),
+                &[0x00, 1, 0],
+                &[2],
+            ]
+            .concat()
+        };
+        let commands = |parser, protocol_version| {
+            Commands::decode(&data(parser), protocol_version)
+                .map(|commands| commands.commands().join(" "))
+                .ok()
+        };
+
+        assert_eq!(commands(40, 764).as_deref(), Some("wait"));
+        assert_eq!(commands(41, 765).as_deref(), Some("wait"));
+        // 40 is minecraft:gamemode on 765, which has no minimum to skip
+        assert_ne!(commands(40, 765).as_deref(), Some("wait"));
+    }
+}
@@ -0,0 +1,162 @@
+use std::io;
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{Decode, VarInt},
+    Packet,
+};
+
+/// The clientbound Set Health packet, sent when the player's health, food, or saturation changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SetHealth {
+    /// The player's health, from 0 (dead) to 20 (full).
+    pub health: f32,
+    /// The player's food level, from 0 to 20.
+    pub food: i32,
+    pub saturation: f32,
+}
+
+impl SetHealth {
+    /// Decodes a Set Health packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            health: f32::decode(&mut data)?,
+            food: i32::from(VarInt::decode(&mut data)?),
+            saturation: f32::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound Set Experience packet, sent when the player's experience changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SetExperience {
+    /// How full the experience bar is, from 0 to 1.
+    pub bar: f32,
+    pub level: i32,
+    /// The experience gained in total.
+    pub total: i32,
+}
+
+impl SetExperience {
+    /// Decodes a Set Experience packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let bar = f32::decode(&mut data)?;
+        let level = i32::from(VarInt::decode(&mut data)?);
+        let total = i32::from(VarInt::decode(&mut data)?);
+
+        Ok(Self { bar, level, total })
+    }
+}
+
+/// The clientbound Combat Death packet, sent when the player dies and is shown the death screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombatDeath {
+    /// The entity id of the player who died.
+    pub player_id: i32,
+    /// The death message (e.g., "Steve fell from a high place").
+    pub message: ChatComponent,
+}
+
+impl CombatDeath {
+    /// Decodes a Combat Death packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            player_id: i32::from(VarInt::decode(&mut data)?),
+            message: ChatComponent::decode(&mut data, protocol_version)?,
+        })
+    }
+}
+
+/// The serverbound Client Command packet, which asks the server to respawn the player or to send
+/// their statistics.
+pub struct ClientCommand {
+    /// If the player is respawned (0) or their statistics are requested (1).
+    pub action: i32,
+}
+
+impl ClientCommand {
+    /// The command respawning the player after they died.
+    pub const PERFORM_RESPAWN: Self = Self { action: 0 };
+    /// The command asking the server for the player's statistics, which it answers with
+    /// [`Statistics`](super::Statistics).
+    pub const REQUEST_STATISTICS: Self = Self { action: 1 };
+}
+
+/// Implement conversion from `ClientCommand` -> `Packet`
+impl From<ClientCommand> for Packet {
+    fn from(p: ClientCommand) -> Self {
+        Self::new(0x08, VarInt::from(p.action).as_slice().to_vec())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{CombatDeath, SetExperience, SetHealth};
+    use crate::protocol::{
+        chat::ChatComponent,
+        encoding::{EncodedString, VarInt},
+    };
+
+    /// Builds the body of a Combat Death packet for the player with entity id `player_id`.
+    pub fn combat_death_data(player_id: i32, message: &str) -> Vec<u8> {
+        let message = format!(r#"{{"text":"{message}"}}"#);
+
+        [
+            VarInt::from(player_id).as_slice(),
+            &EncodedString::try_from(message).unwrap().as_slice(),
+        ]
+        .concat()
+    }
+
+    /// Builds the body of a Set Health packet with the given `health` and full food.
+    pub fn set_health_data(health: f32) -> Vec<u8> {
+        [
+            health.to_be_bytes().as_slice(),
+            VarInt::from(20).as_slice(),
+            &5.0f32.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_set_health() {
+        assert_eq!(
+            SetHealth::decode(&set_health_data(4.5)).unwrap(),
+            SetHealth {
+                health: 4.5,
+                food: 20,
+                saturation: 5.0,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_set_experience() {
+        let data = [
+            0.5f32.to_be_bytes().as_slice(),
+            VarInt::from(30).as_slice(),
+            VarInt::from(1395).as_slice(),
+        ]
+        .concat();
+
+        assert_eq!(
+            SetExperience::decode(&data).unwrap(),
+            SetExperience {
+                bar: 0.5,
+                level: 30,
+                total: 1395,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_combat_death() {
+        assert_eq!(
+            CombatDeath::decode(&combat_death_data(7, "Steve fell"), 764).unwrap(),
+            CombatDeath {
+                player_id: 7,
+                message: ChatComponent::text("Steve fell"),
+            }
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::io;
+
+use crate::protocol::encoding::{read_array, Angle, Decode, VarInt};
+
+/// The clientbound packet which adds a (non-player) entity to the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnEntity {
+    pub entity_id: i32,
+    pub uuid: u128,
+    /// The id of the entity type in the `minecraft:entity_type` registry.
+    pub kind: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub pitch: Angle,
+    pub yaw: Angle,
+    /// The velocity along each axis, in units of 1/8000 of a block per tick.
+    pub velocity: [i16; 3],
+}
+
+impl SpawnEntity {
+    /// Decodes a Spawn Entity packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let entity_id = i32::from(VarInt::decode(&mut data)?);
+        let uuid = u128::decode(&mut data)?;
+        let kind = i32::from(VarInt::decode(&mut data)?);
+        let x = f64::decode(&mut data)?;
+        let y = f64::decode(&mut data)?;
+        let z = f64::decode(&mut data)?;
+        let pitch = Angle::decode(&mut data)?;
+        let yaw = Angle::decode(&mut data)?;
+        // the head yaw, and the type-specific data
+        Angle::decode(&mut data)?;
+        VarInt::decode(&mut data)?;
+        let velocity = [
+            i16::decode(&mut data)?,
+            i16::decode(&mut data)?,
+            i16::decode(&mut data)?,
+        ];
+
+        Ok(Self {
+            entity_id,
+            uuid,
+            kind,
+            x,
+            y,
+            z,
+            pitch,
+            yaw,
+            velocity,
+        })
+    }
+}
+
+/// How many units an entity position delta has per block.
+const DELTA_UNITS_PER_BLOCK: f64 = 4096.0;
+
+/// Converts a position delta, in units of 1/4096 of a block along each axis, into blocks.
+pub fn delta_to_blocks(delta: [i16; 3]) -> [f64; 3] {
+    delta.map(|units| f64::from(units) / DELTA_UNITS_PER_BLOCK)
+}
+
+/// Reads a position delta along each axis.
+fn read_delta(data: &mut &[u8]) -> io::Result<[i16; 3]> {
+    Ok([i16::decode(data)?, i16::decode(data)?, i16::decode(data)?])
+}
+
+/// The clientbound packet which moves an entity by less than 8 blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateEntityPosition {
+    pub entity_id: i32,
+    /// The distance moved along each axis, see [`delta_to_blocks`].
+    pub delta: [i16; 3],
+    pub on_ground: bool,
+}
+
+impl UpdateEntityPosition {
+    /// Decodes an Update Entity Position packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            entity_id: i32::from(VarInt::decode(&mut data)?),
+            delta: read_delta(&mut data)?,
+            on_ground: bool::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound packet which moves an entity by less than 8 blocks, and turns it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateEntityPositionAndRotation {
+    pub entity_id: i32,
+    /// The distance moved along each axis, see [`delta_to_blocks`].
+    pub delta: [i16; 3],
+    pub yaw: Angle,
+    pub pitch: Angle,
+    pub on_ground: bool,
+}
+
+impl UpdateEntityPositionAndRotation {
+    /// Decodes an Update Entity Position and Rotation packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            entity_id: i32::from(VarInt::decode(&mut data)?),
+            delta: read_delta(&mut data)?,
+            yaw: Angle::decode(&mut data)?,
+            pitch: Angle::decode(&mut data)?,
+            on_ground: bool::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound packet which turns an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateEntityRotation {
+    pub entity_id: i32,
+    pub yaw: Angle,
+    pub pitch: Angle,
+    pub on_ground: bool,
+}
+
+impl UpdateEntityRotation {
+    /// Decodes an Update Entity Rotation packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            entity_id: i32::from(VarInt::decode(&mut data)?),
+            yaw: Angle::decode(&mut data)?,
+            pitch: Angle::decode(&mut data)?,
+            on_ground: bool::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound Set Passengers packet, giving every entity riding a vehicle (e.g., a boat or
+/// a minecart).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetPassengers {
+    /// The entity id of the vehicle.
+    pub vehicle_id: i32,
+    /// The entity ids of the entities riding the vehicle, which is empty once all have got off.
+    pub passengers: Vec<i32>,
+}
+
+impl SetPassengers {
+    /// Decodes a Set Passengers packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            vehicle_id: i32::from(VarInt::decode(&mut data)?),
+            passengers: read_array(&mut data, |data| Ok(i32::from(VarInt::decode(data)?)))?,
+        })
+    }
+}
+
+/// The clientbound Remove Entities packet, sent when entities are killed or leave the player's
+/// view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoveEntities {
+    pub entity_ids: Vec<i32>,
+}
+
+impl RemoveEntities {
+    /// Decodes a Remove Entities packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            entity_ids: read_array(&mut data, |data| Ok(i32::from(VarInt::decode(data)?)))?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        delta_to_blocks, RemoveEntities, SetPassengers, SpawnEntity,
+        UpdateEntityPositionAndRotation,
+    };
+    use crate::protocol::encoding::{Angle, VarInt};
+
+    /// Builds the body of a Remove Entities packet removing `entity_ids`.
+    pub fn remove_entities_data(entity_ids: &[i32]) -> Vec<u8> {
+        let count = VarInt::from(i32::try_from(entity_ids.len()).unwrap());
+        let entity_ids: Vec<u8> = entity_ids
+            .iter()
+            .flat_map(|entity_id| VarInt::from(*entity_id).as_slice().to_vec())
+            .collect();
+
+        [count.as_slice(), &entity_ids].concat()
+    }
+
+    /// Builds the body of a Set Passengers packet putting the `passengers` on `vehicle_id`.
+    pub fn set_passengers_data(vehicle_id: i32, passengers: &[i32]) -> Vec<u8> {
+        let count = VarInt::from(i32::try_from(passengers.len()).unwrap());
+        let passengers: Vec<u8> = passengers
+            .iter()
+            .flat_map(|passenger| VarInt::from(*passenger).as_slice().to_vec())
+            .collect();
+
+        [
+            VarInt::from(vehicle_id).as_slice(),
+            count.as_slice(),
+            &passengers,
+        ]
+        .concat()
+    }
+
+    /// Builds the body of an Update Entity Position packet moving `entity_id` by `delta`.
+    pub fn position_delta_data(entity_id: i32, delta: [i16; 3]) -> Vec<u8> {
+        [
+            VarInt::from(entity_id).as_slice(),
+            &delta[0].to_be_bytes(),
+            &delta[1].to_be_bytes(),
+            &delta[2].to_be_bytes(),
+            &[1],
+        ]
+        .concat()
+    }
+
+    /// Builds the body of a Spawn Entity packet for the given `entity_id` at `(x, y, z)`.
+    pub fn spawn_entity_data(entity_id: i32, x: f64, y: f64, z: f64) -> Vec<u8> {
+        [
+            VarInt::from(entity_id).as_slice(),
+            &42u128.to_be_bytes(),
+            VarInt::from(7).as_slice(),
+            &x.to_be_bytes(),
+            &y.to_be_bytes(),
+            &z.to_be_bytes(),
+            &[64, 128, 0],
+            VarInt::from(0).as_slice(),
+            &8000i16.to_be_bytes(),
+            &0i16.to_be_bytes(),
+            &(-8000i16).to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_spawn_entity() {
+        let spawn = SpawnEntity::decode(&spawn_entity_data(12, 1.5, -60.0, 3.25)).unwrap();
+
+        assert_eq!(
+            spawn,
+            SpawnEntity {
+                entity_id: 12,
+                uuid: 42,
+                kind: 7,
+                x: 1.5,
+                y: -60.0,
+                z: 3.25,
+                pitch: Angle(64),
+                yaw: Angle(128),
+                velocity: [8000, 0, -8000],
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn decodes_position_and_rotation_delta() {
+        let data = [
+            VarInt::from(12).as_slice(),
+            &4096i16.to_be_bytes(),
+            &(-2048i16).to_be_bytes(),
+            &0i16.to_be_bytes(),
+            &[64, 128, 0],
+        ]
+        .concat();
+        let update = UpdateEntityPositionAndRotation::decode(&data).unwrap();
+
+        assert_eq!(
+            update,
+            UpdateEntityPositionAndRotation {
+                entity_id: 12,
+                delta: [4096, -2048, 0],
+                yaw: Angle(64),
+                pitch: Angle(128),
+                on_ground: false,
+            }
+        );
+        assert_eq!(delta_to_blocks(update.delta), [1.0, -0.5, 0.0]);
+    }
+
+    #[test]
+    fn decodes_set_passengers() {
+        assert_eq!(
+            SetPassengers::decode(&set_passengers_data(300, &[5, 7])).unwrap(),
+            SetPassengers {
+                vehicle_id: 300,
+                passengers: vec![5, 7],
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_remove_entities() {
+        assert_eq!(
+            RemoveEntities::decode(&remove_entities_data(&[3, 300])).unwrap(),
+            RemoveEntities {
+                entity_ids: vec![3, 300],
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::io;
+
+use crate::protocol::encoding::Decode;
+
+/// The [`GameEvent::event`] sent when it starts raining.
+pub const BEGIN_RAINING: u8 = 1;
+/// The [`GameEvent::event`] sent when it stops raining.
+pub const END_RAINING: u8 = 2;
+/// The [`GameEvent::event`] sent when the player's game mode changes, to the game mode in
+/// [`GameEvent::value`].
+pub const CHANGE_GAME_MODE: u8 = 3;
+
+/// A game mode a player can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl TryFrom<u8> for GameMode {
+    type Error = io::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Survival),
+            1 => Ok(Self::Creative),
+            2 => Ok(Self::Adventure),
+            3 => Ok(Self::Spectator),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid game mode {other}"),
+            )),
+        }
+    }
+}
+
+/// The clientbound Game Event packet, which signals one of many changes to the game (e.g., the
+/// weather, or the player's game mode).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameEvent {
+    /// Which change happened, such as [`BEGIN_RAINING`] or [`CHANGE_GAME_MODE`].
+    pub event: u8,
+    /// The value of the change, whose meaning depends on the event.
+    pub value: f32,
+}
+
+impl GameEvent {
+    /// Decodes a Game Event packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            event: u8::decode(&mut data)?,
+            value: f32::decode(&mut data)?,
+        })
+    }
+
+    /// Reads the game mode the player changed to, for a [`CHANGE_GAME_MODE`] event.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value is not a game mode, including if this is another event.
+    pub fn game_mode(&self) -> io::Result<GameMode> {
+        if self.event != CHANGE_GAME_MODE || self.value.fract() != 0.0 || self.value < 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("game event {} is not a game mode change", self.event),
+            ));
+        }
+
+        // the value was checked to be a small whole number above
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        GameMode::try_from(self.value.min(f32::from(u8::MAX)) as u8)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{GameEvent, GameMode, CHANGE_GAME_MODE};
+
+    /// Builds the body of a Game Event packet.
+    pub fn game_event_data(event: u8, value: f32) -> Vec<u8> {
+        [[event].as_slice(), &value.to_be_bytes()].concat()
+    }
+
+    #[test]
+    fn decodes_game_mode_change() {
+        let event = GameEvent::decode(&game_event_data(CHANGE_GAME_MODE, 1.0)).unwrap();
+
+        assert_eq!(event.event, CHANGE_GAME_MODE);
+        assert_eq!(event.game_mode().unwrap(), GameMode::Creative);
+    }
+
+    #[test]
+    fn rejects_invalid_game_modes() {
+        for value in [4.0, 0.5, -1.0] {
+            let event = GameEvent::decode(&game_event_data(CHANGE_GAME_MODE, value)).unwrap();
+            assert!(event.game_mode().is_err(), "{value} should be invalid");
+        }
+
+        let event = GameEvent::decode(&game_event_data(1, 0.0)).unwrap();
+        assert!(event.game_mode().is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::io;
+
+use super::serverbound_id;
+use crate::protocol::{encoding::VarInt, Packet};
+
+/// One of the player's hands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Main,
+    Off,
+}
+
+impl Hand {
+    /// Retrieves the id the hand is sent as.
+    fn id(self) -> i32 {
+        match self {
+            Self::Main => 0,
+            Self::Off => 1,
+        }
+    }
+}
+
+impl TryFrom<i32> for Hand {
+    type Error = io::Error;
+
+    fn try_from(id: i32) -> Result<Self, Self::Error> {
+        match id {
+            0 => Ok(Self::Main),
+            1 => Ok(Self::Off),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("hand {other} must be 0 (main) or 1 (off)"),
+            )),
+        }
+    }
+}
+
+/// How the player interacts with an entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interaction {
+    /// Uses the item in the hand on the entity (e.g., to trade with a villager).
+    Interact(Hand),
+    /// Hits the entity.
+    Attack,
+    /// Uses the item in the hand on the given point of the entity, relative to its position
+    /// (e.g., to put an item on an armour stand).
+    InteractAt { target: [f32; 3], hand: Hand },
+}
+
+impl Interaction {
+    /// Retrieves the id the kind of interaction is sent as.
+    fn id(self) -> i32 {
+        match self {
+            Self::Interact(_) => 0,
+            Self::Attack => 1,
+            Self::InteractAt { .. } => 2,
+        }
+    }
+}
+
+/// The serverbound packet which swings one of the player's arms, as when mining or attacking.
+pub struct SwingArm {
+    pub hand: Hand,
+    pub protocol_version: i32,
+}
+
+/// Implement conversion from `SwingArm` -> `Packet`
+impl From<SwingArm> for Packet {
+    fn from(p: SwingArm) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x32, 0x33),
+            VarInt::from(p.hand.id()).as_slice().to_vec(),
+        )
+    }
+}
+
+/// The serverbound packet which interacts with an entity.
+pub struct InteractEntity {
+    pub entity_id: i32,
+    pub interaction: Interaction,
+    /// If the player is sneaking.
+    pub sneaking: bool,
+    pub protocol_version: i32,
+}
+
+/// Implement conversion from `InteractEntity` -> `Packet`
+impl From<InteractEntity> for Packet {
+    fn from(p: InteractEntity) -> Self {
+        let (target, hand) = match p.interaction {
+            Interaction::Interact(hand) => (None, Some(hand)),
+            Interaction::Attack => (None, None),
+            Interaction::InteractAt { target, hand } => (Some(target), Some(hand)),
+        };
+        let target: Vec<u8> = target
+            .iter()
+            .flatten()
+            .flat_map(|coordinate| coordinate.to_be_bytes())
+            .collect();
+        let hand = hand.map(|hand| VarInt::from(hand.id()));
+
+        Self::new(
+            serverbound_id(p.protocol_version, 0x12, 0x13),
+            [
+                VarInt::from(p.entity_id).as_slice(),
+                VarInt::from(p.interaction.id()).as_slice(),
+                &target,
+                hand.as_ref().map_or(&[], VarInt::as_slice),
+                &[u8::from(p.sneaking)],
+            ]
+            .concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Hand, InteractEntity, Interaction, SwingArm};
+    use crate::protocol::Packet;
+
+    #[test]
+    fn encodes_swing_arm() {
+        let packet = Packet::from(SwingArm {
+            hand: Hand::Main,
+            protocol_version: 764,
+        });
+
+        assert_eq!(packet.id(), 0x32);
+        assert_eq!(packet.data(), [0]);
+
+        let packet = Packet::from(SwingArm {
+            hand: Hand::Main,
+            protocol_version: 765,
+        });
+        assert_eq!(packet.id(), 0x33);
+    }
+
+    #[test]
+    fn encodes_attack() {
+        let packet = Packet::from(InteractEntity {
+            entity_id: 300,
+            interaction: Interaction::Attack,
+            sneaking: false,
+            protocol_version: 764,
+        });
+
+        assert_eq!(packet.id(), 0x12);
+        // the entity id as a var-int, then the attack type, without a hand
+        assert_eq!(packet.data(), [0xac, 0x02, 1, 0]);
+    }
+
+    #[test]
+    fn encodes_interaction_at_point() {
+        let packet = Packet::from(InteractEntity {
+            entity_id: 1,
+            interaction: Interaction::InteractAt {
+                target: [0.5, 1.0, 0.0],
+                hand: Hand::Off,
+            },
+            sneaking: true,
+            protocol_version: 764,
+        });
+
+        assert_eq!(
+            packet.data(),
+            [
+                [1, 2].as_slice(),
+                &0.5f32.to_be_bytes(),
+                &1.0f32.to_be_bytes(),
+                &0.0f32.to_be_bytes(),
+                &[1, 1],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn validates_hand() {
+        assert_eq!(Hand::try_from(1).unwrap(), Hand::Off);
+        assert!(Hand::try_from(2).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use std::io;
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{Decode, VarInt},
+    nbt::{read_optional_nbt, NbtFormat, NbtTag},
+    Packet,
+};
+
+/// The clientbound packet which opens a window (e.g., a chest, or a server's menu).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenScreen {
+    pub window_id: i32,
+    /// The id of the window type in the `minecraft:menu` registry.
+    pub kind: i32,
+    pub title: ChatComponent,
+}
+
+impl OpenScreen {
+    /// Decodes an Open Screen packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let window_id = i32::from(VarInt::decode(&mut data)?);
+        let kind = i32::from(VarInt::decode(&mut data)?);
+        let title = ChatComponent::decode(&mut data, protocol_version)?;
+
+        Ok(Self {
+            window_id,
+            kind,
+            title,
+        })
+    }
+}
+
+/// An item in a slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    /// The id of the item in the `minecraft:item` registry.
+    pub item_id: i32,
+    pub count: i8,
+    /// The NBT data of the item (e.g., its name or enchantments), if it has any.
+    pub nbt: Option<NbtTag>,
+}
+
+impl Slot {
+    /// Decodes a slot laid out for `protocol_version` from the start of `data`, which is `None`
+    /// if the slot is empty.
+    fn decode(data: &mut &[u8], protocol_version: i32) -> io::Result<Option<Self>> {
+        if !bool::decode(data)? {
+            return Ok(None);
+        }
+
+        let item_id = i32::from(VarInt::decode(data)?);
+        let count = i8::decode(data)?;
+        let nbt = read_optional_nbt(data, NbtFormat::for_protocol(protocol_version))?;
+
+        Ok(Some(Self {
+            item_id,
+            count,
+            nbt,
+        }))
+    }
+}
+
+/// The clientbound packet which sets the item in a single slot of a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetContainerSlot {
+    /// The window the slot is in, where 0 is the player's inventory.
+    pub window_id: i8,
+    pub state_id: i32,
+    pub slot: i16,
+    /// The item now in the slot, if it is not empty.
+    pub item: Option<Slot>,
+}
+
+impl SetContainerSlot {
+    /// Decodes a Set Container Slot packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let window_id = i8::decode(&mut data)?;
+        let state_id = i32::from(VarInt::decode(&mut data)?);
+        let slot = i16::decode(&mut data)?;
+        let item = Slot::decode(&mut data, protocol_version)?;
+
+        Ok(Self {
+            window_id,
+            state_id,
+            slot,
+            item,
+        })
+    }
+}
+
+/// The serverbound packet which closes a window.
+pub struct CloseContainer {
+    pub window_id: u8,
+}
+
+/// Implement conversion from `CloseContainer` -> `Packet`
+impl From<CloseContainer> for Packet {
+    fn from(p: CloseContainer) -> Self {
+        Self::new(0x0e, vec![p.window_id])
+    }
+}
+
+/// The serverbound packet which clicks a slot of a window.
+///
+/// The slots the click changed, and the item it left on the cursor, are sent empty; servers
+/// resynchronise the window if they disagree.
+pub struct ClickContainer {
+    pub window_id: u8,
+    /// The last state id the server sent for the window.
+    pub state_id: i32,
+    pub slot: i16,
+    /// Which button was clicked, whose meaning depends on the `mode` (e.g., 0 for a left click,
+    /// in mode 0).
+    pub button: i8,
+    /// The kind of click (e.g., 0 for a normal click, or 1 for a shift click).
+    pub mode: i32,
+}
+
+/// Implement conversion from `ClickContainer` -> `Packet`
+impl From<ClickContainer> for Packet {
+    fn from(p: ClickContainer) -> Self {
+        Self::new(
+            0x0d,
+            [
+                [p.window_id].as_slice(),
+                VarInt::from(p.state_id).as_slice(),
+                &p.slot.to_be_bytes(),
+                &p.button.to_be_bytes(),
+                VarInt::from(p.mode).as_slice(),
+                // no changed slots, and no carried item
+                VarInt::from(0).as_slice(),
+                &[0],
+            ]
+            .concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{ClickContainer, CloseContainer, OpenScreen, SetContainerSlot, Slot};
+    use crate::protocol::Packet;
+    use crate::protocol::{
+        chat::ChatComponent,
+        encoding::EncodedString,
+        nbt::{
+            read_nbt,
+            test::{compound, int, network},
+            NbtFormat,
+        },
+    };
+
+    /// Builds the body of a Set Container Slot packet putting an item in `slot` of `window_id`.
+    pub fn set_slot_data(window_id: i8, slot: i16, item_id: u8, count: i8) -> Vec<u8> {
+        [
+            window_id.to_be_bytes().as_slice(),
+            &[3],
+            &slot.to_be_bytes(),
+            &[1, item_id],
+            &count.to_be_bytes(),
+            // no NBT
+            &[0],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_open_screen() {
+        let title = EncodedString::try_from(r#"{"text":"Select a server"}"#.to_string()).unwrap();
+        let data = [[2, 5].as_slice(), &title.as_slice()].concat();
+
+        assert_eq!(
+            OpenScreen::decode(&data, 764).unwrap(),
+            OpenScreen {
+                window_id: 2,
+                kind: 5,
+                title: ChatComponent::text("Select a server"),
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_set_slot() {
+        assert_eq!(
+            SetContainerSlot::decode(&set_slot_data(2, 13, 0x2a, 1), 764).unwrap(),
+            SetContainerSlot {
+                window_id: 2,
+                state_id: 3,
+                slot: 13,
+                item: Some(Slot {
+                    item_id: 0x2a,
+                    count: 1,
+                    nbt: None,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_emptied_slot() {
+        let data = [[0, 3].as_slice(), &36i16.to_be_bytes(), &[0]].concat();
+
+        assert_eq!(SetContainerSlot::decode(&data, 764).unwrap().item, None);
+    }
+
+    #[test]
+    fn decodes_item_nbt() {
+        let nbt = network(compound(vec![("Damage", int(3))]));
+        // an item, then whatever follows the slot in its packet
+        let data = [[1, 0x2a, 1].as_slice(), &nbt, &[0x7f]].concat();
+        let mut remaining = data.as_slice();
+
+        let item = Slot::decode(&mut remaining, 764).unwrap().unwrap();
+        assert_eq!(
+            item.nbt,
+            Some(read_nbt(&mut nbt.as_slice(), NbtFormat::Network).unwrap())
+        );
+        assert_eq!(remaining, [0x7f]);
+    }
+
+    #[test]
+    fn builds_left_click() {
+        let packet = Packet::from(ClickContainer {
+            window_id: 2,
+            state_id: 3,
+            slot: 13,
+            button: 0,
+            mode: 0,
+        });
+
+        assert_eq!(packet.id(), 0x0d);
+        assert_eq!(packet.data(), [2, 3, 0, 13, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn builds_close() {
+        let packet = Packet::from(CloseContainer { window_id: 2 });
+
+        assert_eq!(packet.id(), 0x0e);
+        assert_eq!(packet.data(), [2]);
+    }
+}
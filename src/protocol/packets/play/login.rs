@@ -0,0 +1,174 @@
+use std::io;
+
+use super::GameMode;
+use crate::protocol::encoding::{read_array, Decode, VarInt};
+
+/// Bits of [`Respawn::data_kept`], set for each kind of the player's data which they keep across
+/// the respawn, rather than starting afresh.
+pub const KEEP_ATTRIBUTES: u8 = 0x01;
+pub const KEEP_METADATA: u8 = 0x02;
+
+/// The clientbound Login (play) packet, sent as the player joins the world. Only the fields up
+/// to the dimension the player is in are decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Login {
+    /// The player's entity id.
+    pub entity_id: i32,
+    pub hardcore: bool,
+    /// How many chunks out from the player the server sends the world.
+    pub view_distance: i32,
+    /// The name of the dimension type of the player's dimension, in the registry sent during
+    /// configuration (e.g., `minecraft:overworld`).
+    pub dimension_type: String,
+    /// The name of the player's dimension (e.g., `minecraft:the_nether`).
+    pub dimension_name: String,
+}
+
+impl Login {
+    /// Decodes a Login (play) packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let entity_id = i32::decode(&mut data)?;
+        let hardcore = bool::decode(&mut data)?;
+        // the names of every dimension
+        read_array(&mut data, String::decode)?;
+        // the max players, which is ignored
+        VarInt::decode(&mut data)?;
+        let view_distance = i32::from(VarInt::decode(&mut data)?);
+        // the simulation distance
+        VarInt::decode(&mut data)?;
+        // reduced debug info, the respawn screen, and limited crafting
+        for _ in 0..3 {
+            bool::decode(&mut data)?;
+        }
+
+        Ok(Self {
+            entity_id,
+            hardcore,
+            view_distance,
+            dimension_type: String::decode(&mut data)?,
+            dimension_name: String::decode(&mut data)?,
+        })
+    }
+}
+
+/// The clientbound Respawn packet, sent when the player respawns after dying or moves to another
+/// dimension, after which the server sends the world around them afresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Respawn {
+    /// The name of the dimension type of the player's new dimension, in the registry sent during
+    /// configuration (e.g., `minecraft:the_nether`).
+    pub dimension_type: String,
+    /// The name of the player's new dimension (e.g., `minecraft:the_nether`).
+    pub dimension_name: String,
+    pub game_mode: GameMode,
+    /// What the player keeps across the respawn, see [`KEEP_ATTRIBUTES`] and [`KEEP_METADATA`].
+    pub data_kept: u8,
+}
+
+impl Respawn {
+    /// Decodes a Respawn packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let dimension_type = String::decode(&mut data)?;
+        let dimension_name = String::decode(&mut data)?;
+        // the hashed seed
+        i64::decode(&mut data)?;
+        let game_mode = GameMode::try_from(u8::decode(&mut data)?)?;
+        // the previous game mode, and if the world is a debug or a superflat world
+        for _ in 0..3 {
+            u8::decode(&mut data)?;
+        }
+        // where the player last died, if they have
+        if bool::decode(&mut data)? {
+            String::decode(&mut data)?;
+            i64::decode(&mut data)?;
+        }
+        // the portal cooldown
+        VarInt::decode(&mut data)?;
+
+        Ok(Self {
+            dimension_type,
+            dimension_name,
+            game_mode,
+            data_kept: u8::decode(&mut data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{Login, Respawn, KEEP_ATTRIBUTES};
+    use crate::protocol::{encoding::EncodedString, packets::play::GameMode};
+
+    /// Builds the body of a Login (play) packet putting the player in the overworld, whose
+    /// dimension type is `dimension_type`.
+    pub fn login_data(dimension_type: &str) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        [
+            7i32.to_be_bytes().as_slice(),
+            &[0, 1],
+            &string("minecraft:overworld"),
+            &[20, 10, 10, 0, 1, 0],
+            &string(dimension_type),
+            &string("minecraft:overworld"),
+            // the hashed seed and the rest, which are not decoded
+            &[0; 8],
+            &[0, 0xff, 0, 0, 0, 0],
+        ]
+        .concat()
+    }
+
+    /// Builds the body of a Respawn packet moving the player to the dimension `dimension_name`, of
+    /// the dimension type `dimension_type`, in creative mode.
+    pub fn respawn_data(dimension_type: &str, dimension_name: &str, data_kept: u8) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        [
+            string(dimension_type).as_slice(),
+            &string(dimension_name),
+            &[0; 8],
+            // creative, previously survival, in neither a debug nor a superflat world
+            &[1, 0, 0, 0],
+            // the player last died in the overworld
+            &[1],
+            &string("minecraft:overworld"),
+            &[0; 8],
+            &[0],
+            &[data_kept],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_respawn() {
+        let data = respawn_data(
+            "minecraft:the_nether",
+            "minecraft:the_nether",
+            KEEP_ATTRIBUTES,
+        );
+
+        assert_eq!(
+            Respawn::decode(&data).unwrap(),
+            Respawn {
+                dimension_type: "minecraft:the_nether".to_string(),
+                dimension_name: "minecraft:the_nether".to_string(),
+                game_mode: GameMode::Creative,
+                data_kept: KEEP_ATTRIBUTES,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_login() {
+        assert_eq!(
+            Login::decode(&login_data("minecraft:overworld")).unwrap(),
+            Login {
+                entity_id: 7,
+                hardcore: false,
+                view_distance: 10,
+                dimension_type: "minecraft:overworld".to_string(),
+                dimension_name: "minecraft:overworld".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! The packets of the Play state.
+//!
+//! Packets are laid out as on protocol 764 (1.20.2), see
+//! [`PROTOCOL_VERSION`](crate::protocol::PROTOCOL_VERSION), and 765 (1.20.3), whose ids from Reset
+//! Score (0x42) onwards moved.
+
+use std::io;
+
+use crate::protocol::{encoding::Decode, Packet};
+
+/// The first protocol version (1.20.3) with Reset Score and Change Container Slot State, which
+/// moved the ids of every clientbound and serverbound packet after them respectively.
+const RENUMBERED_VERSION: i32 = 765;
+
+/// Finds the id of a serverbound packet on `protocol_version`, given its id on protocol 764 and
+/// from 765 onwards respectively.
+pub(crate) fn serverbound_id(protocol_version: i32, old_id: i32, new_id: i32) -> i32 {
+    if protocol_version >= RENUMBERED_VERSION {
+        new_id
+    } else {
+        old_id
+    }
+}
+
+pub(crate) mod block;
+pub use block::{AcknowledgeBlockChange, BlockUpdate, DiggingStatus, Face, PlayerAction};
+
+pub(crate) mod chat;
+pub use chat::{ChatCommand, ChatMessage, MessageAcknowledgment, PlayerChat, SystemChat};
+
+pub(crate) mod commands;
+pub use commands::{CommandNode, Commands, NodeKind};
+
+pub(crate) mod chunk;
+pub use chunk::{ChunkData, SetCenterChunk, UnloadChunk};
+
+pub(crate) mod death;
+pub use death::{ClientCommand, CombatDeath, SetExperience, SetHealth};
+
+pub(crate) mod entity;
+pub use entity::{
+    delta_to_blocks, RemoveEntities, SetPassengers, SpawnEntity, UpdateEntityPosition,
+    UpdateEntityPositionAndRotation, UpdateEntityRotation,
+};
+
+mod interact;
+pub use interact::{Hand, InteractEntity, Interaction, SwingArm};
+
+pub(crate) mod game_event;
+pub use game_event::{GameEvent, GameMode};
+
+pub(crate) mod inventory;
+pub use inventory::{ClickContainer, CloseContainer, OpenScreen, SetContainerSlot, Slot};
+
+pub(crate) mod player_info;
+pub use player_info::{PlayerInfoEntry, PlayerInfoRemove, PlayerInfoUpdate};
+
+pub(crate) mod login;
+pub use login::{Login, Respawn, KEEP_ATTRIBUTES, KEEP_METADATA};
+
+mod plugin_message;
+pub use plugin_message::PluginMessage;
+
+pub(crate) mod position;
+pub use position::{
+    ConfirmTeleportation, SetDefaultSpawnPosition, SetPlayerRotation, SynchronizePlayerPosition,
+};
+
+pub(crate) mod suggestions;
+pub use suggestions::{CommandSuggestions, CommandSuggestionsRequest, Suggestion};
+
+pub(crate) mod resource_pack;
+pub use resource_pack::{ResourcePack, ResourcePackResponse};
+
+pub(crate) mod server_data;
+pub use server_data::ServerData;
+
+mod statistics;
+pub use statistics::Statistics;
+
+pub(crate) mod title;
+pub use title::{SetActionBarText, SetSubtitleText, SetTitleText};
+
+/// The clientbound packets of the Play state which we handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clientbound {
+    /// Brackets a group of packets which are handled together, once all of them have arrived.
+    BundleDelimiter,
+    SpawnEntity,
+    AcknowledgeBlockChange,
+    BlockUpdate,
+    SetContainerSlot,
+    CommandSuggestions,
+    Commands,
+    Disconnect,
+    GameEvent,
+    KeepAlive,
+    ChunkData,
+    Login,
+    UpdateEntityPosition,
+    UpdateEntityPositionAndRotation,
+    UpdateEntityRotation,
+    OpenScreen,
+    PlayerChat,
+    CombatDeath,
+    PlayerInfoRemove,
+    PlayerInfoUpdate,
+    Ping,
+    SynchronizePlayerPosition,
+    RemoveEntities,
+    ResourcePack,
+    Respawn,
+    ServerData,
+    SetActionBarText,
+    SetCenterChunk,
+    SetDefaultSpawnPosition,
+    SetExperience,
+    SetHealth,
+    SetPassengers,
+    SetSubtitleText,
+    SetTitleText,
+    Statistics,
+    SystemChat,
+    UnloadChunk,
+    /// Decoded as [`UpdateTags`](crate::protocol::packets::configuration::UpdateTags), as during
+    /// configuration.
+    UpdateTags,
+}
+
+impl Clientbound {
+    /// The ids of each packet, for protocol 764 and for 765 respectively.
+    const IDS: [(Self, [i32; 2]); 38] = [
+        (Self::BundleDelimiter, [0x00, 0x00]),
+        (Self::SpawnEntity, [0x01, 0x01]),
+        (Self::Statistics, [0x04, 0x04]),
+        (Self::AcknowledgeBlockChange, [0x05, 0x05]),
+        (Self::BlockUpdate, [0x09, 0x09]),
+        (Self::CommandSuggestions, [0x10, 0x10]),
+        (Self::Commands, [0x11, 0x11]),
+        (Self::SetContainerSlot, [0x15, 0x15]),
+        (Self::Disconnect, [0x1b, 0x1b]),
+        (Self::UnloadChunk, [0x1f, 0x1f]),
+        (Self::GameEvent, [0x20, 0x20]),
+        (Self::KeepAlive, [0x24, 0x24]),
+        (Self::ChunkData, [0x25, 0x25]),
+        (Self::Login, [0x29, 0x29]),
+        (Self::UpdateEntityPosition, [0x2c, 0x2c]),
+        (Self::UpdateEntityPositionAndRotation, [0x2d, 0x2d]),
+        (Self::UpdateEntityRotation, [0x2e, 0x2e]),
+        (Self::OpenScreen, [0x31, 0x31]),
+        (Self::Ping, [0x33, 0x33]),
+        (Self::PlayerChat, [0x37, 0x37]),
+        (Self::CombatDeath, [0x3a, 0x3a]),
+        (Self::PlayerInfoRemove, [0x3b, 0x3b]),
+        (Self::PlayerInfoUpdate, [0x3c, 0x3c]),
+        (Self::SynchronizePlayerPosition, [0x3e, 0x3e]),
+        (Self::RemoveEntities, [0x40, 0x40]),
+        (Self::ResourcePack, [0x42, 0x44]),
+        (Self::Respawn, [0x43, 0x45]),
+        (Self::ServerData, [0x47, 0x49]),
+        (Self::SetActionBarText, [0x48, 0x4a]),
+        (Self::SetCenterChunk, [0x50, 0x52]),
+        (Self::SetDefaultSpawnPosition, [0x52, 0x54]),
+        (Self::SetExperience, [0x58, 0x5a]),
+        (Self::SetHealth, [0x59, 0x5b]),
+        (Self::SetPassengers, [0x5b, 0x5d]),
+        (Self::SetSubtitleText, [0x5f, 0x61]),
+        (Self::SetTitleText, [0x61, 0x63]),
+        (Self::SystemChat, [0x67, 0x69]),
+        (Self::UpdateTags, [0x70, 0x74]),
+    ];
+
+    /// Whether the connection can not go on without handling the packet, as the server kicks
+    /// the player if it goes unanswered, or it ends or starts play.
+    pub fn is_critical(self) -> bool {
+        matches!(
+            self,
+            Self::Disconnect | Self::Login | Self::KeepAlive | Self::SynchronizePlayerPosition
+        )
+    }
+
+    /// Picks which of the ids in [`IDS`](Self::IDS) are used on `protocol_version`.
+    fn column(protocol_version: i32) -> usize {
+        usize::from(protocol_version >= RENUMBERED_VERSION)
+    }
+
+    /// Finds the packet with the given `id` on `protocol_version`, if it is one we handle.
+    pub fn from_id(id: i32, protocol_version: i32) -> Option<Self> {
+        let column = Self::column(protocol_version);
+
+        Self::IDS
+            .iter()
+            .find(|(_, ids)| ids[column] == id)
+            .map(|(packet, _)| *packet)
+    }
+
+    /// Retrieves the id of the packet on `protocol_version`.
+    pub fn id(self, protocol_version: i32) -> i32 {
+        Self::IDS
+            .iter()
+            .find(|(packet, _)| *packet == self)
+            .map(|(_, ids)| ids[Self::column(protocol_version)])
+            .expect("every packet has an id")
+    }
+}
+
+/// The keep alive the server regularly sends during play, which must be echoed back.
+pub struct KeepAlive {
+    pub id: i64,
+    pub protocol_version: i32,
+}
+
+impl KeepAlive {
+    /// Decodes a clientbound Keep Alive packet body, received on `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            id: i64::decode(&mut data)?,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `KeepAlive` -> `Packet`, as the serverbound response
+impl From<KeepAlive> for Packet {
+    fn from(p: KeepAlive) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x14, 0x15),
+            p.id.to_be_bytes().to_vec(),
+        )
+    }
+}
+
+/// The ping the server sends during play, which must be answered with a pong.
+pub struct Ping {
+    pub id: i32,
+    pub protocol_version: i32,
+}
+
+impl Ping {
+    /// Decodes a clientbound Ping packet body, received on `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            id: i32::decode(&mut data)?,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `Ping` -> `Packet`, as the serverbound Pong response
+impl From<Ping> for Packet {
+    fn from(p: Ping) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x23, 0x24),
+            p.id.to_be_bytes().to_vec(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Clientbound, KeepAlive};
+    use crate::protocol::Packet;
+
+    #[test]
+    fn round_trips_ids() {
+        for (packet, [old_id, new_id]) in Clientbound::IDS {
+            assert_eq!(Clientbound::from_id(old_id, 764), Some(packet));
+            assert_eq!(packet.id(764), old_id);
+            assert_eq!(Clientbound::from_id(new_id, 765), Some(packet));
+            assert_eq!(packet.id(765), new_id);
+        }
+    }
+
+    #[test]
+    fn answers_keep_alive_for_protocol() {
+        for (protocol_version, id) in [(764, 0x14), (765, 0x15)] {
+            let keep_alive = KeepAlive::decode(&7i64.to_be_bytes(), protocol_version).unwrap();
+            let packet = Packet::from(keep_alive);
+
+            assert_eq!(packet.id(), id);
+            assert_eq!(packet.data(), 7i64.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn moves_ids_from_protocol_765() {
+        assert_eq!(Clientbound::ResourcePack.id(765), 0x44);
+        assert_eq!(Clientbound::Disconnect.id(765), 0x1b);
+        // Reset Score, then Remove Resource Pack
+        assert_eq!(Clientbound::from_id(0x42, 765), None);
+        assert_eq!(Clientbound::from_id(0x43, 765), None);
+    }
+}
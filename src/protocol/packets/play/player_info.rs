@@ -0,0 +1,190 @@
+use std::io;
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{read_array, Decode, VarInt},
+    packets::login::ProfileProperty,
+};
+
+/// The action of a [`PlayerInfoUpdate`] which adds players to the tab list.
+const ADD_PLAYER: u8 = 0x01;
+/// The action of a [`PlayerInfoUpdate`] which sets the players' chat session.
+const INITIALIZE_CHAT: u8 = 0x02;
+/// The action of a [`PlayerInfoUpdate`] which sets the players' game mode.
+const UPDATE_GAME_MODE: u8 = 0x04;
+/// The action of a [`PlayerInfoUpdate`] which sets if the players are shown in the tab list.
+const UPDATE_LISTED: u8 = 0x08;
+/// The action of a [`PlayerInfoUpdate`] which sets the players' latency.
+const UPDATE_LATENCY: u8 = 0x10;
+/// The action of a [`PlayerInfoUpdate`] which sets the players' tab list name.
+const UPDATE_DISPLAY_NAME: u8 = 0x20;
+
+/// A player in a [`PlayerInfoUpdate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoEntry {
+    pub uuid: u128,
+    /// The name of the player, if they are being added to the tab list.
+    pub name: Option<String>,
+}
+
+/// The clientbound packet which adds players to the tab list, or updates them.
+///
+/// Only the names of added players are decoded; the data of the other actions is skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoUpdate {
+    pub players: Vec<PlayerInfoEntry>,
+}
+
+impl PlayerInfoUpdate {
+    /// Decodes a Player Info Update packet body, whose display names are NBT from protocol 765.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let actions = u8::decode(&mut data)?;
+
+        let players = read_array(&mut data, |data| {
+            let uuid = u128::decode(data)?;
+
+            // the data of each action follows in bit order
+            let mut name = None;
+            if actions & ADD_PLAYER != 0 {
+                name = Some(String::decode(data)?);
+                read_array(data, ProfileProperty::decode)?;
+            }
+            if actions & INITIALIZE_CHAT != 0 && bool::decode(data)? {
+                // the chat session id, public key expiry, public key, and its signature
+                u128::decode(data)?;
+                i64::decode(data)?;
+                read_array(data, u8::decode)?;
+                read_array(data, u8::decode)?;
+            }
+            if actions & UPDATE_GAME_MODE != 0 {
+                VarInt::decode(data)?;
+            }
+            if actions & UPDATE_LISTED != 0 {
+                bool::decode(data)?;
+            }
+            if actions & UPDATE_LATENCY != 0 {
+                VarInt::decode(data)?;
+            }
+            if actions & UPDATE_DISPLAY_NAME != 0 && bool::decode(data)? {
+                ChatComponent::decode(data, protocol_version)?;
+            }
+
+            Ok(PlayerInfoEntry { uuid, name })
+        })?;
+
+        Ok(Self { players })
+    }
+}
+
+/// The clientbound packet which removes players from the tab list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoRemove {
+    pub uuids: Vec<u128>,
+}
+
+impl PlayerInfoRemove {
+    /// Decodes a Player Info Remove packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            uuids: read_array(&mut data, u128::decode)?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{PlayerInfoEntry, PlayerInfoRemove, PlayerInfoUpdate};
+    use crate::protocol::{
+        encoding::{EncodedString, VarInt},
+        nbt::test::{compound, network, string},
+    };
+
+    /// Builds the body of a Player Info Update packet adding each of `players` to the tab list,
+    /// and setting their latency.
+    pub fn add_players_data(players: &[(u128, &str)]) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        let count = VarInt::from(i32::try_from(players.len()).unwrap());
+        let mut data = [[0x11].as_slice(), count.as_slice()].concat();
+        for (uuid, name) in players {
+            data.extend_from_slice(&uuid.to_be_bytes());
+            data.extend_from_slice(&string(name));
+            // one signed profile property
+            data.extend_from_slice(&[1]);
+            data.extend_from_slice(&string("textures"));
+            data.extend_from_slice(&string("e30="));
+            data.extend_from_slice(&[1]);
+            data.extend_from_slice(&string("signature"));
+            // the latency
+            data.extend_from_slice(VarInt::from(35).as_slice());
+        }
+
+        data
+    }
+
+    #[test]
+    fn decodes_added_players() {
+        let update =
+            PlayerInfoUpdate::decode(&add_players_data(&[(1, "Steve"), (2, "Alex")]), 764).unwrap();
+
+        assert_eq!(
+            update.players,
+            [
+                PlayerInfoEntry {
+                    uuid: 1,
+                    name: Some("Steve".to_string())
+                },
+                PlayerInfoEntry {
+                    uuid: 2,
+                    name: Some("Alex".to_string())
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_other_actions() {
+        // update listed and display name, with no display name
+        let data = [[0x28, 1].as_slice(), &7u128.to_be_bytes(), &[1, 0]].concat();
+
+        assert_eq!(
+            PlayerInfoUpdate::decode(&data, 764).unwrap().players,
+            [PlayerInfoEntry {
+                uuid: 7,
+                name: None
+            }]
+        );
+    }
+
+    #[test]
+    fn skips_nbt_display_names() {
+        let display_name = network(compound(vec![("text", string("[Admin] Steve"))]));
+        let mut data = vec![0x20, 2];
+        for uuid in [1u128, 2] {
+            data.extend_from_slice(&uuid.to_be_bytes());
+            data.push(1);
+            data.extend_from_slice(&display_name);
+        }
+
+        assert_eq!(
+            PlayerInfoUpdate::decode(&data, 765).unwrap().players,
+            [
+                PlayerInfoEntry {
+                    uuid: 1,
+                    name: None
+                },
+                PlayerInfoEntry {
+                    uuid: 2,
+                    name: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_removed_players() {
+        let data = [[2].as_slice(), &1u128.to_be_bytes(), &2u128.to_be_bytes()].concat();
+
+        assert_eq!(PlayerInfoRemove::decode(&data).unwrap().uuids, [1, 2]);
+    }
+}
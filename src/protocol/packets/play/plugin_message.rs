@@ -0,0 +1,66 @@
+use std::{io, num::TryFromIntError};
+
+use super::serverbound_id;
+use crate::protocol::{
+    encoding::{is_valid_identifier, EncodedString},
+    Packet,
+};
+
+/// The serverbound packet which sends a payload on a plugin channel, for plugins and mods.
+pub struct PluginMessage {
+    channel: EncodedString,
+    data: Vec<u8>,
+    protocol_version: i32,
+}
+
+impl PluginMessage {
+    /// Creates a new Plugin Message packet sending `data` on `channel`, which must be a valid
+    /// identifier (e.g., `minecraft:brand`), on `protocol_version`.
+    pub fn new(channel: &str, data: Vec<u8>, protocol_version: i32) -> Result<Self, io::Error> {
+        if !is_valid_identifier(channel) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{channel}' is not a valid plugin channel"),
+            ));
+        }
+
+        Ok(Self {
+            channel: channel
+                .to_string()
+                .try_into()
+                .map_err(|e: TryFromIntError| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            data,
+            protocol_version,
+        })
+    }
+}
+
+/// Implement conversion from `PluginMessage` -> `Packet`
+impl From<PluginMessage> for Packet {
+    fn from(p: PluginMessage) -> Self {
+        // the data is not length-prefixed, as it runs to the end of the packet
+        Self::new(
+            serverbound_id(p.protocol_version, 0x0f, 0x10),
+            [p.channel.as_slice(), p.data].concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PluginMessage;
+    use crate::protocol::Packet;
+
+    #[test]
+    fn encodes_plugin_message() {
+        let packet = Packet::from(PluginMessage::new("my_mod:sync", vec![1, 2, 3], 764).unwrap());
+
+        assert_eq!(packet.id(), 0x0f);
+        assert_eq!(packet.data(), b"\x0bmy_mod:sync\x01\x02\x03");
+    }
+
+    #[test]
+    fn rejects_invalid_channel() {
+        assert!(PluginMessage::new("My Channel", vec![], 764).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use std::io;
+
+use super::serverbound_id;
+use crate::protocol::{
+    encoding::{Decode, Position, VarInt},
+    Packet,
+};
+
+/// Bits of [`SynchronizePlayerPosition::flags`], set for each field which is relative to the
+/// player's current position rather than absolute.
+pub const RELATIVE_X: u8 = 0x01;
+pub const RELATIVE_Y: u8 = 0x02;
+pub const RELATIVE_Z: u8 = 0x04;
+pub const RELATIVE_YAW: u8 = 0x08;
+pub const RELATIVE_PITCH: u8 = 0x10;
+
+/// The clientbound Synchronize Player Position packet, which teleports the player and must be
+/// confirmed with [`ConfirmTeleportation`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SynchronizePlayerPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// The rotation, in degrees.
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Which fields are relative, see [`RELATIVE_X`] and the other flags.
+    pub flags: u8,
+    pub teleport_id: i32,
+}
+
+impl SynchronizePlayerPosition {
+    /// Decodes a Synchronize Player Position packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            x: f64::decode(&mut data)?,
+            y: f64::decode(&mut data)?,
+            z: f64::decode(&mut data)?,
+            yaw: f32::decode(&mut data)?,
+            pitch: f32::decode(&mut data)?,
+            flags: u8::decode(&mut data)?,
+            teleport_id: i32::from(VarInt::decode(&mut data)?),
+        })
+    }
+}
+
+/// The clientbound Set Default Spawn Position packet, giving where players spawn when they join
+/// for the first time or respawn without a bed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SetDefaultSpawnPosition {
+    pub location: Position,
+    /// The yaw players face when they spawn, in degrees.
+    pub angle: f32,
+}
+
+impl SetDefaultSpawnPosition {
+    /// Decodes a Set Default Spawn Position packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            location: Position::decode(&mut data)?,
+            angle: f32::decode(&mut data)?,
+        })
+    }
+}
+
+/// The serverbound packet confirming the teleport of a [`SynchronizePlayerPosition`].
+pub struct ConfirmTeleportation {
+    pub teleport_id: i32,
+}
+
+/// Implement conversion from `ConfirmTeleportation` -> `Packet`
+impl From<ConfirmTeleportation> for Packet {
+    fn from(p: ConfirmTeleportation) -> Self {
+        Self::new(0x00, VarInt::from(p.teleport_id).as_slice().to_vec())
+    }
+}
+
+/// The serverbound packet which turns the player, without moving them.
+pub struct SetPlayerRotation {
+    /// The rotation, in degrees.
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+    pub protocol_version: i32,
+}
+
+/// Implement conversion from `SetPlayerRotation` -> `Packet`
+impl From<SetPlayerRotation> for Packet {
+    fn from(p: SetPlayerRotation) -> Self {
+        Self::new(
+            serverbound_id(p.protocol_version, 0x18, 0x19),
+            [
+                p.yaw.to_be_bytes().as_slice(),
+                &p.pitch.to_be_bytes(),
+                &[u8::from(p.on_ground)],
+            ]
+            .concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        SetDefaultSpawnPosition, SetPlayerRotation, SynchronizePlayerPosition, RELATIVE_YAW,
+    };
+    use crate::protocol::{
+        encoding::{Position, VarInt},
+        Packet,
+    };
+
+    /// Builds the body of a Set Default Spawn Position packet at `location`, facing south.
+    pub fn spawn_position_data(location: Position) -> Vec<u8> {
+        [
+            location.pack().to_be_bytes().as_slice(),
+            &0.0f32.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Builds the body of a Synchronize Player Position packet teleporting the player to `x`,
+    /// `y`, `z`, with an absolute rotation.
+    pub fn synchronize_position_data(x: f64, y: f64, z: f64, teleport_id: i32) -> Vec<u8> {
+        [
+            x.to_be_bytes().as_slice(),
+            &y.to_be_bytes(),
+            &z.to_be_bytes(),
+            &90.0f32.to_be_bytes(),
+            &0.0f32.to_be_bytes(),
+            &[0],
+            VarInt::from(teleport_id).as_slice(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_synchronize_position() {
+        let mut data = synchronize_position_data(1.5, 64.0, -3.0, 7);
+        data[32] = RELATIVE_YAW;
+
+        assert_eq!(
+            SynchronizePlayerPosition::decode(&data).unwrap(),
+            SynchronizePlayerPosition {
+                x: 1.5,
+                y: 64.0,
+                z: -3.0,
+                yaw: 90.0,
+                pitch: 0.0,
+                flags: RELATIVE_YAW,
+                teleport_id: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_spawn_position() {
+        let location = Position {
+            x: -120,
+            y: -60,
+            z: -3_000_000,
+        };
+
+        assert_eq!(
+            SetDefaultSpawnPosition::decode(&spawn_position_data(location)).unwrap(),
+            SetDefaultSpawnPosition {
+                location,
+                angle: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn encodes_player_rotation() {
+        let packet = Packet::from(SetPlayerRotation {
+            yaw: 90.0,
+            pitch: 0.0,
+            on_ground: true,
+            protocol_version: 764,
+        });
+
+        assert_eq!(packet.id(), 0x18);
+        assert_eq!(
+            packet.data(),
+            [
+                90.0f32.to_be_bytes().as_slice(),
+                &0.0f32.to_be_bytes(),
+                &[1]
+            ]
+            .concat()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::io;
+
+use super::serverbound_id;
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{Decode, VarInt},
+    packets::configuration::RESOURCE_PACK_STACK_VERSION,
+    Packet,
+};
+
+/// The clientbound packet which asks the client to download and apply a resource pack.
+///
+/// From protocol 765 onwards, this is Add Resource Pack, as servers may push several.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePack {
+    /// The id of the pack, from protocol 765 onwards, which the response names.
+    pub uuid: Option<u128>,
+    pub url: String,
+    /// The SHA-1 hash of the pack, as hex.
+    pub hash: String,
+    /// If the server kicks players who decline the pack.
+    pub forced: bool,
+    /// The message shown when asking the player to accept the pack.
+    pub prompt: Option<ChatComponent>,
+}
+
+impl ResourcePack {
+    /// Decodes a Resource Pack packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let uuid = if protocol_version >= RESOURCE_PACK_STACK_VERSION {
+            Some(u128::decode(&mut data)?)
+        } else {
+            None
+        };
+        let url = String::decode(&mut data)?;
+        let hash = String::decode(&mut data)?;
+        let forced = bool::decode(&mut data)?;
+        let prompt = if bool::decode(&mut data)? {
+            Some(ChatComponent::decode(&mut data, protocol_version)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            uuid,
+            url,
+            hash,
+            forced,
+            prompt,
+        })
+    }
+}
+
+/// The serverbound packet answering a [`ResourcePack`] request.
+pub struct ResourcePackResponse {
+    /// The id of the pack answered, from protocol 765 onwards.
+    pub uuid: Option<u128>,
+    /// If the pack was loaded (0), declined (1), failed to download (2), or accepted (3).
+    pub result: i32,
+    pub protocol_version: i32,
+}
+
+impl ResourcePackResponse {
+    /// The response declining `resource_pack`, on `protocol_version`.
+    pub fn declined(resource_pack: &ResourcePack, protocol_version: i32) -> Self {
+        Self {
+            uuid: resource_pack.uuid,
+            result: 1,
+            protocol_version,
+        }
+    }
+}
+
+/// Implement conversion from `ResourcePackResponse` -> `Packet`
+impl From<ResourcePackResponse> for Packet {
+    fn from(p: ResourcePackResponse) -> Self {
+        let uuid = p
+            .uuid
+            .map_or(Vec::new(), |uuid| uuid.to_be_bytes().to_vec());
+
+        Self::new(
+            serverbound_id(p.protocol_version, 0x27, 0x28),
+            [uuid.as_slice(), VarInt::from(p.result).as_slice()].concat(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{ResourcePack, ResourcePackResponse};
+    use crate::protocol::{chat::ChatComponent, encoding::EncodedString, Packet};
+
+    /// Builds the body of a Resource Pack packet for the pack at `url`, which the server kicks
+    /// players for declining if it is `forced`.
+    pub fn resource_pack_data(url: &str, forced: bool) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        [
+            string(url).as_slice(),
+            &string(&"0".repeat(40)),
+            &[u8::from(forced), 1],
+            &string(r#"{"text":"Please accept"}"#),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_resource_pack() {
+        assert_eq!(
+            ResourcePack::decode(
+                &resource_pack_data("https://example.com/pack.zip", true),
+                764
+            )
+            .unwrap(),
+            ResourcePack {
+                uuid: None,
+                url: "https://example.com/pack.zip".to_string(),
+                hash: "0".repeat(40),
+                forced: true,
+                prompt: Some(ChatComponent::text("Please accept")),
+            }
+        );
+    }
+
+    #[test]
+    fn names_pack_from_protocol_765() {
+        let uuid = 0x0123_4567_89ab_cdef_0123_4567_89ab_cdef_u128;
+        let url = EncodedString::try_from("https://example.com/pack.zip".to_string()).unwrap();
+        let hash = EncodedString::try_from("0".repeat(40)).unwrap();
+        // an optional pack, without a prompt
+        let data = [
+            uuid.to_be_bytes().as_slice(),
+            &url.as_slice(),
+            &hash.as_slice(),
+            &[0, 0],
+        ]
+        .concat();
+
+        let resource_pack = ResourcePack::decode(&data, 765).unwrap();
+        assert_eq!(resource_pack.uuid, Some(uuid));
+        assert!(!resource_pack.forced);
+        assert_eq!(resource_pack.prompt, None);
+
+        let response = Packet::from(ResourcePackResponse::declined(&resource_pack, 765));
+        assert_eq!(response.id(), 0x28);
+        assert_eq!(
+            response.data(),
+            [uuid.to_be_bytes().as_slice(), &[1]].concat()
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::io;
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{read_length, Decode},
+};
+
+/// The clientbound Server Data packet, describing the server once the player has joined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerData {
+    /// The message of the day.
+    pub motd: ChatComponent,
+    /// The server icon, as PNG bytes.
+    pub icon: Option<Vec<u8>>,
+    /// If the server kicks players who send unsigned chat messages.
+    pub enforces_secure_chat: bool,
+}
+
+impl ServerData {
+    /// Decodes a Server Data packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let motd = ChatComponent::decode(&mut data, protocol_version)?;
+        let icon = if bool::decode(&mut data)? {
+            let length = read_length(&mut data)?;
+            if length > data.len() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let (icon, rest) = data.split_at(length);
+            data = rest;
+            Some(icon.to_vec())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            motd,
+            icon,
+            enforces_secure_chat: bool::decode(&mut data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::ServerData;
+    use crate::protocol::{chat::ChatComponent, encoding::EncodedString};
+
+    /// Builds the body of a Server Data packet, with an icon, for a server which may enforce
+    /// secure chat.
+    pub fn server_data_data(enforces_secure_chat: bool) -> Vec<u8> {
+        [
+            EncodedString::try_from(r#"{"text":"A Minecraft Server"}"#.to_string())
+                .unwrap()
+                .as_slice()
+                .as_slice(),
+            &[1, 3, 0x89, b'P', b'N'],
+            &[u8::from(enforces_secure_chat)],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn decodes_server_data() {
+        assert_eq!(
+            ServerData::decode(&server_data_data(true), 764).unwrap(),
+            ServerData {
+                motd: ChatComponent::text("A Minecraft Server"),
+                icon: Some(vec![0x89, b'P', b'N']),
+                enforces_secure_chat: true,
+            }
+        );
+    }
+}
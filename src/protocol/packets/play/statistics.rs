@@ -0,0 +1,63 @@
+use std::{collections::HashMap, io};
+
+use crate::protocol::encoding::{read_array, Decode, VarInt};
+
+/// The clientbound Award Statistics packet, answering
+/// [`ClientCommand::REQUEST_STATISTICS`](super::ClientCommand::REQUEST_STATISTICS).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The value of each statistic, by its category id (e.g., 0 for blocks mined) and its
+    /// statistic id within the category (e.g., the block's id).
+    pub values: HashMap<(i32, i32), i32>,
+}
+
+impl Statistics {
+    /// Decodes an Award Statistics packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let values = read_array(&mut data, |data| {
+            let category = i32::from(VarInt::decode(data)?);
+            let statistic = i32::from(VarInt::decode(data)?);
+            let value = i32::from(VarInt::decode(data)?);
+
+            Ok(((category, statistic), value))
+        })?;
+
+        Ok(Self {
+            values: values.into_iter().collect(),
+        })
+    }
+
+    /// Retrieves the value of the `statistic` in `category`, which is 0 if the server sent none.
+    pub fn get(&self, category: i32, statistic: i32) -> i32 {
+        self.values
+            .get(&(category, statistic))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Statistics;
+    use crate::protocol::{packets::play::ClientCommand, Packet};
+
+    #[test]
+    fn decodes_statistics() {
+        // blocks mined of stone, then the custom statistic of jumps at 300
+        let data = [2, 0, 1, 12, 8, 22, 0xac, 0x02];
+        let statistics = Statistics::decode(&data).unwrap();
+
+        assert_eq!(statistics.values.len(), 2);
+        assert_eq!(statistics.get(0, 1), 12);
+        assert_eq!(statistics.get(8, 22), 300);
+        assert_eq!(statistics.get(8, 23), 0);
+    }
+
+    #[test]
+    fn requests_statistics() {
+        let packet = Packet::from(ClientCommand::REQUEST_STATISTICS);
+
+        assert_eq!(packet.id(), 0x08);
+        assert_eq!(packet.data(), [1]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{io, num::TryFromIntError};
+
+use crate::protocol::{
+    chat::ChatComponent,
+    encoding::{read_array, read_length, Decode, EncodedString, VarInt},
+    Packet,
+};
+
+/// The longest text the server completes.
+const MAX_TEXT_LENGTH: usize = 32_500;
+
+/// The serverbound packet which asks the server how the command being typed could be completed.
+pub struct CommandSuggestionsRequest {
+    /// The id the response is sent with, to match it to this request.
+    transaction_id: i32,
+    /// Everything typed so far, including the leading `/`.
+    text: EncodedString,
+}
+
+impl CommandSuggestionsRequest {
+    /// Creates a new Command Suggestions Request packet completing `text`, whose response is sent
+    /// with `transaction_id`.
+    pub fn new(transaction_id: i32, text: String) -> Result<Self, io::Error> {
+        if text.chars().count() > MAX_TEXT_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("text to complete must be at most {MAX_TEXT_LENGTH} characters"),
+            ));
+        }
+
+        Ok(Self {
+            transaction_id,
+            text: text
+                .try_into()
+                .map_err(|e: TryFromIntError| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+        })
+    }
+}
+
+/// Implement conversion from `CommandSuggestionsRequest` -> `Packet`
+impl From<CommandSuggestionsRequest> for Packet {
+    fn from(p: CommandSuggestionsRequest) -> Self {
+        Self::new(
+            0x0a,
+            [
+                VarInt::from(p.transaction_id).as_slice(),
+                &p.text.as_slice(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// A way the text being typed could be completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The text replacing the range of [`CommandSuggestions::start`] and
+    /// [`CommandSuggestions::length`].
+    pub text: String,
+    /// What is shown when hovering over the suggestion.
+    pub tooltip: Option<ChatComponent>,
+}
+
+/// The clientbound Command Suggestions Response packet, answering a
+/// [`CommandSuggestionsRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSuggestions {
+    pub transaction_id: i32,
+    /// Where the text the suggestions replace starts, in characters.
+    pub start: usize,
+    /// How long the text the suggestions replace is, in characters.
+    pub length: usize,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl CommandSuggestions {
+    /// Decodes a Command Suggestions Response packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        let transaction_id = i32::from(VarInt::decode(&mut data)?);
+        let start = read_length(&mut data)?;
+        let length = read_length(&mut data)?;
+        let suggestions = read_array(&mut data, |data| {
+            let text = String::decode(data)?;
+            let tooltip = if bool::decode(data)? {
+                Some(ChatComponent::decode(data, protocol_version)?)
+            } else {
+                None
+            };
+
+            Ok(Suggestion { text, tooltip })
+        })?;
+
+        Ok(Self {
+            transaction_id,
+            start,
+            length,
+            suggestions,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{CommandSuggestions, CommandSuggestionsRequest, Suggestion};
+    use crate::protocol::{
+        chat::ChatComponent,
+        encoding::EncodedString,
+        nbt::test::{compound, network, string},
+        Packet,
+    };
+
+    /// Builds the body of a Command Suggestions Response packet suggesting `give` and `gamemode`
+    /// (with a tooltip) for the `g` of `/g`.
+    pub fn suggestions_data(transaction_id: u8) -> Vec<u8> {
+        let string = |s: &str| EncodedString::try_from(s.to_string()).unwrap().as_slice();
+
+        [
+            [transaction_id, 1, 1, 2].as_slice(),
+            &string("give"),
+            &[0],
+            &string("gamemode"),
+            &[1],
+            &string(r#"{"text":"Change game mode"}"#),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn encodes_suggestions_request() {
+        let packet = Packet::from(CommandSuggestionsRequest::new(3, "/g".to_string()).unwrap());
+
+        assert_eq!(packet.id(), 0x0a);
+        assert_eq!(packet.data(), b"\x03\x02/g");
+    }
+
+    #[test]
+    fn decodes_suggestions() {
+        assert_eq!(
+            CommandSuggestions::decode(&suggestions_data(3), 764).unwrap(),
+            CommandSuggestions {
+                transaction_id: 3,
+                start: 1,
+                length: 1,
+                suggestions: vec![
+                    Suggestion {
+                        text: "give".to_string(),
+                        tooltip: None,
+                    },
+                    Suggestion {
+                        text: "gamemode".to_string(),
+                        tooltip: Some(ChatComponent::text("Change game mode")),
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_nbt_tooltips() {
+        let tooltip = network(compound(vec![("text", string("Change game mode"))]));
+        let data = [
+            [3, 1, 1, 1].as_slice(),
+            &EncodedString::try_from("gamemode".to_string())
+                .unwrap()
+                .as_slice(),
+            &[1],
+            &tooltip,
+        ]
+        .concat();
+
+        let suggestions = CommandSuggestions::decode(&data, 765).unwrap();
+        assert_eq!(
+            suggestions.suggestions,
+            [Suggestion {
+                text: "gamemode".to_string(),
+                tooltip: Some(ChatComponent::text("Change game mode")),
+            }]
+        );
+    }
+}
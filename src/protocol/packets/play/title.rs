@@ -0,0 +1,109 @@
+use std::io;
+
+use crate::protocol::chat::ChatComponent;
+
+/// The clientbound Set Title Text packet, showing large text in the middle of the screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetTitleText {
+    pub text: ChatComponent,
+}
+
+impl SetTitleText {
+    /// Decodes a Set Title Text packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            text: ChatComponent::decode(&mut data, protocol_version)?,
+        })
+    }
+}
+
+/// The clientbound Set Subtitle Text packet, showing text below the title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetSubtitleText {
+    pub text: ChatComponent,
+}
+
+impl SetSubtitleText {
+    /// Decodes a Set Subtitle Text packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            text: ChatComponent::decode(&mut data, protocol_version)?,
+        })
+    }
+}
+
+/// The clientbound Set Action Bar Text packet, showing text above the hotbar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetActionBarText {
+    pub text: ChatComponent,
+}
+
+impl SetActionBarText {
+    /// Decodes a Set Action Bar Text packet body, laid out for `protocol_version`.
+    pub fn decode(mut data: &[u8], protocol_version: i32) -> io::Result<Self> {
+        Ok(Self {
+            text: ChatComponent::decode(&mut data, protocol_version)?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{SetActionBarText, SetSubtitleText, SetTitleText};
+    use crate::protocol::{
+        chat::ChatComponent,
+        encoding::EncodedString,
+        nbt::test::{compound, network, string},
+    };
+
+    /// Builds the body of a packet holding only a chat component, as JSON `text`.
+    pub fn text_data(text: &str) -> Vec<u8> {
+        EncodedString::try_from(format!(r#"{{"text":"{text}"}}"#))
+            .unwrap()
+            .as_slice()
+    }
+
+    #[test]
+    fn decodes_title() {
+        assert_eq!(
+            SetTitleText::decode(&text_data("Welcome"), 764).unwrap(),
+            SetTitleText {
+                text: ChatComponent::text("Welcome"),
+            }
+        );
+
+        let data = network(compound(vec![
+            ("text", string("Welcome")),
+            ("color", string("gold")),
+        ]));
+        let title = SetTitleText::decode(&data, 765).unwrap();
+        assert_eq!(title.text.to_plain(), "Welcome");
+        assert_eq!(title.text.color.as_deref(), Some("gold"));
+    }
+
+    #[test]
+    fn decodes_subtitle() {
+        assert_eq!(
+            SetSubtitleText::decode(&text_data("to the server"), 764).unwrap(),
+            SetSubtitleText {
+                text: ChatComponent::text("to the server"),
+            }
+        );
+
+        let data = network(string("to the server"));
+        assert_eq!(
+            SetSubtitleText::decode(&data, 765).unwrap().text,
+            ChatComponent::text("to the server")
+        );
+    }
+
+    #[test]
+    fn decodes_action_bar() {
+        assert_eq!(
+            SetActionBarText::decode(&text_data("Mana: 30"), 764)
+                .unwrap()
+                .text,
+            ChatComponent::text("Mana: 30")
+        );
+    }
+}
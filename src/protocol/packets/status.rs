@@ -1,5 +1,10 @@
-use crate::protocol::Packet;
+use std::{io, time::Duration};
 
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::{chat::ChatComponent, encoding::Decode, Packet};
+
+/// The serverbound packet requesting the server's status, after a status handshake.
 #[derive(Default)]
 pub struct Status {}
 
@@ -9,3 +14,244 @@ impl From<Status> for Packet {
         Self::new(0x00, vec![])
     }
 }
+
+/// The id of the clientbound Status Response packet.
+pub const STATUS_RESPONSE_ID: i32 = 0x00;
+
+/// The id of the clientbound Pong Response packet, which echoes a [`PingRequest`].
+pub const PONG_RESPONSE_ID: i32 = 0x01;
+
+/// The serverbound packet which the server echoes back, to measure the latency to it.
+pub struct PingRequest {
+    pub payload: i64,
+}
+
+/// Implement conversion from `PingRequest` -> `Packet`
+impl From<PingRequest> for Packet {
+    fn from(p: PingRequest) -> Self {
+        Self::new(0x01, p.payload.to_be_bytes().to_vec())
+    }
+}
+
+/// The status of a server, as shown in the multiplayer server list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    /// The version the server runs.
+    pub version: ServerVersion,
+    /// How many players are online, if the server shares it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub players: Option<ServerPlayers>,
+    /// The message of the day.
+    #[serde(default)]
+    pub description: ChatComponent,
+    /// The server icon, as a `data:image/png;base64,` URI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    /// If the server requires chat messages to be signed (1.19.1 onwards).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforces_secure_chat: Option<bool>,
+    /// How long the server took to answer a ping after the status, if it answered.
+    #[serde(skip)]
+    pub latency: Option<Duration>,
+}
+
+/// The version a server runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerVersion {
+    /// The name of the version (e.g., `1.20.2`, or `Paper 1.20.2`).
+    pub name: String,
+    /// The protocol version the server speaks.
+    pub protocol: i32,
+}
+
+/// The players on a server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerPlayers {
+    /// The most players which may be online, or -1 if the server did not say.
+    #[serde(default = "unknown_max", deserialize_with = "deserialize_count")]
+    pub max: i64,
+    #[serde(default, deserialize_with = "deserialize_count")]
+    pub online: i64,
+    /// Some of the players which are online.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sample: Vec<PlayerSample>,
+}
+
+/// The [`ServerPlayers::max`] of a server which does not say it.
+fn unknown_max() -> i64 {
+    -1
+}
+
+/// Deserializes a player count, which some servers send as a string rather than a number.
+fn deserialize_count<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Count {
+        Number(i64),
+        Text(String),
+    }
+
+    match Count::deserialize(deserializer)? {
+        Count::Number(count) => Ok(count),
+        Count::Text(text) => text.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// A player in the [`ServerPlayers::sample`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: String,
+}
+
+impl ServerStatus {
+    /// Decodes a Status Response packet body.
+    pub fn decode(mut data: &[u8]) -> io::Result<Self> {
+        let json = String::decode(&mut data)?;
+
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Serializes the status back into the JSON of a Status Response, as a server would send it.
+    ///
+    /// The latency is not part of the status, so is left out.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("statuses always serialize, having only string keys")
+    }
+
+    /// Retrieves how many players are online, or 0 if the server does not say.
+    pub fn players_online(&self) -> i64 {
+        self.players.as_ref().map_or(0, |players| players.online)
+    }
+
+    /// Retrieves the most players which may be online, or -1 if the server does not say.
+    pub fn players_max(&self) -> i64 {
+        self.players.as_ref().map_or(-1, |players| players.max)
+    }
+
+    /// Whether the server looks like it is run through Geyser, the bridge letting Bedrock
+    /// players join Java servers, going by the name of its version.
+    ///
+    /// This is a best-effort guess, as a server may hide its software or be misnamed.
+    pub fn is_geyser(&self) -> bool {
+        let name = self.version.name.to_lowercase();
+
+        ["geyser", "floodgate"]
+            .iter()
+            .any(|marker| name.contains(marker))
+    }
+
+    /// Renders the message of the day as the two lines the server list shows it as.
+    ///
+    /// The description is split at its first newline, so a one-line message has an empty second
+    /// line, and anything after a second newline is not shown.
+    pub fn motd_lines(&self) -> (String, String) {
+        let plain = self.description.to_plain();
+        let mut lines = plain.lines();
+
+        (
+            lines.next().unwrap_or_default().to_string(),
+            lines.next().unwrap_or_default().to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ServerStatus;
+    use crate::protocol::encoding::EncodedString;
+
+    fn status_with_description(description: &str) -> ServerStatus {
+        let json = serde_json::json!({
+            "version": { "name": "1.20.2", "protocol": 764 },
+            "description": description,
+        });
+
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn decodes_status_response() {
+        let json = r#"{"version":{"name":"1.20.2","protocol":764},"players":{"max":20,"online":1,"sample":[{"name":"Steve","id":"00000000-0000-0000-0000-000000000001"}]},"description":"A Minecraft Server","enforcesSecureChat":true}"#;
+        let data = EncodedString::try_from(json.to_string())
+            .unwrap()
+            .as_slice();
+
+        let status = ServerStatus::decode(&data).unwrap();
+        assert_eq!(status.version.protocol, 764);
+        assert_eq!(status.players.unwrap().sample[0].name, "Steve");
+        assert_eq!(status.description.to_plain(), "A Minecraft Server");
+        assert_eq!(status.enforces_secure_chat, Some(true));
+    }
+
+    #[test]
+    fn splits_motd_lines() {
+        let status = status_with_description("A Minecraft Server\nNow with minigames");
+        assert_eq!(
+            status.motd_lines(),
+            (
+                "A Minecraft Server".to_string(),
+                "Now with minigames".to_string()
+            )
+        );
+
+        let status = status_with_description("A Minecraft Server");
+        assert_eq!(
+            status.motd_lines(),
+            ("A Minecraft Server".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn detects_geyser() {
+        let mut status = status_with_description("A Minecraft Server");
+        assert!(!status.is_geyser());
+
+        status.version.name = "Geyser (git-master-1a2b3c4) 1.20.2".to_string();
+        assert!(status.is_geyser());
+    }
+
+    #[test]
+    fn reads_player_counts() {
+        let status_with_players = |players: serde_json::Value| -> ServerStatus {
+            let json = serde_json::json!({
+                "version": { "name": "1.20.2", "protocol": 764 },
+                "players": players,
+            });
+
+            serde_json::from_value(json).unwrap()
+        };
+
+        let status = status_with_players(serde_json::json!({ "max": 20, "online": 3 }));
+        assert_eq!((status.players_online(), status.players_max()), (3, 20));
+
+        let status = status_with_players(serde_json::json!({ "max": "100", "online": "42" }));
+        assert_eq!((status.players_online(), status.players_max()), (42, 100));
+
+        let status = status_with_players(serde_json::json!({}));
+        assert_eq!((status.players_online(), status.players_max()), (0, -1));
+
+        let status = status_with_description("A Minecraft Server");
+        assert_eq!((status.players_online(), status.players_max()), (0, -1));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let json = r#"{"version":{"name":"Paper 1.20.2","protocol":764},"players":{"max":"100","online":2,"sample":[{"name":"Steve","id":"00000000-0000-0000-0000-000000000001"}]},"description":{"text":"A ","color":"gold","extra":["Minecraft Server"]},"favicon":"data:image/png;base64,AAAA","enforcesSecureChat":false}"#;
+        let status: ServerStatus = serde_json::from_str(json).unwrap();
+
+        let reparsed: ServerStatus = serde_json::from_str(&status.to_json()).unwrap();
+        assert_eq!(reparsed, status);
+
+        // a plain description is written back as a plain string
+        let status = status_with_description("A Minecraft Server");
+        assert_eq!(
+            status.to_json(),
+            r#"{"version":{"name":"1.20.2","protocol":764},"description":"A Minecraft Server"}"#
+        );
+    }
+}
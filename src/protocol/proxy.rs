@@ -0,0 +1,118 @@
+//! The [PROXY protocol](https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt) header,
+//! which some server frontends (e.g., `HAProxy`) require before the Minecraft handshake.
+
+use std::net::{IpAddr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+/// The signature which starts every version 2 header.
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\x00\r\nQUIT\n";
+
+/// A version of the PROXY protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocol {
+    /// The human-readable text header.
+    V1,
+    /// The binary header.
+    V2,
+}
+
+impl ProxyProtocol {
+    /// Builds the header announcing a TCP connection from `source` to `destination`.
+    ///
+    /// If only one of the addresses is IPv6, the other is sent as an IPv4-mapped IPv6 address.
+    pub fn header(self, source: SocketAddr, destination: SocketAddr) -> Vec<u8> {
+        let (source_ip, destination_ip) = match (source.ip(), destination.ip()) {
+            (IpAddr::V6(source_ip), IpAddr::V4(destination_ip)) => (
+                IpAddr::V6(source_ip),
+                IpAddr::V6(destination_ip.to_ipv6_mapped()),
+            ),
+            (IpAddr::V4(source_ip), IpAddr::V6(destination_ip)) => (
+                IpAddr::V6(source_ip.to_ipv6_mapped()),
+                IpAddr::V6(destination_ip),
+            ),
+            addresses => addresses,
+        };
+
+        match self {
+            Self::V1 => {
+                let protocol = if source_ip.is_ipv4() { "TCP4" } else { "TCP6" };
+                format!(
+                    "PROXY {protocol} {source_ip} {destination_ip} {} {}\r\n",
+                    source.port(),
+                    destination.port()
+                )
+                .into_bytes()
+            }
+            Self::V2 => {
+                // version 2, PROXY command; then TCP over IPv4 or IPv6
+                let (family, addresses) = match (source_ip, destination_ip) {
+                    (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => {
+                        (0x11, [source_ip.octets(), destination_ip.octets()].concat())
+                    }
+                    (IpAddr::V6(source_ip), IpAddr::V6(destination_ip)) => {
+                        (0x21, [source_ip.octets(), destination_ip.octets()].concat())
+                    }
+                    _ => unreachable!("addresses are mapped to the same family"),
+                };
+                let length =
+                    u16::try_from(addresses.len() + 4).expect("address block is at most 36 bytes");
+
+                [
+                    V2_SIGNATURE.as_slice(),
+                    &[0x21, family],
+                    &length.to_be_bytes(),
+                    &addresses,
+                    &source.port().to_be_bytes(),
+                    &destination.port().to_be_bytes(),
+                ]
+                .concat()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use super::ProxyProtocol;
+
+    fn addresses(source: &str, destination: &str) -> (SocketAddr, SocketAddr) {
+        (source.parse().unwrap(), destination.parse().unwrap())
+    }
+
+    #[test]
+    fn builds_v1_header() {
+        let (source, destination) = addresses("192.168.0.1:56324", "10.0.0.2:25565");
+        assert_eq!(
+            ProxyProtocol::V1.header(source, destination),
+            b"PROXY TCP4 192.168.0.1 10.0.0.2 56324 25565\r\n"
+        );
+
+        let (source, destination) = addresses("[::1]:56324", "10.0.0.2:25565");
+        assert_eq!(
+            ProxyProtocol::V1.header(source, destination),
+            b"PROXY TCP6 ::1 ::ffff:10.0.0.2 56324 25565\r\n"
+        );
+    }
+
+    #[test]
+    fn builds_v2_header() {
+        let (source, destination) = addresses("192.168.0.1:56324", "10.0.0.2:25565");
+        let header = ProxyProtocol::V2.header(source, destination);
+
+        assert_eq!(
+            header,
+            [
+                b"\r\n\r\n\x00\r\nQUIT\n".as_slice(),
+                &[0x21, 0x11, 0, 12],
+                &[192, 168, 0, 1, 10, 0, 0, 2],
+                &56324u16.to_be_bytes(),
+                &25565u16.to_be_bytes(),
+            ]
+            .concat()
+        );
+    }
+}
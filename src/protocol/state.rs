@@ -0,0 +1,54 @@
+/// The states a connection moves through, each of which has its own set of packets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    /// Before the handshake has been sent.
+    Handshaking,
+    /// Retrieving the server status, after a status handshake.
+    Status,
+    /// Logging in, after a login handshake.
+    Login,
+    /// Exchanging registries and settings, between login and play (1.20.2 onwards).
+    Configuration,
+    /// In the world.
+    Play,
+}
+
+impl State {
+    const ALL: [Self; 5] = [
+        Self::Handshaking,
+        Self::Status,
+        Self::Login,
+        Self::Configuration,
+        Self::Play,
+    ];
+
+    /// Retrieves the id the state is stored as in a [capture](super::capture).
+    pub fn to_id(self) -> u8 {
+        match self {
+            Self::Handshaking => 0,
+            Self::Status => 1,
+            Self::Login => 2,
+            Self::Configuration => 3,
+            Self::Play => 4,
+        }
+    }
+
+    /// Finds the state with the given capture `id`.
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.to_id() == id)
+    }
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Handshaking => "handshaking",
+            Self::Status => "status",
+            Self::Login => "login",
+            Self::Configuration => "configuration",
+            Self::Play => "play",
+        };
+
+        f.write_str(name)
+    }
+}
@@ -0,0 +1,257 @@
+//! Utilities for testing a [`Connection`] against a scripted, in-memory server.
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    net::{TcpListener, TcpStream},
+};
+
+use super::{
+    encoding::{EncodedString, VarInt},
+    packets::{
+        configuration::{
+            self, test::registry_codec_data, AcknowledgeFinishConfiguration, ClientBrand,
+        },
+        login,
+        play::{self, login::test::login_data},
+    },
+    Connection, Packet, State,
+};
+
+/// The server side of a connection, which a test scripts by sending and expecting packets.
+///
+/// The connection is in-memory unless the server was [accepted](MockServer::accept) over TCP.
+pub struct MockServer<S = DuplexStream> {
+    connection: Connection<S>,
+}
+
+/// How a [`MockServer`] takes a client through logging in, in [`MockServer::join`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JoinScript {
+    /// The threshold compression is enabled with before Login Success, if it is enabled.
+    pub compression_threshold: Option<i32>,
+    /// If the server asks to encrypt the connection, which the client gives up on, rather than
+    /// letting it in.
+    pub encryption: bool,
+}
+
+/// Creates a client connection speaking `protocol_version`, joined to a [`MockServer`].
+pub fn connect(protocol_version: i32) -> (Connection<DuplexStream>, MockServer) {
+    let (client, server) = tokio::io::duplex(64 * 1024);
+
+    (
+        Connection::new(client, protocol_version),
+        MockServer {
+            connection: Connection::new(server, protocol_version),
+        },
+    )
+}
+
+/// Creates a client connection which is already in the Play state, joined to a [`MockServer`].
+pub fn play() -> (Connection<DuplexStream>, MockServer) {
+    play_with_version(super::PROTOCOL_VERSION)
+}
+
+/// Creates a client connection speaking `protocol_version` which is already in the Play state,
+/// joined to a [`MockServer`].
+pub fn play_with_version(protocol_version: i32) -> (Connection<DuplexStream>, MockServer) {
+    let (client, server) = tokio::io::duplex(64 * 1024);
+
+    (
+        Connection::with_state(client, protocol_version, State::Play),
+        MockServer {
+            connection: Connection::with_state(server, protocol_version, State::Play),
+        },
+    )
+}
+
+/// Creates a client connection which has been configured with the dimension types of
+/// [`registry_codec_data`], and so is in the Play state, joined to a [`MockServer`].
+pub async fn play_configured() -> (Connection<DuplexStream>, MockServer) {
+    let protocol_version = super::PROTOCOL_VERSION;
+    let (client, server) = tokio::io::duplex(64 * 1024);
+    let mut client = Connection::with_state(client, protocol_version, State::Configuration);
+    let mut server = MockServer {
+        connection: Connection::with_state(server, protocol_version, State::Configuration),
+    };
+
+    let (configured, ()) = tokio::join!(
+        client.configure(configuration::ClientInformation::default()),
+        server.configure()
+    );
+    configured.expect("failed to configure client");
+
+    (client, server)
+}
+
+/// Builds a Login Success for the player "Steve", laid out for `protocol_version` (from 735
+/// onwards, which send the UUID as a `u128`).
+fn login_success(protocol_version: i32) -> Packet {
+    assert!(protocol_version >= login::BINARY_UUID_VERSION);
+
+    let properties: &[u8] = if protocol_version >= login::PROPERTIES_VERSION {
+        &[0]
+    } else {
+        &[]
+    };
+    let strict_error_handling: &[u8] =
+        if login::STRICT_ERROR_HANDLING_VERSIONS.contains(&protocol_version) {
+            &[0]
+        } else {
+            &[]
+        };
+    let data = [
+        [0; 16].as_slice(),
+        b"\x05Steve",
+        properties,
+        strict_error_handling,
+    ]
+    .concat();
+
+    Packet::new(login::LOGIN_SUCCESS_ID, data)
+}
+
+impl MockServer<TcpStream> {
+    /// Accepts the next connection to `listener`, speaking `protocol_version` on it.
+    pub async fn accept(listener: &TcpListener, protocol_version: i32) -> Self {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .expect("failed to accept connection");
+
+        Self {
+            connection: Connection::new(stream, protocol_version),
+        }
+    }
+}
+
+impl<S> MockServer<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Sends a packet to the client.
+    pub async fn send<P: Into<Packet>>(&mut self, packet: P) {
+        self.connection
+            .write_packet(packet)
+            .await
+            .expect("failed to send packet to client");
+    }
+
+    /// Receives the next packet the client sent.
+    pub async fn receive(&mut self) -> Packet {
+        self.connection
+            .read_packet()
+            .await
+            .expect("failed to receive packet from client")
+    }
+
+    /// Receives the next packet the client sent, asserting it has the given `id`.
+    pub async fn expect(&mut self, id: i32) -> Packet {
+        let packet = self.receive().await;
+        assert_eq!(packet.id(), id, "client sent an unexpected packet");
+
+        packet
+    }
+
+    /// Accepts a Login Start as the player "Steve", replying with Login Success (and expecting
+    /// Login Acknowledged where the protocol requires it).
+    pub async fn accept_login(&mut self) {
+        let protocol_version = self.connection.protocol_version();
+
+        self.expect(0x00).await;
+        self.send(login_success(protocol_version)).await;
+
+        if protocol_version >= login::CONFIGURATION_VERSION {
+            self.expect(0x03).await;
+        }
+    }
+
+    /// Takes the client from its login handshake to the Play state as `script` says, then
+    /// sends it Login (play) putting the player in the overworld.
+    ///
+    /// Configuration only sends the registry of dimension types, and finishes straight away. The
+    /// server must speak one of the [`PLAY_VERSIONS`](super::PLAY_VERSIONS), which all lay out
+    /// Login (play) alike.
+    pub async fn join(&mut self, script: JoinScript) {
+        let protocol_version = self.connection.protocol_version();
+        assert!(
+            super::PLAY_VERSIONS.contains(&protocol_version),
+            "play is not spoken on protocol {protocol_version}"
+        );
+
+        // the handshake, then the login start
+        self.expect(0x00).await;
+        self.expect(0x00).await;
+        if script.encryption {
+            self.send(Packet::new(
+                login::ENCRYPTION_REQUEST_ID,
+                encryption_request_data(protocol_version),
+            ))
+            .await;
+            return;
+        }
+
+        if let Some(threshold) = script.compression_threshold {
+            self.send(Packet::new(
+                login::SET_COMPRESSION_ID,
+                VarInt::from(threshold).as_slice().to_vec(),
+            ))
+            .await;
+            // a negative threshold disables compression
+            self.connection
+                .set_compression_threshold(usize::try_from(threshold).ok());
+        }
+        self.send(login_success(protocol_version)).await;
+
+        // login acknowledged, then the client brand
+        self.expect(0x03).await;
+        let brand = ClientBrand::new(String::new(), protocol_version).unwrap();
+        self.expect(Packet::from(brand).id()).await;
+        self.configure().await;
+
+        self.send(Packet::new(
+            play::Clientbound::Login.id(protocol_version),
+            login_data("minecraft:overworld"),
+        ))
+        .await;
+    }
+
+    /// Expects the client information of a client in the Configuration state, then sends it the
+    /// registry of dimension types and finishes configuration straight away.
+    pub async fn configure(&mut self) {
+        let protocol_version = self.connection.protocol_version();
+        self.expect(0x00).await;
+
+        let registry_data = configuration::Clientbound::RegistryData
+            .id(protocol_version)
+            .unwrap();
+        self.send(Packet::new(registry_data, registry_codec_data()))
+            .await;
+        let finish_configuration = configuration::Clientbound::FinishConfiguration
+            .id(protocol_version)
+            .unwrap();
+        self.send(Packet::new(finish_configuration, vec![])).await;
+        self.expect(Packet::from(AcknowledgeFinishConfiguration { protocol_version }).id())
+            .await;
+    }
+}
+
+/// Builds the body of an Encryption Request with an empty server id, laid out for
+/// `protocol_version`.
+fn encryption_request_data(protocol_version: i32) -> Vec<u8> {
+    let server_id = EncodedString::try_from(String::new()).unwrap();
+    // the public key and the verify token, then if the client must authenticate with Mojang
+    let data = [
+        server_id.as_slice().as_slice(),
+        VarInt::from(4).as_slice(),
+        &[0; 4],
+        VarInt::from(4).as_slice(),
+        &[1, 2, 3, 4],
+    ]
+    .concat();
+
+    if protocol_version >= 766 {
+        [data.as_slice(), &[1]].concat()
+    } else {
+        data
+    }
+}
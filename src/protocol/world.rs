@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    encoding::{Angle, Position},
+    packets::configuration::DimensionType,
+    packets::play::{
+        position::{RELATIVE_PITCH, RELATIVE_X, RELATIVE_Y, RELATIVE_YAW, RELATIVE_Z},
+        Commands, GameMode, Respawn, ServerData, SetExperience, SetHealth, SetPassengers,
+        SpawnEntity, SynchronizePlayerPosition,
+    },
+};
+
+/// How many chunks out from the centre chunk must be received before the world counts as
+/// loaded, so the area of 5x5 chunks around the player.
+const LOADED_RADIUS: i32 = 2;
+
+/// How many chunks further out than the view distance chunks are kept, as the vanilla client
+/// does, so chunks on its edge are not forgotten as soon as the player steps away.
+const KEPT_CHUNK_MARGIN: i32 = 3;
+
+/// An entity the server has told us about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub uuid: u128,
+    /// The id of the entity type in the `minecraft:entity_type` registry.
+    pub kind: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: Angle,
+    pub pitch: Angle,
+    /// The velocity along each axis, in units of 1/8000 of a block per tick.
+    pub velocity: [i16; 3],
+    /// Whether the entity was last said to be on the ground.
+    pub on_ground: bool,
+}
+
+impl From<&SpawnEntity> for Entity {
+    fn from(spawn: &SpawnEntity) -> Self {
+        Self {
+            uuid: spawn.uuid,
+            kind: spawn.kind,
+            x: spawn.x,
+            y: spawn.y,
+            z: spawn.z,
+            yaw: spawn.yaw,
+            pitch: spawn.pitch,
+            velocity: spawn.velocity,
+            on_ground: false,
+        }
+    }
+}
+
+/// Where the player is, and which way they face.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// The rotation, in degrees.
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// What the client knows about the world, built up from the packets received during play.
+#[derive(Debug, Default)]
+pub struct World {
+    /// The entities in the world, by entity id.
+    pub entities: HashMap<i32, Entity>,
+    /// The names of the players in the tab list, by UUID.
+    pub players: HashMap<u128, String>,
+    /// The chunk the client's loaded area is centred on.
+    pub center_chunk: (i32, i32),
+    /// The positions of the chunks which have been received, and not yet unloaded or left
+    /// behind.
+    pub chunks: HashSet<(i32, i32)>,
+    /// How many chunks out from the player the server sends the world, once they have joined.
+    pub view_distance: Option<i32>,
+    /// Whether the area around the centre chunk has been received yet.
+    pub loaded: bool,
+    /// The commands the server last said the player may send.
+    pub commands: Commands,
+    /// The player's health, food, and saturation, once the server has sent them.
+    pub health: Option<SetHealth>,
+    /// The player's experience level and progress, once the server has sent them.
+    pub experience: Option<SetExperience>,
+    /// Where the server last teleported the player to, once it has.
+    pub position: Option<PlayerPosition>,
+    /// Whether the server said it kicks players who send unsigned chat messages.
+    pub enforces_secure_chat: bool,
+    /// The message of the day and icon the server last sent during play, if it has.
+    pub server_data: Option<ServerData>,
+    /// The state of each block the server has updated since the player joined, by position
+    /// (blocks sent with their chunk are not tracked). Blocks are forgotten with their chunk.
+    pub blocks: HashMap<Position, i32>,
+    /// The sequence number of the latest of the player's block changes the server has handled.
+    pub acknowledged_sequence: Option<i32>,
+    /// The height of the player's dimension, once they have joined it (if the server sent its
+    /// dimension type during configuration).
+    pub dimension: Option<DimensionType>,
+    /// The name of the player's dimension (e.g., `minecraft:the_nether`), once they have joined.
+    pub dimension_name: Option<String>,
+    /// Where players spawn without a bed, once the server has said.
+    pub spawn: Option<Position>,
+    /// The player's game mode, once the server has changed it (or respawned the player).
+    pub game_mode: Option<GameMode>,
+    /// Whether it is raining.
+    pub raining: bool,
+    /// The player's own entity id, once they have joined.
+    pub player_entity_id: Option<i32>,
+    /// The entity id of the vehicle the player is riding, if they are riding one.
+    pub vehicle: Option<i32>,
+}
+
+impl World {
+    /// Moves the entity with the given `entity_id` by `offset` blocks along each axis, turning it
+    /// to the `(yaw, pitch)` of `rotation` if there is one.
+    ///
+    /// Entities which are not being tracked are ignored.
+    pub fn move_entity(
+        &mut self,
+        entity_id: i32,
+        offset: [f64; 3],
+        rotation: Option<(Angle, Angle)>,
+        on_ground: bool,
+    ) {
+        let Some(entity) = self.entities.get_mut(&entity_id) else {
+            return;
+        };
+
+        entity.x += offset[0];
+        entity.y += offset[1];
+        entity.z += offset[2];
+        if let Some((yaw, pitch)) = rotation {
+            entity.yaw = yaw;
+            entity.pitch = pitch;
+        }
+        entity.on_ground = on_ground;
+    }
+
+    /// Teleports the player as the server asked in `sync`, relative to where they were for the
+    /// fields it marks as relative.
+    pub fn teleport(&mut self, sync: &SynchronizePlayerPosition) {
+        let current = self.position.unwrap_or_default();
+        let relative = |flag: u8| sync.flags & flag != 0;
+        let offset =
+            |flag, from: f64, value: f64| if relative(flag) { from + value } else { value };
+        let angle = |flag, from: f32, value: f32| if relative(flag) { from + value } else { value };
+
+        self.position = Some(PlayerPosition {
+            x: offset(RELATIVE_X, current.x, sync.x),
+            y: offset(RELATIVE_Y, current.y, sync.y),
+            z: offset(RELATIVE_Z, current.z, sync.z),
+            yaw: angle(RELATIVE_YAW, current.yaw, sync.yaw),
+            pitch: angle(RELATIVE_PITCH, current.pitch, sync.pitch),
+        });
+    }
+
+    /// Records who rides a vehicle, as the server said in `set`, noting whether the player got on
+    /// or off it.
+    pub fn set_passengers(&mut self, set: &SetPassengers) {
+        let riding = self
+            .player_entity_id
+            .is_some_and(|player| set.passengers.contains(&player));
+
+        if riding {
+            self.vehicle = Some(set.vehicle_id);
+        } else if self.vehicle == Some(set.vehicle_id) {
+            self.vehicle = None;
+        }
+    }
+
+    /// Stops tracking the entities with the given `entity_ids`, getting the player off their
+    /// vehicle if it is one of them.
+    pub fn remove_entities(&mut self, entity_ids: &[i32]) {
+        for entity_id in entity_ids {
+            self.entities.remove(entity_id);
+        }
+        if self
+            .vehicle
+            .is_some_and(|vehicle| entity_ids.contains(&vehicle))
+        {
+            self.vehicle = None;
+        }
+    }
+
+    /// Resets the world as the player respawns into the dimension of `respawn`, whose height is
+    /// `dimension` (if the server sent its dimension type).
+    ///
+    /// The chunks, blocks, entities, and weather of the old dimension are cleared when the
+    /// dimension changes, as the server sends the new one afresh. Either way, the player is
+    /// dismounted, and where they are is unknown until the server teleports them.
+    pub fn respawn(&mut self, respawn: &Respawn, dimension: Option<DimensionType>) {
+        if self.dimension_name.as_deref() != Some(respawn.dimension_name.as_str()) {
+            self.chunks.clear();
+            self.loaded = false;
+            self.blocks.clear();
+            self.entities.clear();
+            self.raining = false;
+        }
+
+        self.dimension = dimension;
+        self.dimension_name = Some(respawn.dimension_name.clone());
+        self.game_mode = Some(respawn.game_mode);
+        self.vehicle = None;
+        self.position = None;
+    }
+
+    /// Moves the centre of the loaded area to `center_chunk`, forgetting the chunks (and their
+    /// blocks) which are now out of range of it.
+    pub fn set_center_chunk(&mut self, center_chunk: (i32, i32)) {
+        self.center_chunk = center_chunk;
+
+        let Some(view_distance) = self.view_distance else {
+            return;
+        };
+        let range = view_distance.max(LOADED_RADIUS) + KEPT_CHUNK_MARGIN;
+        let in_range = |(x, z): (i32, i32)| {
+            (x - center_chunk.0).abs() <= range && (z - center_chunk.1).abs() <= range
+        };
+
+        self.chunks.retain(|chunk| in_range(*chunk));
+        self.blocks
+            .retain(|position, _| in_range(chunk_of(*position)));
+    }
+
+    /// Forgets the chunk at `chunk`, and the blocks in it, as the server unloaded it.
+    pub fn unload_chunk(&mut self, chunk: (i32, i32)) {
+        self.chunks.remove(&chunk);
+        self.blocks
+            .retain(|position, _| chunk_of(*position) != chunk);
+    }
+
+    /// Whether the player is riding a vehicle, in which case the vehicle moves them rather than
+    /// their own movement.
+    pub fn is_mounted(&self) -> bool {
+        self.vehicle.is_some()
+    }
+
+    /// Whether every chunk near the centre chunk has been received, which is taken as the
+    /// point at which the world around the player has loaded.
+    pub fn is_area_loaded(&self) -> bool {
+        let (center_x, center_z) = self.center_chunk;
+
+        (-LOADED_RADIUS..=LOADED_RADIUS).all(|x| {
+            (-LOADED_RADIUS..=LOADED_RADIUS)
+                .all(|z| self.chunks.contains(&(center_x + x, center_z + z)))
+        })
+    }
+}
+
+/// Finds the chunk holding the block at `position`.
+fn chunk_of(position: Position) -> (i32, i32) {
+    (position.x >> 4, position.z >> 4)
+}
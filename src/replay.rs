@@ -0,0 +1,282 @@
+use std::io::{self, Read, Write};
+
+use crate::protocol::{
+    capture::{CaptureReader, Direction, Record},
+    chat::ChatComponent,
+    encoding::{Decode, VarInt},
+    packets::{configuration, login, play, ServerStatus},
+    State,
+};
+
+/// How many packets of a replay were decoded.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// Packets decoded successfully.
+    pub decoded: usize,
+    /// Packets which failed to decode.
+    pub failed: usize,
+    /// Packets with no decoder.
+    pub unknown: usize,
+}
+
+/// Runs every packet of a capture through its decoder, writing the decoded fields (or the
+/// decoding error) of each to `out`.
+pub fn replay<R, W>(mut capture: CaptureReader<R>, mut out: W) -> io::Result<ReplaySummary>
+where
+    R: Read,
+    W: Write,
+{
+    let protocol_version = capture.protocol_version();
+    let mut summary = ReplaySummary::default();
+
+    while let Some(record) = capture.read()? {
+        let direction = match record.direction {
+            Direction::Clientbound => "clientbound",
+            Direction::Serverbound => "serverbound",
+        };
+        write!(
+            out,
+            "[{direction} {} 0x{:02x}] ",
+            record.state,
+            record.packet.id()
+        )?;
+
+        match decode(&record, protocol_version) {
+            Some(Ok(decoded)) => {
+                summary.decoded += 1;
+                writeln!(out, "{decoded}")?;
+            }
+            Some(Err(e)) => {
+                summary.failed += 1;
+                writeln!(out, "failed to decode: {e}")?;
+            }
+            None => {
+                summary.unknown += 1;
+                writeln!(out, "unknown packet ({} bytes)", record.packet.data().len())?;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Decodes a single captured packet into a description, or `None` if there is no decoder for it.
+fn decode(record: &Record, protocol_version: i32) -> Option<io::Result<String>> {
+    let data = record.packet.data();
+    let id = record.packet.id();
+
+    let decoded = match (record.direction, record.state, id) {
+        (Direction::Serverbound, State::Handshaking, 0x00) => decode_handshake(data),
+        (Direction::Clientbound, State::Status, 0x00) => debug(ServerStatus::decode(data)),
+        (Direction::Clientbound, State::Login, login::DISCONNECT_ID) => {
+            String::decode(&mut &data[..])
+                .map(|reason| format!("Disconnect {{ reason: {reason} }}"))
+        }
+        (Direction::Clientbound, State::Login, login::LOGIN_SUCCESS_ID) => {
+            debug(login::LoginSuccess::decode(data, protocol_version))
+        }
+        (Direction::Clientbound, State::Login, login::SET_COMPRESSION_ID) => {
+            login::SetCompression::decode(data)
+                .map(|p| format!("SetCompression {{ threshold: {} }}", p.threshold))
+        }
+        (Direction::Clientbound, State::Configuration, _) => {
+            match configuration::Clientbound::from_id(id, protocol_version)? {
+                configuration::Clientbound::KeepAlive => {
+                    configuration::KeepAlive::decode(data, protocol_version)
+                        .map(|p| format!("KeepAlive {{ id: {} }}", p.id))
+                }
+                configuration::Clientbound::Disconnect => {
+                    debug(configuration::Disconnect::decode(data, protocol_version))
+                }
+                configuration::Clientbound::FeatureFlags => {
+                    debug(configuration::FeatureFlags::decode(data))
+                }
+                configuration::Clientbound::UpdateTags => {
+                    debug(configuration::UpdateTags::decode(data))
+                }
+                kind => Ok(format!("{kind:?}")),
+            }
+        }
+        (Direction::Clientbound, State::Play, _) => decode_play(id, data, protocol_version)?,
+        _ => return None,
+    };
+
+    Some(decoded)
+}
+
+/// Decodes a clientbound packet of the Play state, or returns `None` if it is not one we handle.
+fn decode_play(id: i32, data: &[u8], protocol_version: i32) -> Option<io::Result<String>> {
+    Some(match play::Clientbound::from_id(id, protocol_version)? {
+        play::Clientbound::BundleDelimiter => Ok(String::from("BundleDelimiter")),
+        play::Clientbound::AcknowledgeBlockChange => {
+            debug(play::AcknowledgeBlockChange::decode(data))
+        }
+        play::Clientbound::BlockUpdate => debug(play::BlockUpdate::decode(data)),
+        play::Clientbound::SpawnEntity => debug(play::SpawnEntity::decode(data)),
+        play::Clientbound::Disconnect => ChatComponent::decode(&mut &data[..], protocol_version)
+            .map(|reason| format!("Disconnect {{ reason: {reason:?} }}")),
+        play::Clientbound::KeepAlive => play::KeepAlive::decode(data, protocol_version)
+            .map(|p| format!("KeepAlive {{ id: {} }}", p.id)),
+        play::Clientbound::Ping => {
+            play::Ping::decode(data, protocol_version).map(|p| format!("Ping {{ id: {} }}", p.id))
+        }
+        play::Clientbound::Login => debug(play::Login::decode(data)),
+        play::Clientbound::Respawn => debug(play::Respawn::decode(data)),
+        play::Clientbound::ChunkData => debug(play::ChunkData::decode(data)),
+        play::Clientbound::PlayerInfoUpdate => {
+            debug(play::PlayerInfoUpdate::decode(data, protocol_version))
+        }
+        play::Clientbound::PlayerInfoRemove => debug(play::PlayerInfoRemove::decode(data)),
+        play::Clientbound::OpenScreen => debug(play::OpenScreen::decode(data, protocol_version)),
+        play::Clientbound::SetContainerSlot => {
+            debug(play::SetContainerSlot::decode(data, protocol_version))
+        }
+        play::Clientbound::CommandSuggestions => {
+            debug(play::CommandSuggestions::decode(data, protocol_version))
+        }
+        play::Clientbound::Commands => debug(play::Commands::decode(data, protocol_version)),
+        play::Clientbound::UpdateEntityPosition => debug(play::UpdateEntityPosition::decode(data)),
+        play::Clientbound::UpdateEntityPositionAndRotation => {
+            debug(play::UpdateEntityPositionAndRotation::decode(data))
+        }
+        play::Clientbound::UpdateEntityRotation => debug(play::UpdateEntityRotation::decode(data)),
+        play::Clientbound::PlayerChat => debug(play::PlayerChat::decode(data)),
+        play::Clientbound::CombatDeath => debug(play::CombatDeath::decode(data, protocol_version)),
+        play::Clientbound::SynchronizePlayerPosition => {
+            debug(play::SynchronizePlayerPosition::decode(data))
+        }
+        play::Clientbound::ResourcePack => {
+            debug(play::ResourcePack::decode(data, protocol_version))
+        }
+        play::Clientbound::SetExperience => debug(play::SetExperience::decode(data)),
+        play::Clientbound::Statistics => debug(play::Statistics::decode(data)),
+        play::Clientbound::GameEvent => debug(play::GameEvent::decode(data)),
+        play::Clientbound::UpdateTags => debug(configuration::UpdateTags::decode(data)),
+        play::Clientbound::RemoveEntities => debug(play::RemoveEntities::decode(data)),
+        play::Clientbound::SetPassengers => debug(play::SetPassengers::decode(data)),
+        play::Clientbound::SetHealth => debug(play::SetHealth::decode(data)),
+        play::Clientbound::ServerData => debug(play::ServerData::decode(data, protocol_version)),
+        play::Clientbound::SetDefaultSpawnPosition => {
+            debug(play::SetDefaultSpawnPosition::decode(data))
+        }
+        play::Clientbound::SetCenterChunk => debug(play::SetCenterChunk::decode(data)),
+        // the title packets hold nothing but their text
+        kind @ (play::Clientbound::SetTitleText
+        | play::Clientbound::SetSubtitleText
+        | play::Clientbound::SetActionBarText) => {
+            ChatComponent::decode(&mut &data[..], protocol_version)
+                .map(|text| format!("{kind:?} {{ text: {text:?} }}"))
+        }
+        play::Clientbound::SystemChat => debug(play::SystemChat::decode(data, protocol_version)),
+        play::Clientbound::UnloadChunk => debug(play::UnloadChunk::decode(data)),
+    })
+}
+
+/// Formats a `decoded` packet as it is printed, by its debug representation.
+fn debug<T: std::fmt::Debug>(decoded: io::Result<T>) -> io::Result<String> {
+    decoded.map(|packet| format!("{packet:?}"))
+}
+
+/// Decodes the fields of a serverbound handshake.
+fn decode_handshake(mut data: &[u8]) -> io::Result<String> {
+    let protocol_version = i32::from(VarInt::decode(&mut data)?);
+    let server_address = String::decode(&mut data)?;
+    let server_port = u16::decode(&mut data)?;
+    let next_state = i32::from(VarInt::decode(&mut data)?);
+
+    Ok(format!("Handshake {{ protocol_version: {protocol_version}, server_address: {server_address:?}, server_port: {server_port}, next_state: {next_state} }}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{replay, ReplaySummary};
+    use crate::protocol::{
+        capture::{test::SharedBuffer, CaptureReader, CaptureWriter, Direction},
+        encoding::EncodedString,
+        nbt::test::{compound, network, string},
+        packets::{play, Handshake},
+        Packet, State,
+    };
+
+    #[test]
+    fn replays_status_capture() {
+        let buffer = SharedBuffer::default();
+        let mut writer = CaptureWriter::new(buffer.clone(), -1, true).unwrap();
+
+        let handshake: Packet = Handshake::new(-1, "localhost".to_string(), 25565, true)
+            .unwrap()
+            .into();
+        let json = r#"{"version":{"name":"1.20.2","protocol":764},"description":"hi"}"#;
+        let response = Packet::new(
+            0x00,
+            EncodedString::try_from(json.to_string())
+                .unwrap()
+                .as_slice(),
+        );
+        let records = [
+            (Direction::Serverbound, State::Handshaking, handshake),
+            (Direction::Clientbound, State::Status, response),
+            // a truncated status response
+            (
+                Direction::Clientbound,
+                State::Status,
+                Packet::new(0x00, vec![0xff]),
+            ),
+            (
+                Direction::Clientbound,
+                State::Status,
+                Packet::new(0x05, vec![]),
+            ),
+        ];
+        for (direction, state, packet) in &records {
+            writer.write(*direction, *state, packet).unwrap();
+        }
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let mut out = Vec::new();
+        let summary = replay(CaptureReader::new(bytes.as_slice()).unwrap(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(
+            summary,
+            ReplaySummary {
+                decoded: 2,
+                failed: 1,
+                unknown: 1
+            }
+        );
+        assert_eq!(
+            lines[0],
+            r#"[serverbound handshaking 0x00] Handshake { protocol_version: -1, server_address: "localhost", server_port: 25565, next_state: 1 }"#
+        );
+        assert!(lines[1].starts_with("[clientbound status 0x00] ServerStatus {"));
+        assert!(lines[1].contains(r#"name: "1.20.2""#));
+        assert!(lines[2].starts_with("[clientbound status 0x00] failed to decode:"));
+        assert_eq!(
+            lines[3],
+            "[clientbound status 0x05] unknown packet (0 bytes)"
+        );
+    }
+
+    #[test]
+    fn replays_nbt_disconnect() {
+        let buffer = SharedBuffer::default();
+        let mut writer = CaptureWriter::new(buffer.clone(), 765, true).unwrap();
+
+        let reason = network(compound(vec![("text", string("Kicked"))]));
+        let disconnect = Packet::new(play::Clientbound::Disconnect.id(765), reason);
+        writer
+            .write(Direction::Clientbound, State::Play, &disconnect)
+            .unwrap();
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let mut out = Vec::new();
+        let summary = replay(CaptureReader::new(bytes.as_slice()).unwrap(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(summary.decoded, 1, "{out}");
+        assert!(out.starts_with("[clientbound play 0x1b] Disconnect { reason: "));
+        assert!(out.contains(r#""Kicked""#));
+    }
+}
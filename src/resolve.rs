@@ -0,0 +1,175 @@
+//! Resolves server addresses to the host and port to connect to, following `_minecraft._tcp`
+//! SRV records as the vanilla client does.
+
+use std::{future::Future, net::SocketAddr};
+
+use hickory_resolver::TokioAsyncResolver;
+use serde::{Deserialize, Serialize};
+
+use crate::util::split_server_address;
+pub use crate::util::DEFAULT_PORT;
+
+/// Which addresses of a host are connected to, for hosts with both IPv4 and IPv6 addresses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    /// Any address, in the order they were resolved, so either family is tried if the other
+    /// can not be reached.
+    #[default]
+    Auto,
+    /// Only IPv4 addresses.
+    V4,
+    /// Only IPv6 addresses.
+    V6,
+}
+
+impl AddressFamily {
+    /// Keeps the `addresses` of this family, in the order they were resolved.
+    pub fn filter<I>(self, addresses: I) -> Vec<SocketAddr>
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        addresses
+            .into_iter()
+            .filter(|address| match self {
+                Self::Auto => true,
+                Self::V4 => address.is_ipv4(),
+                Self::V6 => address.is_ipv6(),
+            })
+            .collect()
+    }
+}
+
+/// A way of looking up SRV records.
+pub trait SrvLookup {
+    /// Looks up the SRV record `name`, returning the target host and port of the first record,
+    /// if there is one.
+    fn lookup(&self, name: &str) -> impl Future<Output = Option<(String, u16)>>;
+}
+
+/// Looks up SRV records with the DNS resolver configured on the system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl SrvLookup for SystemResolver {
+    async fn lookup(&self, name: &str) -> Option<(String, u16)> {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf().ok()?;
+        let records = resolver.srv_lookup(name).await.ok()?;
+
+        records.iter().next().map(|record| {
+            let target = record.target().to_utf8();
+            (target.trim_end_matches('.').to_string(), record.port())
+        })
+    }
+}
+
+/// Resolves `server_address` to the host and port to connect to.
+///
+/// An address with a port is used as-is. Otherwise, if `resolve_srv` is set, the server's
+/// `_minecraft._tcp` SRV record is followed, falling back to the [`DEFAULT_PORT`] of the host.
+///
+/// Fails if the address is malformed, see [`util::parse_server_address`](crate::util::parse_server_address).
+pub async fn resolve<L: SrvLookup>(
+    lookup: &L,
+    server_address: &str,
+    resolve_srv: bool,
+) -> Result<(String, u16), std::io::Error> {
+    let (host, port) = split_server_address(server_address)?;
+    if let Some(port) = port {
+        return Ok((host.to_string(), port));
+    }
+
+    if resolve_srv {
+        if let Some(target) = lookup.lookup(&format!("_minecraft._tcp.{host}")).await {
+            return Ok(target);
+        }
+    }
+
+    Ok((host.to_string(), DEFAULT_PORT))
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use std::net::SocketAddr;
+
+    use super::{resolve, AddressFamily, SrvLookup};
+
+    /// A lookup with a single SRV record, which remembers every name it was asked for.
+    #[derive(Default)]
+    struct FakeLookup {
+        names: RefCell<Vec<String>>,
+    }
+
+    impl SrvLookup for FakeLookup {
+        async fn lookup(&self, name: &str) -> Option<(String, u16)> {
+            self.names.borrow_mut().push(name.to_string());
+
+            (name == "_minecraft._tcp.example.com").then(|| ("mc.example.com".to_string(), 25570))
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_srv_records() {
+        let lookup = FakeLookup::default();
+
+        assert_eq!(
+            resolve(&lookup, "example.com", true).await.unwrap(),
+            ("mc.example.com".to_string(), 25570)
+        );
+        assert_eq!(
+            resolve(&lookup, "example.org", true).await.unwrap(),
+            ("example.org".to_string(), 25565)
+        );
+        assert_eq!(
+            *lookup.names.borrow(),
+            ["_minecraft._tcp.example.com", "_minecraft._tcp.example.org"]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_srv_lookup_when_disabled() {
+        let lookup = FakeLookup::default();
+
+        assert_eq!(
+            resolve(&lookup, "example.com", false).await.unwrap(),
+            ("example.com".to_string(), 25565)
+        );
+        assert!(lookup.names.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn uses_explicit_port() {
+        let lookup = FakeLookup::default();
+
+        assert_eq!(
+            resolve(&lookup, "example.com:25566", true).await.unwrap(),
+            ("example.com".to_string(), 25566)
+        );
+        assert_eq!(
+            resolve(&lookup, "[::1]:25566", true).await.unwrap(),
+            ("::1".to_string(), 25566)
+        );
+        assert_eq!(
+            resolve(&lookup, "::1", false).await.unwrap(),
+            ("::1".to_string(), 25565)
+        );
+        assert!(lookup.names.borrow().is_empty());
+    }
+
+    #[test]
+    fn filters_addresses_by_family() {
+        let addresses: Vec<SocketAddr> = ["[2001:db8::1]:25565", "192.0.2.1:25565", "[::1]:25565"]
+            .iter()
+            .map(|address| address.parse().unwrap())
+            .collect();
+
+        assert_eq!(
+            AddressFamily::V4.filter(addresses.clone()),
+            ["192.0.2.1:25565".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(AddressFamily::V6.filter(addresses.clone()).len(), 2);
+        assert_eq!(AddressFamily::Auto.filter(addresses.clone()), addresses);
+    }
+}
@@ -0,0 +1,198 @@
+//! Small helpers shared by the rest of the client.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// The port a server listens on when the address does not give one.
+pub const DEFAULT_PORT: u16 = 25565;
+
+/// Parses a server address of the form `host` or `host:port` into its host and port, defaulting
+/// to the [`DEFAULT_PORT`].
+///
+/// The host is a hostname, an IPv4 address, or an IPv6 address, which must be wrapped in
+/// brackets (e.g., `[::1]:25565`) to be given a port.
+pub fn parse_server_address(input: &str) -> Result<(String, u16), io::Error> {
+    let (host, port) = split_server_address(input)?;
+
+    Ok((host.to_string(), port.unwrap_or(DEFAULT_PORT)))
+}
+
+/// Splits a server address into its host and its port, if it gives one, as
+/// [`parse_server_address`] does.
+pub fn split_server_address(input: &str) -> Result<(&str, Option<u16>), io::Error> {
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid server address '{input}': {reason}"),
+        )
+    };
+
+    let (host, port) = if let Some(rest) = input.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| invalid("missing closing bracket"))?;
+        match rest {
+            "" => (host, None),
+            _ => (
+                host,
+                Some(
+                    rest.strip_prefix(':')
+                        .ok_or_else(|| invalid("expected a port after the bracket"))?,
+                ),
+            ),
+        }
+    } else {
+        match input.split_once(':') {
+            // a bare IPv6 address, which can not have a port
+            Some((_, rest)) if rest.contains(':') => (input, None),
+            Some((host, port)) => (host, Some(port)),
+            None => (input, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+    let port = port
+        .map(|port| {
+            port.parse()
+                .map_err(|_| invalid(&format!("'{port}' is not a port")))
+        })
+        .transpose()?;
+
+    Ok((host, port))
+}
+
+/// Writes `output` to the file at `path`, creating it and any missing parent directories, and
+/// either adding to the end of an existing file if `append` is set or replacing it otherwise.
+pub fn write_output(path: &Path, output: &str, append: bool) -> io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    file.write_all(output.as_bytes())
+}
+
+/// How many bytes are on each line of a [`hex_dump`].
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// Formats `data` as a hex dump, each line giving the offset of its bytes, the bytes in hex,
+/// and the bytes as ASCII (with `.` for those which are not printable).
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, bytes) in data.chunks(HEX_DUMP_WIDTH).enumerate() {
+        let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        let ascii: String = bytes
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    char::from(byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        // writing to a string can not fail
+        let _ = writeln!(
+            dump,
+            "{:08x}  {:<width$}  |{ascii}|",
+            line * HEX_DUMP_WIDTH,
+            hex.join(" "),
+            width = HEX_DUMP_WIDTH * 3 - 1
+        );
+    }
+
+    dump
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hex_dump, parse_server_address, write_output};
+    use crate::protocol::packets::ServerStatus;
+
+    #[test]
+    fn parses_server_addresses() {
+        for (input, host, port) in [
+            ("example.com", "example.com", 25565),
+            ("example.com:25566", "example.com", 25566),
+            ("192.168.1.2", "192.168.1.2", 25565),
+            ("192.168.1.2:25566", "192.168.1.2", 25566),
+            ("::1", "::1", 25565),
+            ("[::1]", "::1", 25565),
+            ("[::1]:25566", "::1", 25566),
+        ] {
+            assert_eq!(
+                parse_server_address(input).unwrap(),
+                (host.to_string(), port),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn dumps_bytes_as_hex() {
+        assert_eq!(
+            hex_dump(b"\x00\x01Hello, world!\xff\x7f!"),
+            "\
+00000000  00 01 48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 ff  |..Hello, world!.|
+00000010  7f 21                                            |.!|
+"
+        );
+        assert_eq!(hex_dump(&[]), "");
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        for input in [
+            "example.com:port",
+            "example.com:65536",
+            "example.com:",
+            "[::1",
+            "[::1]25566",
+            "",
+            ":25565",
+        ] {
+            assert!(parse_server_address(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn writes_output_to_file() {
+        let directory = std::env::temp_dir().join(format!(
+            "minecraft-console-client-test-output-{}",
+            std::process::id()
+        ));
+        let path = directory.join("results").join("status.json");
+        let status: ServerStatus =
+            serde_json::from_str(r#"{"version":{"name":"1.20.2","protocol":764}}"#).unwrap();
+        let json = format!("{}\n", status.to_json());
+
+        // the parent directories are created
+        write_output(&path, &json, false).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let read: ServerStatus = serde_json::from_str(&written).unwrap();
+        assert_eq!(read.version.protocol, 764);
+
+        write_output(&path, &json, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), json.repeat(2));
+
+        write_output(&path, &json, false).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), json);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}